@@ -0,0 +1,623 @@
+use anyhow::{anyhow, Result};
+use borsh::BorshDeserialize;
+use crate::model::{AccountField, Metadata};
+use crate::programs::{BUBBLEGUM_PROGRAM, CORE_PROGRAM, TOKEN_AUTH_RULES_PROGRAM, TOKEN_METADATA_PROGRAM};
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+
+// Longest string field we believe; anything bigger means we're reading garbage.
+const MAX_STRING_LEN: usize = 10_000;
+
+// Walks account data front to back, recording every field it reads with the byte
+// offset it started at, so a failure can say exactly where decoding went wrong.
+pub struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    pub fields: Vec<AccountField>,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Reader { data, pos: 0, fields: Vec::new() }
+    }
+
+    pub fn offset(&self) -> usize {
+        self.pos
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    // Jump to an offset another field points at. Offsets stay relative to the
+    // start of the account.
+    pub fn seek(&mut self, name: &str, offset: usize) -> Result<()> {
+        if offset > self.data.len() {
+            return Err(anyhow!("{} points to byte {}, past the end of the {}-byte account", name, offset, self.data.len()));
+        }
+        self.pos = offset;
+        Ok(())
+    }
+
+    fn take(&mut self, name: &str, len: usize) -> Result<&'a [u8]> {
+        if self.remaining() < len {
+            return Err(anyhow!("{} at byte {}: needs {} bytes, only {} left", name, self.pos, len, self.remaining()));
+        }
+        let bytes = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn record(&mut self, name: &str, offset: usize, value: String) {
+        self.fields.push(AccountField { name: name.to_string(), offset: Some(offset), value });
+    }
+
+    pub fn u8(&mut self, name: &str) -> Result<u8> {
+        let offset = self.pos;
+        let value = self.take(name, 1)?[0];
+        self.record(name, offset, value.to_string());
+        Ok(value)
+    }
+
+    pub fn u16(&mut self, name: &str) -> Result<u16> {
+        let offset = self.pos;
+        let value = u16::from_le_bytes(self.take(name, 2)?.try_into().expect("2 bytes"));
+        self.record(name, offset, value.to_string());
+        Ok(value)
+    }
+
+    pub fn u32(&mut self, name: &str) -> Result<u32> {
+        let offset = self.pos;
+        let value = u32::from_le_bytes(self.take(name, 4)?.try_into().expect("4 bytes"));
+        self.record(name, offset, value.to_string());
+        Ok(value)
+    }
+
+    pub fn u64(&mut self, name: &str) -> Result<u64> {
+        let offset = self.pos;
+        let value = u64::from_le_bytes(self.take(name, 8)?.try_into().expect("8 bytes"));
+        self.record(name, offset, value.to_string());
+        Ok(value)
+    }
+
+    pub fn bool(&mut self, name: &str) -> Result<bool> {
+        let offset = self.pos;
+        let value = match self.take(name, 1)?[0] {
+            0 => false,
+            1 => true,
+            other => {
+                self.pos = offset;
+                return Err(anyhow!("{} at byte {}: {} is not a valid bool", name, offset, other));
+            }
+        };
+        self.record(name, offset, value.to_string());
+        Ok(value)
+    }
+
+    pub fn pubkey(&mut self, name: &str) -> Result<Pubkey> {
+        let offset = self.pos;
+        let value = Pubkey::try_from(self.take(name, 32)?).expect("32 bytes");
+        self.record(name, offset, value.to_string());
+        Ok(value)
+    }
+
+    // Borsh string: u32 length, then UTF-8. Metadata pads with NULs, which are dropped.
+    pub fn string(&mut self, name: &str) -> Result<String> {
+        let offset = self.pos;
+        let len = u32::from_le_bytes(self.take(name, 4)?.try_into().expect("4 bytes")) as usize;
+        // Failures leave the cursor on the field, so the offset reported is where it starts
+        if len > MAX_STRING_LEN {
+            self.pos = offset;
+            return Err(anyhow!("{} at byte {}: length prefix {} is not plausible", name, offset, len));
+        }
+        let Ok(bytes) = self.take(name, len) else {
+            let left = self.remaining();
+            self.pos = offset;
+            return Err(anyhow!("{} at byte {}: length prefix says {} bytes, only {} left", name, offset, len, left));
+        };
+        let value = String::from_utf8(bytes.to_vec()).map_err(|e| {
+            self.pos = offset;
+            anyhow!("{} at byte {}: not valid UTF-8 ({})", name, offset + 4, e)
+        })?;
+        let value = value.trim_end_matches('\0').to_string();
+        self.record(name, offset, format!("{:?}", value));
+        Ok(value)
+    }
+
+    pub fn bytes(&mut self, name: &str, len: usize) -> Result<&'a [u8]> {
+        let offset = self.pos;
+        let bytes = self.take(name, len)?;
+        self.record(name, offset, format!("{} bytes", len));
+        Ok(bytes)
+    }
+
+    // Borsh Option: a 0/1 tag, then the value when present.
+    pub fn option<T>(&mut self, name: &str, read: impl FnOnce(&mut Self, &str) -> Result<T>) -> Result<Option<T>> {
+        let offset = self.pos;
+        match self.take(name, 1)?[0] {
+            0 => {
+                self.record(name, offset, "None".to_string());
+                Ok(None)
+            }
+            1 => read(self, name).map(Some),
+            tag => {
+                self.pos = offset;
+                Err(anyhow!("{} at byte {}: option tag {} is neither 0 nor 1", name, offset, tag))
+            }
+        }
+    }
+}
+
+// What a decoder made of an account.
+pub struct Decoded {
+    pub kind: String,
+    pub fields: Vec<AccountField>,
+}
+
+// Decode raw data for the programs we know the layouts of. None when the owner or
+// the account type isn't one of them; an error when it is but the data doesn't fit.
+pub fn decode(owner: &str, data: &[u8]) -> Option<Result<Decoded>> {
+    let mut reader = Reader::new(data);
+    let kind = match owner {
+        TOKEN_METADATA_PROGRAM => token_metadata(&mut reader, data)?,
+        CORE_PROGRAM => core(&mut reader, data)?,
+        BUBBLEGUM_PROGRAM => tree_config(&mut reader, data)?,
+        TOKEN_AUTH_RULES_PROGRAM => rule_set(&mut reader),
+        _ => return None,
+    };
+    Some(kind.map(|kind| Decoded { kind, fields: reader.fields }))
+}
+
+fn token_metadata(r: &mut Reader, data: &[u8]) -> Option<Result<String>> {
+    let kind = match data.first()? {
+        4 => {
+            return Some(decode_metadata(data).map(|metadata| {
+                r.fields = metadata_fields(&metadata);
+                "Metadata".to_string()
+            }))
+        }
+        1 => "Edition",
+        2 => "Master Edition (v1)",
+        6 => "Master Edition",
+        7 => "Edition Marker",
+        8 => "Use Authority Record",
+        9 => "Collection Authority Record",
+        11 => "Token Record",
+        12 => "Metadata Delegate Record",
+        14 => "Holder Delegate Record",
+        _ => return None,
+    };
+    let result = (|| -> Result<()> {
+        match r.u8("key")? {
+            1 => {
+                r.pubkey("parent")?;
+                r.u64("edition")?;
+            }
+            2 | 6 => {
+                r.u64("supply")?;
+                r.option("max_supply", |r, n| r.u64(n))?;
+            }
+            7 => {
+                let ledger = r.bytes("ledger", 31)?;
+                let printed: u32 = ledger.iter().map(|b| b.count_ones()).sum();
+                r.fields.push(AccountField { name: "editions_marked".to_string(), offset: Some(1), value: printed.to_string() });
+            }
+            8 => {
+                r.u64("allowed_uses")?;
+                r.u8("bump")?;
+            }
+            9 => {
+                r.u8("bump")?;
+                r.option("update_authority", |r, n| r.pubkey(n))?;
+            }
+            11 => {
+                r.u8("bump")?;
+                let offset = r.offset();
+                let state = r.u8("state")?;
+                name_last(r, offset, &["Unlocked", "Locked", "Listed"], state);
+                r.option("rule_set_revision", |r, n| r.u64(n))?;
+                r.option("delegate", |r, n| r.pubkey(n))?;
+                let offset = r.offset();
+                if let Some(role) = r.option("delegate_role", |r, n| r.u8(n))? {
+                    name_last(r, offset + 1, &["Sale", "Transfer", "Utility", "Staking", "Standard", "LockedTransfer", "Migration"], role);
+                }
+                r.option("locked_transfer", |r, n| r.pubkey(n))?;
+            }
+            _ => {
+                r.u8("bump")?;
+                r.pubkey("mint")?;
+                r.pubkey("delegate")?;
+                r.pubkey("update_authority")?;
+            }
+        }
+        Ok(())
+    })();
+    Some(result.map(|_| kind.to_string()))
+}
+
+// Swap the number just recorded at `offset` for its enum name.
+fn name_last(r: &mut Reader, offset: usize, names: &[&str], value: u8) {
+    if let (Some(field), Some(name)) = (r.fields.iter_mut().rev().find(|f| f.offset == Some(offset)), names.get(value as usize)) {
+        field.value = format!("{} ({})", name, value);
+    }
+}
+
+fn core(r: &mut Reader, data: &[u8]) -> Option<Result<String>> {
+    let kind = match data.first()? {
+        1 => "Core Asset",
+        5 => "Core Collection",
+        _ => return None,
+    };
+    let result = (|| -> Result<()> {
+        if r.u8("key")? == 1 {
+            r.pubkey("owner")?;
+            let offset = r.offset();
+            match r.u8("update_authority")? {
+                0 => name_last(r, offset, &["None"], 0),
+                tag @ (1 | 2) => {
+                    name_last(r, offset, &["None", "Address", "Collection"], tag);
+                    r.pubkey("update_authority_address")?;
+                }
+                tag => return Err(anyhow!("update_authority at byte {}: unknown variant {}", offset, tag)),
+            }
+            r.string("name")?;
+            r.string("uri")?;
+            r.option("seq", |r, n| r.u64(n))?;
+        } else {
+            r.pubkey("update_authority")?;
+            r.string("name")?;
+            r.string("uri")?;
+            r.u32("num_minted")?;
+            r.u32("current_size")?;
+        }
+        if r.remaining() > 0 {
+            let offset = r.offset();
+            r.fields.push(AccountField { name: "plugins".to_string(), offset: Some(offset), value: format!("{} bytes", r.remaining()) });
+        }
+        Ok(())
+    })();
+    Some(result.map(|_| kind.to_string()))
+}
+
+// Anchor accounts start with sha256("account:<Name>")[..8].
+fn anchor_discriminator(name: &str) -> [u8; 8] {
+    let hash = solana_sdk::hash::hash(format!("account:{}", name).as_bytes());
+    hash.to_bytes()[..8].try_into().expect("8 bytes")
+}
+
+fn tree_config(r: &mut Reader, data: &[u8]) -> Option<Result<String>> {
+    if data.get(..8)? != anchor_discriminator("TreeConfig") {
+        return None;
+    }
+    let result = (|| -> Result<()> {
+        r.bytes("discriminator", 8)?;
+        r.pubkey("tree_creator")?;
+        r.pubkey("tree_delegate")?;
+        r.u64("total_mint_capacity")?;
+        r.u64("num_minted")?;
+        r.bool("is_public")?;
+        let offset = r.offset();
+        let state = r.u8("is_decompressible")?;
+        name_last(r, offset, &["Enabled", "Disabled"], state);
+        Ok(())
+    })();
+    Some(result.map(|_| "Bubblegum Tree Config".to_string()))
+}
+
+// Rule sets are MessagePack after a Borsh header, so only the header and the
+// revision map are read.
+fn rule_set(r: &mut Reader) -> Result<String> {
+    r.u8("key")?;
+    let location = r.u64("revision_map_location")? as usize;
+    r.seek("revision_map_location", location)?;
+    r.u8("revision_map_version")?;
+    let count = r.u32("revisions")? as usize;
+    // Only the newest of the u64 revision offsets is worth showing
+    if count > 0 {
+        let last = r.offset() + 8 * (count - 1);
+        r.seek("revisions", last)?;
+        r.u64("latest_revision_offset")?;
+    }
+    Ok("Rule Set".to_string())
+}
+
+pub fn decode_metadata(data: &[u8]) -> Result<Metadata> {
+    let mut slice: &[u8] = data;
+    Metadata::deserialize(&mut slice).map_err(|e| anyhow!("Failed to parse metadata from bytes: {}", e))
+}
+
+// The metadata layout read field by field, the same way Borsh reads it into
+// `model::Metadata`. Slower and only used to show where a broken account goes wrong.
+pub fn trace_metadata(r: &mut Reader) -> Result<()> {
+    r.u8("key")?;
+    r.pubkey("update_authority")?;
+    r.pubkey("mint")?;
+    r.string("data.name")?;
+    r.string("data.symbol")?;
+    r.string("data.uri")?;
+    r.u16("data.seller_fee_basis_points")?;
+    r.option("data.creators", |r, n| {
+        let count = r.u32(n)?;
+        for i in 0..count {
+            r.pubkey(&format!("{}[{}].address", n, i))?;
+            r.bool(&format!("{}[{}].verified", n, i))?;
+            r.u8(&format!("{}[{}].share", n, i))?;
+        }
+        Ok(())
+    })?;
+    r.bool("primary_sale_happened")?;
+    r.bool("is_mutable")?;
+    r.option("edition_nonce", |r, n| r.u8(n))?;
+    r.option("token_standard", |r, n| r.u8(n))?;
+    r.option("collection", |r, _| {
+        r.bool("collection.verified")?;
+        r.pubkey("collection.key")
+    })?;
+    r.option("uses", |r, _| {
+        r.u8("uses.use_method")?;
+        r.u64("uses.remaining")?;
+        r.u64("uses.total")
+    })?;
+    r.option("collection_details", |r, n| enum_v1(r, n, |r| r.u64("collection_details.size").map(|_| ())))?;
+    r.option("programmable_config", |r, n| {
+        enum_v1(r, n, |r| r.option("programmable_config.rule_set", |r, n| r.pubkey(n)).map(|_| ()))
+    })?;
+    Ok(())
+}
+
+// Both metadata enums have a single V1 variant.
+fn enum_v1(r: &mut Reader, name: &str, read: impl FnOnce(&mut Reader) -> Result<()>) -> Result<()> {
+    let offset = r.offset();
+    match r.u8(name)? {
+        0 => read(r),
+        tag => {
+            r.pos = offset;
+            Err(anyhow!("{} at byte {}: unknown variant {}", name, offset, tag))
+        }
+    }
+}
+
+// A decoded metadata account as name/value pairs. Borsh did the reading, so there
+// are no offsets.
+pub fn metadata_fields(metadata: &Metadata) -> Vec<AccountField> {
+    let field = |name: &str, value: String| AccountField { name: name.to_string(), offset: None, value };
+    let mut fields = vec![
+        field("update_authority", metadata.update_authority.to_string()),
+        field("mint", metadata.mint.to_string()),
+        field("name", metadata.data.name.trim_end_matches('\0').to_string()),
+        field("symbol", metadata.data.symbol.trim_end_matches('\0').to_string()),
+        field("uri", metadata.data.uri.trim_end_matches('\0').to_string()),
+        field("seller_fee_basis_points", metadata.data.seller_fee_basis_points.to_string()),
+    ];
+    for (i, creator) in metadata.data.creators.iter().flatten().enumerate() {
+        fields.push(field(&format!("creator_{}", i), format!("{} share {}%{}", creator.address, creator.share, if creator.verified { " verified" } else { "" })));
+    }
+    fields.push(field("primary_sale_happened", metadata.primary_sale_happened.to_string()));
+    fields.push(field("is_mutable", metadata.is_mutable.to_string()));
+    if let Some(standard) = metadata.token_standard {
+        let names = ["NonFungible", "FungibleAsset", "Fungible", "NonFungibleEdition", "ProgrammableNonFungible", "ProgrammableNonFungibleEdition"];
+        fields.push(field("token_standard", names.get(standard as usize).map(|n| n.to_string()).unwrap_or_else(|| standard.to_string())));
+    }
+    if let Some(collection) = &metadata.collection {
+        fields.push(field("collection", format!("{}{}", collection.key, if collection.verified { " verified" } else { " unverified" })));
+    }
+    fields
+}
+
+// Accounts the RPC node parsed for us (SPL Token, stake, vote, sysvars, loaders),
+// flattened to dotted paths.
+pub fn flatten_parsed(parsed: &Value) -> Vec<AccountField> {
+    let mut fields = Vec::new();
+    flatten("", parsed.get("info").unwrap_or(parsed), &mut fields);
+    fields
+}
+
+fn flatten(prefix: &str, value: &Value, fields: &mut Vec<AccountField>) {
+    let join = |key: &str| if prefix.is_empty() { key.to_string() } else { format!("{}.{}", prefix, key) };
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map {
+                flatten(&join(key), v, fields);
+            }
+        }
+        Value::Array(items) => {
+            for (i, v) in items.iter().enumerate() {
+                flatten(&join(&i.to_string()), v, fields);
+            }
+        }
+        Value::String(s) => fields.push(AccountField { name: prefix.to_string(), offset: None, value: s.clone() }),
+        other => fields.push(AccountField { name: prefix.to_string(), offset: None, value: other.to_string() }),
+    }
+}
+
+// Classic offset / hex / ASCII dump of `max` bytes from `start` (rounded down to a
+// row), noting what's left after.
+pub fn hex_dump(data: &[u8], start: usize, max: usize) -> String {
+    let start = (start.min(data.len()) / 16) * 16;
+    let end = data.len().min(start + max);
+    let mut out = String::new();
+    for (row, chunk) in data[start..end].chunks(16).enumerate() {
+        let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+        let ascii: String = chunk.iter().map(|b| if b.is_ascii_graphic() || *b == b' ' { *b as char } else { '.' }).collect();
+        out.push_str(&format!("{:08x}  {:<47}  {}\n", start + row * 16, hex.join(" "), ascii));
+    }
+    if data.len() > end {
+        out.push_str(&format!("... {} more bytes\n", data.len() - end));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+    use crate::jutsu::decode::{diagnose, DecodeType};
+    use crate::model::{Collection, CollectionDetails, Creator, Data, ProgrammableConfig};
+
+    // Key, revision map location, a stand-in MessagePack body, then the map
+    fn rule_set_account(revisions: &[u64]) -> Vec<u8> {
+        let location = 9 + 20;
+        let mut data = vec![4u8];
+        data.extend((location as u64).to_le_bytes());
+        data.extend([0xAA; 20]);
+        data.push(1);
+        data.extend((revisions.len() as u32).to_le_bytes());
+        for offset in revisions {
+            data.extend(offset.to_le_bytes());
+        }
+        data
+    }
+
+    fn field<'a>(fields: &'a [AccountField], name: &str) -> &'a AccountField {
+        fields.iter().find(|f| f.name == name).unwrap_or_else(|| panic!("no field {}", name))
+    }
+
+    #[test]
+    fn rule_set_latest_revision() {
+        let data = rule_set_account(&[9, 100, 250]);
+        let decoded = decode(TOKEN_AUTH_RULES_PROGRAM, &data).unwrap().unwrap();
+        let revisions = field(&decoded.fields, "revisions");
+        assert_eq!((revisions.offset, revisions.value.as_str()), (Some(30), "3"));
+        // The third entry: map start + version + count + two entries before it
+        let latest = field(&decoded.fields, "latest_revision_offset");
+        assert_eq!((latest.offset, latest.value.as_str()), (Some(29 + 5 + 16), "250"));
+    }
+
+    #[test]
+    fn rule_set_without_revisions() {
+        let decoded = decode(TOKEN_AUTH_RULES_PROGRAM, &rule_set_account(&[])).unwrap().unwrap();
+        assert_eq!(field(&decoded.fields, "revisions").value, "0");
+        assert!(decoded.fields.iter().all(|f| f.name != "latest_revision_offset"));
+    }
+
+    #[test]
+    fn rule_set_map_past_the_end() {
+        let mut data = rule_set_account(&[9]);
+        data[1..9].copy_from_slice(&500u64.to_le_bytes());
+        let error = decode(TOKEN_AUTH_RULES_PROGRAM, &data).unwrap().err().unwrap().to_string();
+        assert_eq!(error, format!("revision_map_location points to byte 500, past the end of the {}-byte account", data.len()));
+
+        // A truncated map reports offsets within the account, not within the map
+        let mut data = rule_set_account(&[9, 100]);
+        data.truncate(data.len() - 4);
+        let error = decode(TOKEN_AUTH_RULES_PROGRAM, &data).unwrap().err().unwrap().to_string();
+        assert!(error.starts_with("latest_revision_offset at byte 42:"), "{}", error);
+    }
+
+    fn sample_metadata() -> Metadata {
+        Metadata {
+            key: 4,
+            update_authority: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            data: Data {
+                name: "Shinobi #7\0\0\0".to_string(),
+                symbol: "SHNB".to_string(),
+                uri: "https://arweave.net/abc".to_string(),
+                seller_fee_basis_points: 500,
+                creators: Some(vec![
+                    Creator { address: Pubkey::new_unique(), verified: true, share: 0 },
+                    Creator { address: Pubkey::new_unique(), verified: false, share: 100 },
+                ]),
+            },
+            primary_sale_happened: true,
+            is_mutable: false,
+            edition_nonce: Some(254),
+            token_standard: Some(4),
+            collection: Some(Collection { verified: true, key: Pubkey::new_unique() }),
+            uses: None,
+            collection_details: Some(CollectionDetails::V1 { size: 42 }),
+            programmable_config: Some(ProgrammableConfig::V1 { rule_set: Some(Pubkey::new_unique()) }),
+        }
+    }
+
+    fn traced(data: &[u8]) -> (Reader<'_>, Result<()>) {
+        let mut reader = Reader::new(data);
+        let result = trace_metadata(&mut reader);
+        (reader, result)
+    }
+
+    #[test]
+    fn trace_agrees_with_borsh() {
+        let metadata = sample_metadata();
+        let data = metadata.try_to_vec().unwrap();
+        let decoded = decode_metadata(&data).unwrap();
+        let (reader, result) = traced(&data);
+        result.unwrap();
+        assert_eq!(reader.remaining(), 0);
+
+        let fields = &reader.fields;
+        assert_eq!(field(fields, "update_authority").value, decoded.update_authority.to_string());
+        assert_eq!(field(fields, "data.name").value, "\"Shinobi #7\"");
+        assert_eq!(decoded.data.name.trim_end_matches('\0'), "Shinobi #7");
+        assert_eq!(field(fields, "data.seller_fee_basis_points").value, "500");
+        assert_eq!(field(fields, "data.creators[1].share").value, "100");
+        assert_eq!(field(fields, "edition_nonce").value, "254");
+        assert_eq!(field(fields, "collection.key").value, metadata.collection.unwrap().key.to_string());
+        assert_eq!(field(fields, "uses").value, "None");
+        assert_eq!(field(fields, "collection_details.size").value, "42");
+        let ProgrammableConfig::V1 { rule_set } = decoded.programmable_config.unwrap();
+        assert_eq!(field(fields, "programmable_config.rule_set").value, rule_set.unwrap().to_string());
+        // Offsets follow the layout: key, two pubkeys, then the name's length prefix
+        assert_eq!(field(fields, "data.name").offset, Some(65));
+        assert_eq!(field(fields, "data.symbol").offset, Some(65 + 4 + 13));
+    }
+
+    #[test]
+    fn trace_agrees_with_borsh_without_options() {
+        let mut metadata = sample_metadata();
+        metadata.data.creators = None;
+        metadata.edition_nonce = None;
+        metadata.token_standard = None;
+        metadata.collection = None;
+        metadata.collection_details = None;
+        metadata.programmable_config = None;
+        let data = metadata.try_to_vec().unwrap();
+        assert!(decode_metadata(&data).is_ok());
+        let (reader, result) = traced(&data);
+        result.unwrap();
+        assert_eq!(reader.remaining(), 0);
+        assert_eq!(field(&reader.fields, "data.creators").value, "None");
+    }
+
+    // Where data.creators' option tag sits in `sample_metadata`
+    const CREATORS_TAG: usize = 65 + (4 + 13) + (4 + 4) + (4 + 23) + 2;
+
+    #[test]
+    fn diagnose_bad_option_tag() {
+        let mut data = sample_metadata().try_to_vec().unwrap();
+        assert_eq!(data[CREATORS_TAG], 1);
+        data[CREATORS_TAG] = 7;
+        let diagnosis = diagnose(DecodeType::Metadata, &data);
+        assert!(!diagnosis.decoded);
+        assert_eq!(diagnosis.error.as_deref(), Some(format!("data.creators at byte {}: option tag 7 is neither 0 nor 1", CREATORS_TAG).as_str()));
+        assert_eq!(diagnosis.stopped_at, CREATORS_TAG);
+        assert_eq!(diagnosis.fields.last().unwrap().name, "data.seller_fee_basis_points");
+        assert!(diagnosis.context.is_some());
+    }
+
+    #[test]
+    fn diagnose_string_past_the_end() {
+        let data = sample_metadata().try_to_vec().unwrap();
+        let truncated = &data[..65 + 4 + 5];
+        let diagnosis = diagnose(DecodeType::Metadata, truncated);
+        assert_eq!(diagnosis.error.as_deref(), Some("data.name at byte 65: length prefix says 13 bytes, only 5 left"));
+        assert_eq!(diagnosis.stopped_at, 65);
+        assert_eq!(diagnosis.fields.last().unwrap().name, "mint");
+    }
+
+    #[test]
+    fn diagnose_truncated_pubkey_and_bad_bool() {
+        let data = sample_metadata().try_to_vec().unwrap();
+        let (reader, result) = traced(&data[..50]);
+        assert_eq!(result.unwrap_err().to_string(), "mint at byte 33: needs 32 bytes, only 17 left");
+        assert_eq!(reader.offset(), 33);
+
+        // First creator's verified flag
+        let mut data = data;
+        let verified = CREATORS_TAG + 1 + 4 + 32;
+        data[verified] = 2;
+        let (reader, result) = traced(&data);
+        assert_eq!(result.unwrap_err().to_string(), format!("data.creators[0].verified at byte {}: 2 is not a valid bool", verified));
+        assert_eq!(reader.offset(), verified);
+    }
+}
@@ -0,0 +1,350 @@
+use anyhow::{anyhow, Result};
+use crate::model::{ActivityProfile, Behaviour, EnhancedTransaction, MonthlyCount, ProgramUsage, SignatureInfo, TradeCounts};
+use crate::programs;
+use crate::rpc;
+use reqwest::Client;
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// getSignaturesForAddress caps a page at 1000, the enhanced API at 100.
+const SIGNATURE_PAGE: usize = 1000;
+const ENHANCED_PAGE: usize = 100;
+// Enhanced transactions are much heavier than signatures, so fewer are pulled.
+const MAX_ENHANCED: usize = 500;
+
+const DAY: i64 = 86_400;
+const DORMANT_DAYS: i64 = 180;
+const LONG_HOLD_DAYS: f64 = 90.0;
+const FLIP_HOLD_DAYS: f64 = 14.0;
+
+// Timeline from the signature history, programs and NFT trades from Helius enhanced
+// transactions when HELIUS_API_KEY is set. Failures become warnings, like the balances.
+pub async fn profile(client: &Client, rpc_url: &str, owner: &str, max_txs: usize) -> ActivityProfile {
+    let mut warnings = Vec::new();
+
+    let (signatures, truncated) = match fetch_signatures(client, rpc_url, owner, max_txs).await {
+        Ok(result) => result,
+        Err(e) => {
+            warnings.push(format!("signature history: {}", e));
+            (Vec::new(), false)
+        }
+    };
+
+    let enhanced = match rpc::helius_api_key() {
+        Some(key) => match fetch_enhanced(client, &key, owner, max_txs.min(MAX_ENHANCED)).await {
+            Ok(txs) => txs,
+            Err(e) => {
+                warnings.push(format!("enhanced transactions: {}", e));
+                Vec::new()
+            }
+        },
+        None => {
+            warnings.push("Set HELIUS_API_KEY to break down programs and NFT trades".to_string());
+            Vec::new()
+        }
+    };
+
+    build_profile(owner, &signatures, truncated, &enhanced, warnings, now())
+}
+
+// Newest first, walking back with `before` until the history ends or `max` is reached.
+// Returns the signatures and whether the cap cut the history short.
+pub async fn fetch_signatures(client: &Client, rpc_url: &str, address: &str, max: usize) -> Result<(Vec<SignatureInfo>, bool)> {
+    let mut signatures: Vec<SignatureInfo> = Vec::new();
+    let mut before: Option<String> = None;
+
+    while signatures.len() < max {
+        let limit = SIGNATURE_PAGE.min(max - signatures.len());
+        let mut config = json!({ "limit": limit });
+        if let Some(sig) = &before {
+            config["before"] = json!(sig);
+        }
+
+        let result = rpc::call(client, rpc_url, "getSignaturesForAddress", json!([address, config])).await?;
+        let page: Vec<SignatureInfo> = serde_json::from_value(result)?;
+        let last_page = page.len() < limit;
+        before = page.last().map(|s| s.signature.clone());
+        signatures.extend(page);
+
+        if last_page {
+            return Ok((signatures, false));
+        }
+    }
+    Ok((signatures, true))
+}
+
+// Helius parsed history for an address, newest first.
+pub async fn fetch_enhanced(client: &Client, api_key: &str, address: &str, max: usize) -> Result<Vec<EnhancedTransaction>> {
+    let mut txs: Vec<EnhancedTransaction> = Vec::new();
+    let mut before: Option<String> = None;
+
+    while txs.len() < max {
+        let limit = ENHANCED_PAGE.min(max - txs.len());
+        let mut url = format!(
+            "https://api.helius.xyz/v0/addresses/{}/transactions?api-key={}&limit={}",
+            address, api_key, limit
+        );
+        if let Some(sig) = &before {
+            url.push_str(&format!("&before={}", sig));
+        }
+
+        // The api key is in the query string; keep the URL out of errors that end up in reports
+        let response = client.get(&url).send().await.map_err(|e| e.without_url())?;
+        if !response.status().is_success() {
+            return Err(anyhow!("Helius enhanced transactions request failed with status: {}", response.status()));
+        }
+
+        let page: Vec<EnhancedTransaction> = response.json().await.map_err(|e| e.without_url())?;
+        let last_page = page.len() < limit;
+        before = page.last().map(|t| t.signature.clone());
+        txs.extend(page);
+
+        if last_page {
+            break;
+        }
+    }
+    Ok(txs)
+}
+
+pub fn build_profile(
+    owner: &str,
+    signatures: &[SignatureInfo],
+    truncated: bool,
+    enhanced: &[EnhancedTransaction],
+    warnings: Vec<String>,
+    now: i64,
+) -> ActivityProfile {
+    let times: Vec<i64> = signatures.iter().filter_map(|s| s.block_time).collect();
+    let first_seen = times.iter().min().copied();
+    let last_active = times.iter().max().copied();
+    let failed_tx_count = signatures.iter().filter(|s| s.err.is_some()).count() as u32;
+
+    let mut by_month: BTreeMap<String, u32> = BTreeMap::new();
+    for ts in &times {
+        *by_month.entry(month_key(*ts)).or_default() += 1;
+    }
+    let monthly = by_month
+        .into_iter()
+        .map(|(month, count)| MonthlyCount { month, count })
+        .collect();
+
+    let (trades, holds) = count_trades(owner, enhanced);
+    let avg_holding_days = if holds.is_empty() {
+        None
+    } else {
+        Some(holds.iter().sum::<f64>() / holds.len() as f64)
+    };
+
+    let mut profile = ActivityProfile {
+        first_seen,
+        last_active,
+        tx_count: signatures.len() as u32,
+        failed_tx_count,
+        truncated,
+        monthly,
+        programs: program_usage(enhanced),
+        trades,
+        avg_holding_days,
+        behaviour: Behaviour::Unknown,
+        evidence: Vec::new(),
+        warnings,
+    };
+    let (behaviour, evidence) = classify(&profile, now);
+    profile.behaviour = behaviour;
+    profile.evidence = evidence;
+    profile
+}
+
+// Each program is counted once per transaction that invoked it.
+fn program_usage(enhanced: &[EnhancedTransaction]) -> Vec<ProgramUsage> {
+    let mut counts: HashMap<&str, u32> = HashMap::new();
+    for tx in enhanced {
+        let unique: HashSet<&str> = tx.instructions.iter().map(|i| i.program_id.as_str()).collect();
+        for program in unique {
+            *counts.entry(program).or_default() += 1;
+        }
+    }
+
+    let mut usage: Vec<ProgramUsage> = counts
+        .into_iter()
+        .map(|(program, count)| ProgramUsage {
+            name: programs::name(program).map(str::to_string),
+            program: program.to_string(),
+            count,
+        })
+        .collect();
+    usage.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.program.cmp(&b.program)));
+    usage
+}
+
+// Mints, buys, sells and single-token transfers, replayed oldest first so that
+// every disposal can be matched with the acquisition before it. The second value
+// holds the length in days of each completed hold.
+fn count_trades(owner: &str, enhanced: &[EnhancedTransaction]) -> (TradeCounts, Vec<f64>) {
+    let mut trades = TradeCounts::default();
+    let mut acquired: HashMap<String, i64> = HashMap::new();
+    let mut holds = Vec::new();
+
+    let mut ordered: Vec<&EnhancedTransaction> = enhanced.iter().collect();
+    ordered.sort_by_key(|t| t.timestamp);
+
+    for tx in ordered {
+        trades.analysed += 1;
+        let event = tx.events.nft.as_ref();
+        let kind = event.map(|e| e.event_type.as_str()).unwrap_or(tx.tx_type.as_str());
+        let event_mints: Vec<&str> = event
+            .map(|e| e.nfts.iter().map(|n| n.mint.as_str()).collect())
+            .unwrap_or_default();
+
+        // Single-unit transfers in and out of the wallet: NFTs, in practice
+        let incoming: Vec<&str> = tx.token_transfers.iter()
+            .filter(|t| t.token_amount == 1.0 && t.to_user_account.as_deref() == Some(owner))
+            .map(|t| t.mint.as_str())
+            .collect();
+        let outgoing: Vec<&str> = tx.token_transfers.iter()
+            .filter(|t| t.token_amount == 1.0 && t.from_user_account.as_deref() == Some(owner))
+            .map(|t| t.mint.as_str())
+            .collect();
+
+        let buyer = event.and_then(|e| e.buyer.as_deref()) == Some(owner);
+        let seller = event.and_then(|e| e.seller.as_deref()) == Some(owner);
+
+        let (got, gave): (Vec<&str>, Vec<&str>) = match kind {
+            "NFT_MINT" | "COMPRESSED_NFT_MINT" if buyer || tx.fee_payer == owner => {
+                trades.mints += 1;
+                (if event_mints.is_empty() { incoming } else { event_mints }, Vec::new())
+            }
+            "NFT_SALE" | "NFT_GLOBAL_BID_FILLED" | "NFT_BID" | "COMPRESSED_NFT_SALE" if buyer => {
+                trades.buys += 1;
+                (event_mints, Vec::new())
+            }
+            "NFT_SALE" | "NFT_GLOBAL_BID_FILLED" | "NFT_BID" | "COMPRESSED_NFT_SALE" if seller => {
+                trades.sells += 1;
+                (Vec::new(), event_mints)
+            }
+            _ => {
+                if !incoming.is_empty() {
+                    trades.transfers_in += 1;
+                }
+                if !outgoing.is_empty() {
+                    trades.transfers_out += 1;
+                }
+                (incoming, outgoing)
+            }
+        };
+
+        for mint in got {
+            acquired.insert(mint.to_string(), tx.timestamp);
+        }
+        for mint in gave {
+            if let Some(at) = acquired.remove(mint) {
+                holds.push((tx.timestamp - at) as f64 / DAY as f64);
+            }
+        }
+    }
+
+    (trades, holds)
+}
+
+// The label plus the observations that earned it. Order matters: a dormant
+// wallet is dormant whatever it did before, and bots are ruled out before flippers.
+fn classify(profile: &ActivityProfile, now: i64) -> (Behaviour, Vec<String>) {
+    let Some(last) = profile.last_active else {
+        return (Behaviour::Unknown, vec!["No transactions found for this address".to_string()]);
+    };
+    let first = profile.first_seen.unwrap_or(last);
+    let t = &profile.trades;
+    let mut evidence = Vec::new();
+
+    let idle_days = (now - last) / DAY;
+    if idle_days >= DORMANT_DAYS {
+        evidence.push(format!("Last transaction {} days ago ({})", idle_days, format_date(last)));
+        return (Behaviour::Dormant, evidence);
+    }
+
+    let span_days = ((last - first) / DAY).max(1);
+    let per_day = profile.tx_count as f64 / span_days as f64;
+    let trade_total = t.mints + t.buys + t.sells;
+
+    if t.mints >= 20 && t.mints * 2 >= trade_total {
+        evidence.push(format!("{} mints against {} buys and {} sells", t.mints, t.buys, t.sells));
+    }
+    if profile.tx_count >= 500 && per_day >= 100.0 {
+        evidence.push(format!("About {:.0} transactions per day over {} days", per_day, span_days));
+    }
+    if profile.tx_count >= 50 && profile.failed_tx_count * 10 >= profile.tx_count * 3 {
+        evidence.push(format!(
+            "{} of {} transactions failed, typical of mint and snipe bots",
+            profile.failed_tx_count, profile.tx_count
+        ));
+    }
+    if !evidence.is_empty() {
+        return (Behaviour::MinterBot, evidence);
+    }
+
+    if t.analysed == 0 {
+        evidence.push(format!("Active {} days ago, but no trade history to judge from", idle_days));
+        return (Behaviour::Unknown, evidence);
+    }
+
+    let hold = profile.avg_holding_days;
+    if t.sells >= 5 {
+        if let Some(days) = hold.filter(|d| *d < FLIP_HOLD_DAYS) {
+            evidence.push(format!("{} sales with an average hold of {:.1} days", t.sells, days));
+        } else if t.buys >= 5 && t.sells * 10 >= t.buys * 8 {
+            evidence.push(format!("Sold {} NFTs against {} bought", t.sells, t.buys));
+        }
+        if !evidence.is_empty() {
+            return (Behaviour::Flipper, evidence);
+        }
+    }
+
+    let history_days = (now - first) / DAY;
+    if let Some(days) = hold.filter(|d| *d >= LONG_HOLD_DAYS) {
+        evidence.push(format!("Average hold of {:.0} days before selling", days));
+    }
+    if t.sells == 0 && t.buys + t.mints > 0 && history_days >= 365 {
+        evidence.push(format!("{} NFTs acquired over {} days and none sold", t.buys + t.mints, history_days));
+    }
+    if !evidence.is_empty() {
+        return (Behaviour::LongTermHolder, evidence);
+    }
+
+    evidence.push(format!("{} mints, {} buys, {} sells in the last {} transactions", t.mints, t.buys, t.sells, t.analysed));
+    if let Some(days) = hold {
+        evidence.push(format!("Average hold of {:.1} days", days));
+    }
+    (Behaviour::Collector, evidence)
+}
+
+pub fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+// Unix timestamp to a UTC (year, month, day), after Howard Hinnant's civil_from_days.
+fn civil_date(ts: i64) -> (i64, u32, u32) {
+    let z = ts.div_euclid(DAY) + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+pub fn format_date(ts: i64) -> String {
+    let (y, m, d) = civil_date(ts);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+fn month_key(ts: i64) -> String {
+    let (y, m, _) = civil_date(ts);
+    format!("{:04}-{:02}", y, m)
+}
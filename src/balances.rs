@@ -0,0 +1,193 @@
+use anyhow::Result;
+use crate::jutsu::unmask;
+use crate::model::{EmptyTokenAccount, HeliusAsset, StakeAccount, TokenBalance, WalletBalances};
+use crate::programs::{STAKE_PROGRAM, TOKEN_2022_PROGRAM, TOKEN_PROGRAM};
+use crate::rpc;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+// On-chain metadata lookups for symbols DAS couldn't provide, one RPC call each.
+const SYMBOL_LOOKUPS: usize = 20;
+
+// Stake account layout: u32 state tag, u64 rent reserve, then staker and withdrawer pubkeys.
+const STAKER_OFFSET: usize = 12;
+const WITHDRAWER_OFFSET: usize = 44;
+
+// SOL, fungible token, stake and empty-account picture of a wallet.
+// Every part is best effort: failures become warnings instead of sinking the dossier.
+pub async fn fetch(client: &Client, rpc_url: &str, das_url: &str, owner: &str) -> WalletBalances {
+    let mut balances = WalletBalances::default();
+
+    match rpc::call(client, rpc_url, "getBalance", json!([owner])).await {
+        Ok(v) => balances.sol_lamports = v.get("value").and_then(|l| l.as_u64()).unwrap_or(0),
+        Err(e) => balances.warnings.push(format!("SOL balance: {}", e)),
+    }
+
+    for (program, label) in [(TOKEN_PROGRAM, "spl-token"), (TOKEN_2022_PROGRAM, "token-2022")] {
+        if let Err(e) = fetch_token_accounts(client, rpc_url, owner, program, label, &mut balances).await {
+            balances.warnings.push(format!("{} accounts: {}", label, e));
+        }
+    }
+
+    resolve_symbols(client, rpc_url, das_url, &mut balances.tokens).await;
+    balances.tokens.sort_by(|a, b| b.ui_amount.partial_cmp(&a.ui_amount).unwrap_or(std::cmp::Ordering::Equal));
+
+    match fetch_stakes(client, rpc_url, owner).await {
+        Ok(stakes) => balances.stakes = stakes,
+        Err(e) => balances.warnings.push(format!("stake accounts: {}", e)),
+    }
+
+    balances
+}
+
+// Fold one wallet's balances into a running total; the same token held in
+// several wallets becomes a single line.
+pub fn merge(total: &mut WalletBalances, other: WalletBalances) {
+    total.sol_lamports += other.sol_lamports;
+    for token in other.tokens {
+        match total.tokens.iter_mut().find(|t| t.mint == token.mint) {
+            Some(existing) => {
+                let raw = existing.raw_amount.parse::<u128>().unwrap_or(0) + token.raw_amount.parse::<u128>().unwrap_or(0);
+                existing.raw_amount = raw.to_string();
+                existing.ui_amount += token.ui_amount;
+            }
+            None => total.tokens.push(token),
+        }
+    }
+    total.tokens.sort_by(|a, b| b.ui_amount.partial_cmp(&a.ui_amount).unwrap_or(std::cmp::Ordering::Equal));
+    total.stakes.extend(other.stakes);
+    total.empty_accounts.extend(other.empty_accounts);
+    total.warnings.extend(other.warnings);
+}
+
+async fn fetch_token_accounts(client: &Client, rpc_url: &str, owner: &str, program: &str, label: &str, balances: &mut WalletBalances) -> Result<()> {
+    let result = rpc::call(client, rpc_url, "getTokenAccountsByOwner", json!([
+        owner,
+        { "programId": program },
+        { "encoding": "jsonParsed" }
+    ])).await?;
+
+    let accounts = result.get("value").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    for entry in accounts {
+        let address = entry.get("pubkey").and_then(|p| p.as_str()).unwrap_or_default().to_string();
+        let account = entry.get("account").cloned().unwrap_or(Value::Null);
+        let lamports = account.get("lamports").and_then(|l| l.as_u64()).unwrap_or(0);
+        let info = account.pointer("/data/parsed/info").cloned().unwrap_or(Value::Null);
+        let mint = info.get("mint").and_then(|m| m.as_str()).unwrap_or_default().to_string();
+        let amount = info.get("tokenAmount").cloned().unwrap_or(Value::Null);
+
+        let raw_amount = amount.get("amount").and_then(|a| a.as_str()).unwrap_or("0").to_string();
+        let decimals = amount.get("decimals").and_then(|d| d.as_u64()).unwrap_or(0) as u8;
+        let ui_amount = amount
+            .get("uiAmountString")
+            .and_then(|a| a.as_str())
+            .and_then(|a| a.parse::<f64>().ok())
+            .unwrap_or(0.0);
+
+        if raw_amount == "0" {
+            balances.empty_accounts.push(EmptyTokenAccount {
+                address,
+                mint,
+                program: label.to_string(),
+                lamports,
+            });
+            continue;
+        }
+
+        // A single zero-decimal token is an NFT, which the rest of the dossier already covers
+        if decimals == 0 && raw_amount == "1" {
+            continue;
+        }
+
+        balances.tokens.push(TokenBalance {
+            mint,
+            symbol: None,
+            program: label.to_string(),
+            raw_amount,
+            decimals,
+            ui_amount,
+        });
+    }
+    Ok(())
+}
+
+// Symbols from DAS in one batch, then on-chain metadata for whatever is still missing.
+async fn resolve_symbols(client: &Client, rpc_url: &str, das_url: &str, tokens: &mut [TokenBalance]) {
+    if tokens.is_empty() {
+        return;
+    }
+
+    let ids: Vec<&str> = tokens.iter().map(|t| t.mint.as_str()).collect();
+    let mut symbols: HashMap<String, String> = HashMap::new();
+    if let Ok(result) = rpc::call(client, das_url, "getAssetBatch", json!({ "ids": ids })).await {
+        // Unknown ids come back as null entries
+        let assets: Vec<Option<HeliusAsset>> = serde_json::from_value(result).unwrap_or_default();
+        for asset in assets.into_iter().flatten() {
+            let symbol = asset.token_info.as_ref().and_then(|t| t.symbol.clone())
+                .or_else(|| asset.content.as_ref().and_then(|c| c.metadata.as_ref()).and_then(|m| m.symbol.clone()))
+                .filter(|s| !s.trim().is_empty());
+            if let Some(symbol) = symbol {
+                symbols.insert(asset.id, symbol);
+            }
+        }
+    }
+
+    let mut lookups = 0;
+    for token in tokens.iter_mut() {
+        if let Some(symbol) = symbols.get(&token.mint) {
+            token.symbol = Some(symbol.clone());
+            continue;
+        }
+        if lookups == SYMBOL_LOOKUPS {
+            continue;
+        }
+        lookups += 1;
+        if let Ok(metadata) = unmask::fetch_metadata(client, rpc_url, &token.mint).await {
+            let symbol = metadata.data.symbol.trim_end_matches('\0').trim().to_string();
+            if !symbol.is_empty() {
+                token.symbol = Some(symbol);
+            }
+        }
+    }
+}
+
+// Stake accounts where the wallet is the staker or withdrawer authority.
+async fn fetch_stakes(client: &Client, rpc_url: &str, owner: &str) -> Result<Vec<StakeAccount>> {
+    let mut stakes: Vec<StakeAccount> = Vec::new();
+
+    for offset in [STAKER_OFFSET, WITHDRAWER_OFFSET] {
+        let result = rpc::call(client, rpc_url, "getProgramAccounts", json!([
+            STAKE_PROGRAM,
+            {
+                "encoding": "jsonParsed",
+                "filters": [{ "memcmp": { "offset": offset, "bytes": owner } }]
+            }
+        ])).await?;
+
+        for entry in result.as_array().cloned().unwrap_or_default() {
+            let address = entry.get("pubkey").and_then(|p| p.as_str()).unwrap_or_default().to_string();
+            if stakes.iter().any(|s| s.address == address) {
+                continue;
+            }
+            let account = entry.get("account").cloned().unwrap_or(Value::Null);
+            let parsed = account.pointer("/data/parsed").cloned().unwrap_or(Value::Null);
+            let delegation = parsed.pointer("/info/stake/delegation");
+
+            stakes.push(StakeAccount {
+                address,
+                lamports: account.get("lamports").and_then(|l| l.as_u64()).unwrap_or(0),
+                delegated_lamports: delegation
+                    .and_then(|d| d.get("stake"))
+                    .and_then(|s| s.as_str())
+                    .and_then(|s| s.parse().ok()),
+                voter: delegation
+                    .and_then(|d| d.get("voter"))
+                    .and_then(|v| v.as_str())
+                    .map(|v| v.to_string()),
+                state: parsed.get("type").and_then(|t| t.as_str()).unwrap_or("unknown").to_string(),
+            });
+        }
+    }
+    Ok(stakes)
+}
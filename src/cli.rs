@@ -1,4 +1,11 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+use crate::jutsu::decode::DecodeType;
+use crate::jutsu::hashlist::HashlistKind;
+use crate::jutsu::rarity::LeaderboardFormat;
+use crate::jutsu::snapshot::{SnapshotFormat, SnapshotKind};
+use crate::rarity::RarityMethod;
+use crate::render::{AnimationMode, Dither, Renderer};
+use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about = "Shinobi  - An intelligence tool for the shadows of Solana.")]
@@ -6,6 +13,18 @@ use clap::{Parser, Subcommand};
 pub struct Cli{
    #[command(subcommand)]
    pub command : Commands,
+
+   #[arg(long, global=true, help="Plain text output: no emoji, box art or banner (automatic when piped)")]
+   pub plain: bool,
+
+   #[arg(long, global=true, help="Print only the key facts, one per line")]
+   pub quiet: bool,
+
+   #[arg(long, global=true, help="Keep the dramatic pauses between sections")]
+   pub theatrics: bool,
+
+   #[arg(long, global=true, value_name="PATH", help="Rules file to use instead of ~/.config/shinobi/rules.json")]
+   pub rules: Option<PathBuf>,
 }
 
 #[derive(Subcommand,Debug)]
@@ -14,11 +33,316 @@ pub enum Commands{
         #[arg(required=true, help="The mint address of the NFT to unmask")]
         mint_address:String,
 
-        #[arg(long, help="Display the NFT image as ASCII art in the terminal")]
+        #[arg(long, help="Display the NFT image in the terminal (see --renderer)")]
         image: bool,
+
+        #[command(flatten)]
+        image_args: ImageArgs,
+
+        #[arg(long, help="Probe the metadata, image and animation URLs and report their health")]
+        health: bool,
+
+        #[arg(long, value_enum, value_name="METHOD", num_args=0..=1, default_missing_value="rarity-score",
+            help="Show trait frequencies and the rarity rank within the collection [default method: rarity-score]")]
+        rarity: Option<RarityMethod>,
+
+        #[arg(long, help="Download the image and check whether it looks like a text banner, as phishing airdrops do")]
+        scan_image: bool,
+
+        #[arg(long, help="Skip the risk score and the RPC lookups behind it")]
+        no_risk: bool,
+
+        #[arg(long, conflicts_with="image", help="Print the report, risk score included, as JSON")]
+        json: bool,
     },
     Dossier{
-        #[arg(required=true, help="The wallet address to analyze")]
-        wallet_address:String,
+        #[arg(help="The wallet address to analyze; several are treated as one portfolio")]
+        wallets: Vec<String>,
+
+        #[arg(long, help="File with wallet addresses (JSON array or one per line)")]
+        file: Option<PathBuf>,
+
+        #[arg(long, default_value_t = 10_000, help="Stop paging after this many assets and mark the dossier as partial")]
+        max_assets: u32,
+
+        #[arg(long, help="Skip SOL, token and stake balances (fewer RPC calls)")]
+        skip_balances: bool,
+
+        #[arg(long, help="Skip the transaction history and behavioural profile")]
+        skip_activity: bool,
+
+        #[arg(long, default_value_t = 1000, help="Most recent transactions to look at for the activity profile")]
+        max_txs: usize,
+
+        #[arg(long, help="Print the dossier as JSON")]
+        json: bool,
+    },
+    /// Check the off-chain assets of many NFTs and report broken links
+    AuditAssets{
+        #[arg(help="Mint addresses to audit")]
+        mints: Vec<String>,
+
+        #[arg(long, help="File with mint addresses (JSON array or one per line)")]
+        file: Option<PathBuf>,
+
+        #[arg(long, default_value_t = 8, help="How many mints to check at once")]
+        concurrency: usize,
+    },
+    /// Follow a wallet's SOL funding back to its source, or cluster several wallets
+    Trace{
+        #[arg(help="Wallet to trace (several with --cluster)")]
+        wallets: Vec<String>,
+
+        #[arg(long, help="File with wallet addresses (JSON array or one per line)")]
+        file: Option<PathBuf>,
+
+        #[arg(long, default_value_t = 3, help="How many funding hops to follow back")]
+        hops: u32,
+
+        #[arg(long, help="Group the wallets by shared funders, co-signers and transfers between them")]
+        cluster: bool,
+
+        #[arg(long, help="Print the result as JSON")]
+        json: bool,
+
+        #[arg(long, conflicts_with="json", help="Print the link graph as Graphviz DOT")]
+        dot: bool,
     },
-}
\ No newline at end of file
+    /// Look for links between two wallets
+    Compare{
+        #[arg(help="First wallet address")]
+        wallet_a: String,
+
+        #[arg(help="Second wallet address")]
+        wallet_b: String,
+
+        #[arg(long, default_value_t = 10_000, help="Stop paging each wallet's assets after this many")]
+        max_assets: u32,
+
+        #[arg(long, default_value_t = 200, help="Recent transactions to search per wallet for transfers and counterparties")]
+        max_txs: usize,
+
+        #[arg(long, help="Print the comparison as JSON")]
+        json: bool,
+    },
+    /// Survey a whole collection: supply, holders and how concentrated they are
+    Collection{
+        #[arg(help="The collection NFT's mint address")]
+        collection_mint: String,
+
+        #[arg(long, default_value_t = 25_000, help="Stop after this many members and mark the survey as partial")]
+        max_assets: u32,
+
+        #[arg(long, help="Print the survey as JSON")]
+        json: bool,
+    },
+    /// Export current holders for an airdrop
+    Snapshot{
+        #[arg(help="Collection mint, creator address, or mint addresses (see --by)")]
+        targets: Vec<String>,
+
+        #[arg(long, value_enum, default_value_t = SnapshotKind::Collection, help="What the addresses are")]
+        by: SnapshotKind,
+
+        #[arg(long, help="File with addresses (JSON array or one per line)")]
+        file: Option<PathBuf>,
+
+        #[arg(long, help="Leave out NFTs held by marketplace escrow and other program-owned accounts")]
+        exclude_escrow: bool,
+
+        #[arg(long, value_enum, default_value_t = SnapshotFormat::Csv, help="Output format")]
+        format: SnapshotFormat,
+
+        #[arg(long, short='o', value_name="PATH", help="Write the snapshot to a file instead of stdout")]
+        output: Option<PathBuf>,
+
+        #[arg(long, default_value_t = 100_000, help="Stop after this many mints and mark the snapshot as partial")]
+        max_assets: u32,
+    },
+    /// Build the mint list (hash list) of a collection, creator or candy machine
+    Hashlist{
+        #[arg(help="Collection mint, creator address or candy machine id (see --by)")]
+        address: String,
+
+        #[arg(long, value_enum, default_value_t = HashlistKind::Collection, help="What the address is")]
+        by: HashlistKind,
+
+        #[arg(long, short='o', value_name="PATH", help="Write the hash list to a file instead of stdout")]
+        output: Option<PathBuf>,
+
+        #[arg(long, value_name="PATH", help="Compare against a previously saved hash list")]
+        diff: Option<PathBuf>,
+
+        #[arg(long, requires="diff", help="Print the diff as JSON")]
+        json: bool,
+    },
+    /// Rank every NFT of a collection by trait rarity
+    Rarity{
+        #[arg(help="The collection mint")]
+        collection: String,
+
+        #[arg(long, value_enum, default_value_t = RarityMethod::RarityScore, help="How traits are scored")]
+        method: RarityMethod,
+
+        #[arg(long, help="Rebuild the trait table even if a fresh one is cached")]
+        refresh: bool,
+
+        #[arg(long, default_value_t = 25, help="How many NFTs the leaderboard shows")]
+        top: usize,
+
+        #[arg(long, value_enum, help="Export the whole ranking instead of printing the leaderboard")]
+        format: Option<LeaderboardFormat>,
+
+        #[arg(long, short='o', value_name="PATH", requires="format", help="Write the export to a file instead of stdout")]
+        output: Option<PathBuf>,
+
+        #[arg(long, default_value_t = 25_000, help="Stop after this many members and mark the table as partial")]
+        max_assets: u32,
+    },
+    /// Find NFTs in a collection by trait, name or on-chain fields
+    Search{
+        #[arg(help="The collection mint")]
+        collection: String,
+
+        #[arg(long="trait", value_name="TYPE=VALUE", help="Attribute to match; repeat to require several, or give one type several values to accept any of them")]
+        traits: Vec<String>,
+
+        #[arg(long, value_name="REGEX", help="Regular expression the on-chain name has to match")]
+        name: Option<String>,
+
+        #[arg(long, conflicts_with="immutable", help="Only NFTs whose metadata is still mutable")]
+        mutable: bool,
+
+        #[arg(long, help="Only NFTs whose metadata is frozen")]
+        immutable: bool,
+
+        #[arg(long, value_name="BOOL", help="Only NFTs whose primary sale has (true) or hasn't (false) happened")]
+        primary_sale: Option<bool>,
+
+        #[arg(long, value_name="ADDRESS", help="Only NFTs held by this wallet")]
+        holder: Option<String>,
+
+        #[arg(long, default_value_t = 25_000, help="Stop paging after this many members and mark the search as partial")]
+        max_assets: u32,
+
+        #[arg(long, help="Print the matches as JSON")]
+        json: bool,
+    },
+    /// Everything an update authority (or first creator) controls, grouped by collection
+    Authority{
+        #[arg(help="The update authority or creator address")]
+        address: String,
+
+        #[arg(long, default_value_t = 50_000, help="Stop after this many metadata accounts and mark the report as partial")]
+        max_accounts: usize,
+
+        #[arg(long, default_value_t = 200, help="Most recent transactions of the authority to search for metadata updates")]
+        max_txs: usize,
+
+        #[arg(long, help="Print the report as JSON")]
+        json: bool,
+    },
+    /// Check assessment and risk rules (see --rules)
+    Rules{
+        #[command(subcommand)]
+        action: RulesAction,
+    },
+    /// Fetch any account and decode it if its type is known
+    Inspect{
+        #[arg(help="The account address")]
+        address: String,
+
+        #[arg(long, help="Also show a hex dump of decoded accounts")]
+        hex: bool,
+
+        #[arg(long, help="Print the account as JSON")]
+        json: bool,
+    },
+    /// Derive the Metaplex and SPL addresses of a mint
+    Pda{
+        #[arg(help="The mint")]
+        mint: String,
+
+        #[arg(long, value_name="WALLET", help="Also derive the wallet's token accounts and token record")]
+        owner: Option<String>,
+
+        #[arg(long, value_name="ADDRESS", help="Also derive the collection authority record for this delegate")]
+        authority: Option<String>,
+
+        #[arg(long, value_name="ADDRESS", help="Also derive the use authority record for this user")]
+        user: Option<String>,
+
+        #[arg(long, value_name="NUMBER", help="Also derive the edition marker covering this print number")]
+        edition: Option<u64>,
+
+        #[arg(long, value_name="ADDRESS", help="Also derive the Bubblegum tree config of this merkle tree")]
+        tree: Option<String>,
+
+        #[arg(long, help="Look up which of the addresses exist")]
+        check: bool,
+
+        #[arg(long, help="Print the addresses as JSON")]
+        json: bool,
+    },
+    /// Decode raw account data offline, as captured from snapshots or logs
+    Decode{
+        #[arg(long="type", value_enum, default_value_t = DecodeType::Metadata, help="What the data is")]
+        kind: DecodeType,
+
+        #[arg(long, value_name="DATA", conflicts_with="file", required_unless_present="file", help="The account data as base64")]
+        base64: Option<String>,
+
+        #[arg(long, value_name="PATH", help="File with raw bytes, base64, or a `solana account --output json` dump (- for stdin)")]
+        file: Option<PathBuf>,
+
+        #[arg(long, help="Walk the layout field by field and show where decoding fails")]
+        diagnose: bool,
+
+        #[arg(long, help="Print the result as JSON")]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RulesAction {
+    /// Evaluate the rules against reports saved with --json
+    Test{
+        #[arg(required=true, help="Saved unmask or dossier reports")]
+        reports: Vec<PathBuf>,
+
+        #[arg(long, help="Print the results as JSON")]
+        json: bool,
+    },
+    /// List the rules in effect
+    List,
+}
+
+#[derive(Args, Debug)]
+pub struct ImageArgs{
+    #[arg(long, value_enum, default_value_t = Renderer::Ascii, help="How to draw the image with --image")]
+    pub renderer: Renderer,
+
+    #[arg(long, default_value_t = 60, help="Maximum image width in terminal columns")]
+    pub width: u32,
+
+    #[arg(long, help="Maximum image height in terminal rows")]
+    pub height: Option<u32>,
+
+    #[arg(long, default_value = "standard", help="Character ramp for ASCII renderers: standard, detailed, blocks, or your own dark-to-light characters")]
+    pub ramp: String,
+
+    #[arg(long, value_enum, default_value_t = Dither::None, help="Dithering for the ASCII and sixel renderers")]
+    pub dither: Dither,
+
+    #[arg(long, value_enum, default_value_t = AnimationMode::Strip, help="How to show animated GIF/APNG/WebP images")]
+    pub animation: AnimationMode,
+
+    #[arg(long, default_value_t = 60, help="Maximum number of animation frames to decode")]
+    pub max_frames: usize,
+
+    #[arg(long, value_name="PATH", help="Save the NFT image to a file")]
+    pub save_image: Option<PathBuf>,
+
+    #[arg(long, value_name="PIXELS", requires="save_image", help="Resize the saved image to this width and write it as PNG")]
+    pub save_width: Option<u32>,
+}
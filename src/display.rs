@@ -1,61 +1,21 @@
-use crate::model::{UnmaskReport, DossierReport};
+use crate::model::{AssetHealth, AuditReport, UnmaskReport, DossierReport, WalletBalances, ActivityProfile, Behaviour, TraceReport, ClusterReport, LinkGraph, CompareReport, CollectionReport, SnapshotReport, HashlistDiff, RarityReport, SearchReport, AuthorityReport, RuleTestReport, InspectReport, PdaReport, DecodeDiagnosis, LAMPORTS_PER_SOL};
+use crate::activity::format_date;
+use crate::programs;
+use crate::rarity;
+use crate::rules::RuleSet;
+use crate::probe::media_kind;
 use colored::*;
-use tokio::time::{sleep, Duration};
-use viuer::Config as ViuerConfig;
-use image::io::Reader as ImageReader;
-use image::{DynamicImage, GenericImageView, imageops::FilterType};
-use std::io::Cursor;
-
-fn render_ascii(img: &DynamicImage, width: u32, colorize: bool) -> String {
-    // Character ramp from dark -> light
-
-    const RAMP: &[u8] = b"@%#*+=-:. ";
-
-    // Maintain aspect ratio; terminal characters are taller than wide
-    // so we shrink height by a factor (~0.5) to look proportionate
-    let (w, h) = img.dimensions();
-    if w == 0 || h == 0 || width == 0 { return String::new(); }
-    let scaled_height = ((h as f32 * (width as f32 / w as f32)) * 0.5)
-        .max(1.0)
-        .round() as u32;
-
-    let small = img.resize_exact(width, scaled_height, FilterType::Triangle);
-
-    let mut out = String::new();
-    for y in 0..small.height() {
-        for x in 0..small.width() {
-            let pixel = small.get_pixel(x, y);
-            let r = pixel[0] as f32;
-            let g = pixel[1] as f32;
-            let b = pixel[2] as f32;
-            let lum = 0.299 * r + 0.587 * g + 0.114 * b;
-            let idx = ((lum / 255.0) * (RAMP.len() as f32 - 1.0))
-                .clamp(0.0, (RAMP.len() - 1) as f32) as usize;
-            let ch = RAMP[idx] as char;
-
-            if colorize {
-                // Use 24-bit truecolor if terminal supports; colored crate will do the escape
-                out.push_str(&format!("{}", ch.to_string().truecolor(r as u8, g as u8, b as u8)));
-            } else {
-                out.push(ch);
-            }
-        }
-        out.push('\n');
-    }
-    out
-}
+use crate::style;
+use crate::render::{self, ImageOptions};
+use crate::layout::{pad_to, Frame};
 
 // Note: This function must now be `async` because it uses `await`.
-pub async fn print_unmask_report(report: UnmaskReport) {
-    let top_left = "╔";
-    let top_right = "╗";
-    let bottom_left = "╚";
-    let bottom_right = "╝";
-    let horizontal = "═";
-    let vertical = "║";
-    let divider_left = "╟";
-    let divider_right = "╢";
-    let divider_line = "─";
+pub async fn print_unmask_report(report: UnmaskReport, image_opts: Option<&ImageOptions>) {
+    let style = style::current();
+    if style.quiet {
+        print_unmask_quiet(&report);
+        return;
+    }
     let diamond = "◆";
 
     // ASCII art banner
@@ -72,251 +32,1285 @@ pub async fn print_unmask_report(report: UnmaskReport) {
     ╚══════════════════════════════════════════════════════════╝
     "#;
 
-    println!("{}", banner.bright_purple().bold());
-    sleep(Duration::from_millis(500)).await;
+    if !style.plain {
+        println!("{}", banner.bright_purple().bold());
+    }
+    style::pause(500).await;
+
+    let frame = Frame::new(23);
+
     // --- HEADER WITH NEON EFFECT ---
-    println!("\n{}", format!("{}{}{}", top_left, horizontal.repeat(63), top_right).bright_cyan());
-    let nft_name = format!(" {} {} {}",
-        vertical.bright_cyan(),
+    println!();
+    frame.top();
+    frame.row(&format!("{} {}",
         report.off_chain.name.bright_yellow().bold().underline(),
         diamond.bright_magenta()
-    );
-    println!("{}{:<63}{}", nft_name, "", vertical.bright_cyan());
+    ));
 
     // --- ON-CHAIN INTEL SECTION ---
-    let divider = format!("{}{}{}",
-        divider_left.bright_cyan(),
-        divider_line.repeat(63).bright_cyan(),
-        divider_right.bright_cyan()
-    );
-    println!("{}", divider);
-    let section_header = format!(" {} {} {}",
-        vertical.bright_cyan(),
+    frame.section(&format!("{} {}",
         "⛓️  ON-CHAIN INTELLIGENCE".bright_white().bold(),
         diamond.bright_blue()
-    );
-    println!("{}{:<63}{}", section_header, "", vertical.bright_cyan());
-    println!("{}", divider);
+    ));
 
     // Authority with emphasis
-    let authority_line = format!(" {} {}  {}", vertical.bright_cyan(), "👑 Update Authority:".bright_yellow().bold(), report.on_chain.update_authority.to_string().bright_white());
-    println!("{}{:<63}{}", authority_line, "", vertical.bright_cyan());
+    frame.field(
+        &"👑 Update Authority:".bright_yellow().bold().to_string(),
+        &report.on_chain.update_authority.to_string(),
+        |s| s.bright_white()
+    );
 
     // Mutability status with visual indicators
-    let (mutable_icon, mutable_text, mutable_color) = if report.on_chain.is_mutable {
+    // The caution comes from the mutable-metadata rule, so a rules file can turn it off
+    let mutable_flagged = report.risk.as_ref()
+        .map(|r| r.flags.iter().any(|f| f.rule == "mutable-metadata"))
+        .unwrap_or(true);
+    let (mutable_icon, mutable_text, mutable_color) = if report.on_chain.is_mutable && mutable_flagged {
         ("🔓", "Mutable ⚠️  CAUTION", Color::BrightYellow)
+    } else if report.on_chain.is_mutable {
+        ("🔓", "Mutable", Color::BrightWhite)
     } else {
         ("🔒", "Frozen ✓ VERIFIED", Color::BrightGreen)
     };
-    let mutable_line = format!(" {} {} Metadata State:    {}",
-        vertical.bright_cyan(),
-        mutable_icon,
-        mutable_text.color(mutable_color).bold()
-    );
-    println!("{}{:<63}{}", mutable_line, "", vertical.bright_cyan());
+    frame.field(&format!("{} Metadata State:", mutable_icon), mutable_text, |s| s.color(mutable_color).bold());
+
+    // --- SPAM ---
+    if !report.spam.is_empty() {
+        frame.section(&format!("{} {}",
+            "🚫 SUSPECTED SPAM".bright_red().bold(),
+            diamond.bright_red()
+        ));
+        frame.row(&format!("   {}", "Do not visit links from this NFT or sign anything it asks for".bright_red()));
+        for reason in report.spam.iter() {
+            frame.row(&format!("   ▸ {}", reason.bright_yellow()));
+        }
+    }
 
+    // --- RISK ---
+    if let Some(risk) = &report.risk {
+        frame.section(&format!("{} {}",
+            "🚨 RISK ASSESSMENT".bright_white().bold(),
+            diamond.bright_red()
+        ));
+        let color = match risk.level.as_str() {
+            "high" => Color::BrightRed,
+            "medium" => Color::BrightYellow,
+            _ => Color::BrightGreen,
+        };
+        frame.field("🎯 Risk Score:", &format!("{}/100 ({})", risk.score, risk.level.to_uppercase()), |s| s.color(color).bold());
+        if risk.flags.is_empty() {
+            frame.row(&format!("   {}", "No red flags found".bright_green()));
+        }
+        for flag in risk.flags.iter() {
+            frame.row(&format!("   ▸ {} {} {}",
+                format!("+{:<3}", flag.weight).color(color),
+                flag.rule.bright_cyan().bold(),
+                flag.detail.dimmed()));
+        }
+    }
 
-    println!("{}", divider);
-    let metadata_header = format!(" {} {} {}",
-        vertical.bright_cyan(),
+    frame.section(&format!("{} {}",
         " OFF-CHAIN METADATA".bright_white().bold(),
         diamond.bright_purple()
-    );
-    println!("{}{:<63}{}", metadata_header, "", vertical.bright_cyan());
-    println!("{}", divider);
+    ));
 
-    let image_display = if report.off_chain.image.len() > 45 {
-        format!("{}...", &report.off_chain.image[..45])
-    } else {
-        report.off_chain.image.clone()
-    };
-    let image_line = format!(" {} 🖼️  Image URI:         {}",
-        vertical.bright_cyan(),
-        image_display.bright_white()
-    );
-    println!("{}{:<63}{}", image_line, "", vertical.bright_cyan());
+    frame.field("🖼️  Image URI:", &report.off_chain.image, |s| s.bright_white());
+    if let Some(animation_url) = report.off_chain.animation_url.as_deref().filter(|u| !u.is_empty()) {
+        frame.field("🎞️  Animation URI:", animation_url, |s| s.bright_white());
+    }
 
     // Attributes
-    println!("{}", divider);
-    let attr_header = format!(" {} ✨ {} Attributes Found",
-        vertical.bright_cyan(),
+    frame.section(&format!("✨ {} Attributes Found",
         report.off_chain.attributes.len().to_string().bright_magenta().bold()
-    );
-    println!("{}{:<63}{}", attr_header, "", vertical.bright_cyan());
+    ));
 
     for attr in report.off_chain.attributes.iter() {
         let trait_name = format!("{}:", attr.trait_type).bright_cyan().bold();
-        let trait_value = attr.value.bright_white();
-        let attr_line = format!(" {}    ▸ {:<15} {}",
-            vertical.bright_cyan(),
-            trait_name,
-            trait_value
-        );
-        println!("{}{:<63}{}", attr_line, "", vertical.bright_cyan());
+        let frequency = report.rarity.as_ref()
+            .and_then(|r| r.traits.iter().find(|t| t.trait_type == attr.trait_type.trim()))
+            .map(|t| format!("  {:.1}% ({})", t.frequency * 100.0, t.count));
+        match frequency {
+            Some(frequency) => frame.row(&format!("   ▸ {} {}{}", trait_name, attr.value.bright_white(), frequency.dimmed())),
+            None => frame.field(&format!("   ▸ {}", trait_name), &attr.value, |s| s.bright_white()),
+        }
+    }
+
+    // --- RARITY ---
+    if let Some(rarity) = &report.rarity {
+        frame.section(&format!("{} {}",
+            "💎 RARITY".bright_white().bold(),
+            diamond.bright_cyan()
+        ));
+        let rank = match rarity.rank {
+            Some(rank) => format!("#{} of {}{}", rank, rarity.members, if rarity.partial { "+" } else { "" }),
+            None => "not in the trait table".to_string(),
+        };
+        frame.field("🏆 Rank:", &rank, |s| s.bright_yellow().bold());
+        frame.field("🧮 Score:", &format!("{} ({})", rarity.method.format_score(rarity.score), rarity.method.label()), |s| s.bright_white());
+        if let Some(rarest) = rarity.traits.iter().min_by(|a, b| a.frequency.total_cmp(&b.frequency)) {
+            frame.field("✨ Rarest trait:", &format!("{}: {} ({:.1}%)", rarest.trait_type, rarest.value, rarest.frequency * 100.0), |s| s.bright_magenta());
+        }
+        // Traits the NFT lacks still count against the collection
+        for missing in rarity.traits.iter().filter(|t| t.value == rarity::NONE_VALUE) {
+            frame.row(&format!("   ▸ {} {}", format!("{}:", missing.trait_type).dimmed(),
+                format!("None  {:.1}% ({})", missing.frequency * 100.0, missing.count).dimmed()));
+        }
+        if rarity.partial {
+            frame.row(&format!("   {} {}", "⚠️".bright_yellow(),
+                "PARTIAL: the trait table covers only part of the collection".bright_yellow()));
+        }
+    }
+    for warning in report.warnings.iter() {
+        frame.row(&format!("⚠️  {}", warning.bright_yellow()));
+    }
+
+    // --- ASSET HEALTH ---
+    if !report.asset_health.is_empty() {
+        frame.section(&format!("{} {}",
+            "🩺 ASSET HEALTH".bright_white().bold(),
+            diamond.bright_green()
+        ));
+
+        for health in report.asset_health.iter() {
+            for line in health_lines(health) {
+                frame.row(&line);
+            }
+        }
     }
 
     // --- FOOTER ---
-    println!("{}", format!("{}{}{}",
-        bottom_left.bright_cyan(),
-        horizontal.repeat(63).bright_cyan(),
-        bottom_right.bright_cyan()
-    ));
+    frame.bottom();
 
-    if let Some(image_bytes) = &report.image_data {
-        println!("\n{}", divider);
-        println!(" {} {}",
-            vertical.bright_cyan(),
-            "🖼️  VISUAL CONFIRMATION".bright_white().bold()
-        );
-        println!("{}", divider);
-
-        // Try to render image; prefer portable ASCII (Pixel Clone)
-        match ImageReader::new(Cursor::new(image_bytes)).with_guessed_format() {
-            Ok(reader) => {
-                match reader.decode() {
-                    Ok(img) => {
-                        // First, print ASCII so it's always visible
-                        let ascii = render_ascii(&img, 60, true);
-                        println!("{}", ascii);
-
-                        // Then, best-effort nice rendering if terminal supports it
-                        let conf = ViuerConfig {
-                            width: Some(60),
-                            height: Some(30),
-                            absolute_offset: false,
-                            ..Default::default()
-                        };
-                        let _ = viuer::print(&img, &conf);
-                    }
-                    Err(_) => {
-                        println!(" {} {}", vertical.bright_cyan(), "❌ Could not decode image".bright_red());
-                    }
-                }
+    if let (Some(image_bytes), Some(image_opts)) = (&report.image_data, image_opts) {
+        println!();
+        frame.section(&"🖼️  VISUAL CONFIRMATION".bright_white().bold().to_string());
+
+        // Render once, with whichever renderer was picked on the command line
+        if let Err(e) = print_image(image_bytes, image_opts).await {
+            frame.row(&format!("{} {}", "❌".bright_red(), e.to_string().bright_red()));
+        }
+
+        frame.bottom();
+    }
+
+    if let (Some(animation), Some(image_opts)) = (&report.animation, image_opts) {
+        println!();
+        frame.section(&"🎞️  ANIMATION".bright_white().bold().to_string());
+
+        let format = animation.format.as_deref().unwrap_or("unknown");
+        frame.field("▸ Media:", &format!("{} ({})", media_kind(format), format), |s| s.bright_white().bold());
+        if let Some(size) = animation.size_bytes {
+            frame.field("▸ Size:", &format_bytes(size), |s| s.bright_white());
+        }
+
+        let shown = match (&animation.data, &animation.preview) {
+            (Some(bytes), _) => Some(print_image(bytes, image_opts).await),
+            (None, Some(preview)) => {
+                frame.field("▸ Preview:", animation.preview_source.as_deref().unwrap_or("extracted"), |s| s.bright_white());
+                Some(render::decode(preview).and_then(|img| render::print(&img, image_opts)))
             }
-            Err(_) => {
-                println!(" {} {}", vertical.bright_cyan(), "❌ Could not decode image".bright_red());
+            (None, None) => {
+                frame.field("▸ Preview:", "none available without external tools", |s| s.dimmed());
+                None
             }
+        };
+        if let Some(Err(e)) = shown {
+            frame.row(&format!("{} {}", "❌".bright_red(), e.to_string().bright_red()));
         }
 
-        println!("{}", format!("{}{}{}",
-            bottom_left.bright_cyan(),
-            horizontal.repeat(63).bright_cyan(),
-            bottom_right.bright_cyan()
-        ));
+        frame.bottom();
     }
 
-    println!("\n  {} Powered by {} | Scan complete ✓\n",
-        "⚡".bright_yellow(),
-        "shinobi".bright_purple().bold()
-    );
+    print_footer("Scan complete");
 }
 
 pub async fn print_dossier_report(report: DossierReport) {
-    let top_left = "╔";
-    let top_right = "╗";
-    let bottom_left = "╚";
-    let bottom_right = "╝";
-    let horizontal = "═";
-    let vertical = "║";
-    let divider_left = "╟";
-    let divider_right = "╢";
-    let divider_line = "─";
-
-    println!("\n🥷 {} for target wallet...\n",
-        "Generating dossier".bright_purple().bold()
-    );
+    let style = style::current();
+    if style.quiet {
+        print_dossier_quiet(&report);
+        return;
+    }
+    if !style.plain {
+        println!("\n🥷 {} for target wallet...\n",
+            "Generating dossier".bright_purple().bold()
+        );
+    }
 
-    sleep(Duration::from_millis(300)).await;
+    style::pause(300).await;
 
-    // Truncate wallet address for display
-    let display_wallet = if report.wallet_address.len() > 10 {
-        format!("{}...{}",
-            &report.wallet_address[..4],
-            &report.wallet_address[report.wallet_address.len()-4..]
-        )
-    } else {
+    let frame = Frame::new(23);
+    frame.top();
+    let target = if report.wallets.is_empty() {
         report.wallet_address.clone()
+    } else {
+        format!("{} wallets", report.wallets.len())
     };
-
-    println!("{}", format!("{}{}{}", top_left, horizontal.repeat(67), top_right).bright_cyan());
-    println!(" {} {}  [Dossier] {}                                                             {}",
-        vertical.bright_cyan(),
+    frame.row(&format!("{}  [Dossier] {}",
         "🎯".bright_yellow(),
-        display_wallet.bright_white().bold(),
-        vertical.bright_cyan()
-    );
+        target.bright_white().bold()
+    ));
 
-    let divider = format!("{}{}{}",
-        divider_left.bright_cyan(),
-        divider_line.repeat(67).bright_cyan(),
-        divider_right.bright_cyan()
-    );
-    println!("{}", divider);
+    if !report.wallets.is_empty() {
+        frame.section(&format!("{} WALLETS", "👛".bright_blue()));
+        for share in report.wallets.iter() {
+            let mut parts = vec![format!("{} NFTs", share.nfts)];
+            if let Some(lamports) = share.sol_lamports {
+                parts.push(format!("{:.4} SOL", lamports as f64 / LAMPORTS_PER_SOL));
+            }
+            if share.duplicates > 0 {
+                parts.push(format!("{} already listed under another wallet", share.duplicates));
+            }
+            if share.delegated_within > 0 {
+                parts.push(format!("{} delegated within the set", share.delegated_within));
+            }
+            if share.partial {
+                parts.push("partial".to_string());
+            }
+            frame.field(&format!("   ▸ {}", short_address(&share.address).bright_cyan()), &parts.join(" · "), |s| s.bright_white());
+        }
+    }
 
-    println!(" {} {} PORTFOLIO SUMMARY                                                         {}",
-        vertical.bright_cyan(),
-        "📊".bright_blue(),
-        vertical.bright_cyan()
-    );
-    println!("{}", divider);
+    frame.section(&format!("{} PORTFOLIO SUMMARY", "📊".bright_blue()));
 
-    println!(" {} 🖼️  Total NFTs:       {}                                                        {}",
-        vertical.bright_cyan(),
-        report.total_nfts.to_string().bright_magenta().bold(),
-        vertical.bright_cyan()
-    );
+    if report.partial {
+        frame.field("🖼️  Total NFTs:", &format!("{}+", report.total_nfts), |s| s.bright_magenta().bold());
+        frame.row(&format!("   {} {}", "⚠️".bright_yellow(),
+            "PARTIAL: --max-assets cap reached, figures cover only the assets fetched".bright_yellow()));
+    } else {
+        frame.field("🖼️  Total NFTs:", &report.total_nfts.to_string(), |s| s.bright_magenta().bold());
+    }
+    let b = &report.breakdown;
+    frame.field("🧩 Breakdown:", &format!(
+        "{} NFTs · {} compressed · {} Core · {} fungible · {} other · {} burnt",
+        b.nfts, b.compressed_nfts, b.core_assets, b.fungibles, b.other, b.burnt
+    ), |s| s.bright_white());
+    if b.spam > 0 {
+        frame.field("🚫 Spam:", &format!("{} assets excluded from the assessment", b.spam), |s| s.bright_red());
+        for asset in report.spam.iter().take(3) {
+            frame.field(&format!("   ▸ {}", asset.name.dimmed()), &asset.reasons.join("; "), |s| s.red());
+        }
+    }
 
-    println!(" {} 💎 Top Collections:                                                          {}",
-        vertical.bright_cyan(),
-        vertical.bright_cyan()
-    );
+    frame.row("💎 Top Collections:");
+
+    // Name column takes whatever the rank and count leave over
+    let count_width = report.collections.iter().take(10)
+        .map(|c| format!("{} NFTs", c.count).len())
+        .max()
+        .unwrap_or(0);
+    let name_width = frame.inner_width().saturating_sub(4 + 4 + 1 + count_width);
 
     let top_collections = report.collections.iter().take(10);
     for (i, collection) in top_collections.enumerate() {
-        let rank = format!("{}.", i + 1).bright_yellow();
+        let rank = format!("{:<3}", format!("{}.", i + 1)).bright_yellow();
+        let label = match &collection.symbol {
+            Some(symbol) => format!("{} {}", collection.name.bright_cyan().bold(), format!("({})", symbol).dimmed()),
+            None => collection.name.bright_cyan().bold().to_string(),
+        };
+        let name = pad_to(&label, name_width);
         let count_str = format!("{} NFTs", collection.count).bright_white();
-        println!(" {}    {} {:<40} {}                                          {}",
-            vertical.bright_cyan(),
-            rank,
-            collection.name.bright_cyan().bold(),
-            count_str,
-            vertical.bright_cyan()
+        frame.row(&format!("    {} {} {}", rank, name, count_str));
+        if !report.wallets.is_empty() && collection.by_wallet.len() > 1 {
+            let split: Vec<String> = collection.by_wallet.iter()
+                .map(|(wallet, count)| format!("{} {}", short_address(wallet), count))
+                .collect();
+            frame.row(&format!("        {}", split.join(" · ").dimmed()));
+        }
+    }
+
+    if let Some(balances) = &report.balances {
+        print_balances(&frame, balances);
+    }
+
+    if let Some(activity) = &report.activity {
+        print_activity(&frame, activity);
+    }
+
+    frame.blank();
+
+    frame.field("📝 Assessment:", &assessment(&report), |s| s.bright_green().italic());
+    for flag in report.flags.iter() {
+        frame.row(&format!("⚑  {} {}", flag.rule.bright_cyan().bold(), flag.detail.bright_yellow()));
+    }
+    for warning in report.warnings.iter() {
+        frame.row(&format!("⚠️  {}", warning.bright_yellow()));
+    }
+
+    frame.bottom();
+
+    print_footer("Dossier complete");
+}
+
+fn print_balances(frame: &Frame, balances: &WalletBalances) {
+    frame.section(&format!("{} BALANCES", "💰".bright_yellow()));
+    frame.field("◎ SOL:", &format!("{:.4} SOL", balances.sol_lamports as f64 / LAMPORTS_PER_SOL), |s| s.bright_white().bold());
+
+    if balances.tokens.is_empty() {
+        frame.field("🪙 Tokens:", "none", |s| s.dimmed());
+    } else {
+        frame.field("🪙 Tokens:", &format!("{} fungible balances", balances.tokens.len()), |s| s.bright_white());
+        for token in balances.tokens.iter().take(10) {
+            let label = token.symbol.clone().unwrap_or_else(|| short_address(&token.mint));
+            let program = if token.program == "token-2022" { " (Token-2022)" } else { "" };
+            frame.field(&format!("   ▸ {}", label.bright_cyan().bold()), &format!("{}{}", format_amount(token.ui_amount), program), |s| s.bright_white());
+        }
+    }
+
+    if !balances.stakes.is_empty() {
+        let staked: u64 = balances.stakes.iter().map(|s| s.lamports).sum();
+        frame.field("🥩 Stake:", &format!("{} accounts, {:.4} SOL", balances.stakes.len(), staked as f64 / LAMPORTS_PER_SOL), |s| s.bright_white());
+        for stake in balances.stakes.iter().take(5) {
+            let voter = stake.voter.as_deref().map(short_address).unwrap_or_else(|| "not delegated".to_string());
+            frame.field(&format!("   ▸ {}", short_address(&stake.address)), &format!("{:.4} SOL · {} · {}", stake.lamports as f64 / LAMPORTS_PER_SOL, stake.state, voter), |s| s.bright_white());
+        }
+    }
+
+    if !balances.empty_accounts.is_empty() {
+        let rent: u64 = balances.empty_accounts.iter().map(|a| a.lamports).sum();
+        frame.field("🧹 Reclaimable:", &format!("{:.4} SOL in rent from {} empty token accounts", rent as f64 / LAMPORTS_PER_SOL, balances.empty_accounts.len()), |s| s.bright_green());
+    }
+
+    for warning in balances.warnings.iter() {
+        frame.row(&format!("⚠️  {}", warning.bright_yellow()));
+    }
+}
+
+fn print_activity(frame: &Frame, activity: &ActivityProfile) {
+    frame.section(&format!("{} ACTIVITY", "🕰️".bright_blue()));
+
+    match (activity.first_seen, activity.last_active) {
+        (Some(first), Some(last)) => {
+            let first_label = if activity.truncated {
+                format!("{} or earlier", format_date(first))
+            } else {
+                format_date(first)
+            };
+            frame.field("📅 First seen:", &first_label, |s| s.bright_white());
+            frame.field("⏱️  Last active:", &format_date(last), |s| s.bright_white());
+        }
+        _ => frame.field("📅 History:", "no transactions found", |s| s.dimmed()),
+    }
+
+    let count = if activity.truncated { format!("{}+", activity.tx_count) } else { activity.tx_count.to_string() };
+    frame.field("🔁 Transactions:", &format!("{} ({} failed)", count, activity.failed_tx_count), |s| s.bright_white());
+
+    if !activity.monthly.is_empty() {
+        frame.row("📈 Last months:");
+        let recent: Vec<_> = activity.monthly.iter().rev().take(6).rev().collect();
+        let peak = recent.iter().map(|m| m.count).max().unwrap_or(1).max(1);
+        for month in recent {
+            let bar = "█".repeat(((month.count * 20).div_ceil(peak)) as usize);
+            frame.row(&format!("    {} {} {}", month.month.dimmed(), bar.bright_magenta(), month.count));
+        }
+    }
+
+    if !activity.programs.is_empty() {
+        frame.row("🧭 Programs:");
+        for program in activity.programs.iter().take(5) {
+            let label = program.name.clone().unwrap_or_else(|| short_address(&program.program));
+            frame.field(&format!("   ▸ {}", label.bright_cyan()), &format!("{} txs", program.count), |s| s.bright_white());
+        }
+    }
+
+    let t = &activity.trades;
+    if t.analysed > 0 {
+        frame.field("🛒 NFT activity:", &format!(
+            "{} mints · {} buys · {} sells · {} in · {} out",
+            t.mints, t.buys, t.sells, t.transfers_in, t.transfers_out
+        ), |s| s.bright_white());
+    }
+    if let Some(days) = activity.avg_holding_days {
+        frame.field("⏳ Average hold:", &format!("{:.1} days", days), |s| s.bright_white());
+    }
+
+    frame.field("🧠 Profile:", activity.behaviour.label(), |s| s.bright_green().bold());
+    for evidence in activity.evidence.iter() {
+        frame.field("   ▸", evidence, |s| s.bright_white());
+    }
+
+    for warning in activity.warnings.iter() {
+        frame.row(&format!("⚠️  {}", warning.bright_yellow()));
+    }
+}
+
+fn short_address(address: &str) -> String {
+    if address.len() > 10 {
+        format!("{}...{}", &address[..4], &address[address.len() - 4..])
+    } else {
+        address.to_string()
+    }
+}
+
+// Thousands separators for the integer part, up to 4 decimals.
+fn format_amount(amount: f64) -> String {
+    let text = format!("{:.4}", amount);
+    let (int_part, frac) = text.split_once('.').unwrap_or((&text, ""));
+    let mut grouped = String::new();
+    for (i, c) in int_part.chars().rev().enumerate() {
+        if i > 0 && i % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    let grouped: String = grouped.chars().rev().collect();
+    let frac = frac.trim_end_matches('0');
+    if frac.is_empty() { grouped } else { format!("{}.{}", grouped, frac) }
+}
+
+// Generate assessment
+// Behavioural label first when the activity profile has one, then what the holdings say.
+fn assessment(report: &DossierReport) -> String {
+    match report.activity.as_ref().map(|a| a.behaviour) {
+        Some(behaviour) if behaviour != Behaviour::Unknown && !report.assessment.is_empty() => format!("{}. {}", behaviour.label(), report.assessment),
+        Some(behaviour) if behaviour != Behaviour::Unknown => format!("{}.", behaviour.label()),
+        _ => report.assessment.clone(),
+    }
+}
+
+fn print_footer(done: &str) {
+    if !style::current().plain {
+        println!("\n  {} Powered by {} | {} ✓\n",
+            "⚡".bright_yellow(),
+            "shinobi".bright_purple().bold(),
+            done
         );
     }
+}
 
-    println!(" {}                                                                                {}",
-        vertical.bright_cyan(),
-        vertical.bright_cyan()
-    );
+pub fn print_json<T: serde::Serialize>(value: &T) -> anyhow::Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
 
-    // Generate assessment
-    let assessment = if !report.collections.is_empty() {
-        let top = &report.collections[0];
-        if top.count as f32 / report.total_nfts as f32 > 0.3 {
-            format!("High-conviction '{}' holder.", top.name)
-        } else if report.collections.len() > 5 {
-            "Diversified collector across multiple collections.".to_string()
-        } else {
-            "Selective NFT holder.".to_string()
+// --quiet output: one "key: value" per line, nothing else.
+fn fact(key: &str, value: impl std::fmt::Display) {
+    println!("{}: {}", key, value);
+}
+
+fn print_unmask_quiet(report: &UnmaskReport) {
+    fact("name", &report.off_chain.name);
+    fact("mint", report.on_chain.mint);
+    fact("update_authority", report.on_chain.update_authority);
+    fact("mutable", report.on_chain.is_mutable);
+    fact("image", &report.off_chain.image);
+    if let Some(rarity) = &report.rarity {
+        fact("rarity_rank", rarity.rank.map(|r| r.to_string()).unwrap_or_else(|| "?".to_string()));
+        fact("rarity_score", rarity.method.format_score(rarity.score));
+    }
+    if !report.asset_health.is_empty() {
+        let broken = report.asset_health.iter().filter(|h| !h.issues().is_empty()).count();
+        fact("broken_assets", broken);
+    }
+    fact("suspected_spam", !report.spam.is_empty());
+    for reason in report.spam.iter() {
+        fact("spam_reason", reason);
+    }
+    if let Some(risk) = &report.risk {
+        fact("risk_score", risk.score);
+        fact("risk_level", &risk.level);
+        for flag in risk.flags.iter() {
+            fact("risk_flag", format!("{} +{}", flag.rule, flag.weight));
+        }
+    }
+}
+
+fn print_dossier_quiet(report: &DossierReport) {
+    if report.wallets.is_empty() {
+        fact("wallet", &report.wallet_address);
+    } else {
+        for share in report.wallets.iter() {
+            fact("wallet", format!("{} {} NFTs", share.address, share.nfts));
+        }
+    }
+    fact("total_nfts", report.total_nfts);
+    fact("partial", report.partial);
+    fact("spam", report.breakdown.spam);
+    if let Some(balances) = &report.balances {
+        fact("sol", format!("{:.4}", balances.sol_lamports as f64 / LAMPORTS_PER_SOL));
+        fact("fungible_tokens", balances.tokens.len());
+        fact("stake_accounts", balances.stakes.len());
+        fact("empty_token_accounts", balances.empty_accounts.len());
+    }
+    if let Some(activity) = &report.activity {
+        fact("behaviour", activity.behaviour.label());
+        fact("tx_count", activity.tx_count);
+        if let (Some(first), Some(last)) = (activity.first_seen, activity.last_active) {
+            fact("first_seen", format_date(first));
+            fact("last_active", format_date(last));
         }
+    }
+    if let Some(top) = report.collections.first() {
+        fact("top_collection", format!("{} ({})", top.name, top.count));
+    }
+    fact("assessment", assessment(report));
+    for flag in report.flags.iter() {
+        fact("flag", format!("{} {}", flag.rule, flag.detail));
+    }
+}
+
+// Animated GIF/APNG/WebP get the animation treatment, everything else a single render.
+async fn print_image(bytes: &[u8], opts: &ImageOptions) -> anyhow::Result<()> {
+    match render::decode_animation(bytes, opts.max_frames)? {
+        Some(animation) => {
+            say(&format!("   {} animated {}, {} frames", "▶".bright_magenta(), animation.format, animation.frames.len()));
+            render::print_animation(&animation, opts).await
+        }
+        None => render::print(&render::decode(bytes)?, opts),
+    }
+}
+
+// One headline plus indented detail lines for a probed URL.
+fn health_lines(health: &AssetHealth) -> Vec<String> {
+    let issues = health.issues();
+    let (icon, verdict) = if issues.is_empty() {
+        ("✅", "OK".bright_green().bold())
     } else {
-        "No NFT collections detected.".to_string()
+        ("❌", "BROKEN".bright_red().bold())
     };
 
-    println!(" {} 📝 Assessment:        {}                                                  {}",
-        vertical.bright_cyan(),
-        assessment.bright_green().italic(),
-        vertical.bright_cyan()
-    );
+    let status = health.status.map(|s| s.to_string()).unwrap_or_else(|| "—".to_string());
+    let mut lines = vec![format!("{} {:<10} {}  HTTP {}  {} ms",
+        icon,
+        health.label.bright_cyan().bold(),
+        verdict,
+        status.bright_white(),
+        health.latency_ms
+    )];
+
+    lines.push(format!("   ▸ {}", health.url.dimmed()));
+
+    if !health.redirects.is_empty() {
+        lines.push(format!("   ▸ {} redirect(s) → {}", health.redirects.len(), health.redirects.last().unwrap()));
+    }
+
+    let mut facts = Vec::new();
+    if let Some(fmt) = &health.detected_format {
+        facts.push(format!("{} ({})", fmt, media_kind(fmt)));
+    }
+    if let Some(ct) = &health.content_type {
+        facts.push(format!("served as {}", ct));
+    }
+    if let Some(size) = health.size_bytes {
+        facts.push(format_bytes(size));
+    }
+    if let Some((w, h)) = health.dimensions {
+        facts.push(format!("{}x{}", w, h));
+    }
+    if !facts.is_empty() {
+        lines.push(format!("   ▸ {}", facts.join(" · ").bright_white()));
+    }
+
+    for issue in issues {
+        lines.push(format!("   ⚠️  {}", issue.bright_red()));
+    }
+    lines
+}
+
+// println that honours --plain.
+fn say(line: &str) {
+    println!("{}", style::clean(line));
+}
 
-    println!("{}", format!("{}{}{}",
-        bottom_left.bright_cyan(),
-        horizontal.repeat(67).bright_cyan(),
-        bottom_right.bright_cyan()
+fn format_bytes(size: u64) -> String {
+    if size >= 1024 * 1024 {
+        format!("{:.1} MB", size as f64 / (1024.0 * 1024.0))
+    } else if size >= 1024 {
+        format!("{:.1} KB", size as f64 / 1024.0)
+    } else {
+        format!("{} B", size)
+    }
+}
+
+pub fn print_audit_report(report: AuditReport) {
+    let broken: Vec<_> = report.entries.iter()
+        .filter(|e| e.error.is_some() || e.assets.iter().any(|a| !a.issues().is_empty()))
+        .collect();
+
+    // Quiet mode is a bare broken-link list: mint, then its problems
+    if style::current().quiet {
+        for entry in broken {
+            let mut problems: Vec<String> = entry.error.iter().cloned().collect();
+            for health in entry.assets.iter() {
+                problems.extend(health.issues().into_iter().map(|i| format!("{} {}: {}", health.label, health.url, i)));
+            }
+            println!("{}\t{}", entry.mint, problems.join("; "));
+        }
+        return;
+    }
+
+    say(&format!("\n🥷 {} {} mints, {} with problems\n",
+        "Asset audit:".bright_purple().bold(),
+        report.entries.len().to_string().bright_white().bold(),
+        broken.len().to_string().bright_red().bold()
     ));
 
-    println!("\n  {} Powered by {} | Dossier complete ✓\n",
-        "⚡".bright_yellow(),
-        "shinobi".bright_purple().bold()
-    );
-}
\ No newline at end of file
+    for entry in report.entries.iter() {
+        let name = entry.name.clone().unwrap_or_else(|| "?".to_string());
+        if let Some(err) = &entry.error {
+            say(&format!("❌ {} {}", entry.mint.bright_white(), name.dimmed()));
+            say(&format!("   ⚠️  {}", err.bright_red()));
+            continue;
+        }
+
+        let problems: Vec<&AssetHealth> = entry.assets.iter().filter(|a| !a.issues().is_empty()).collect();
+        if problems.is_empty() {
+            say(&format!("✅ {} {}", entry.mint.bright_white(), name.dimmed()));
+            continue;
+        }
+
+        say(&format!("❌ {} {}", entry.mint.bright_white(), name.bright_yellow()));
+        for health in problems {
+            for line in health_lines(health) {
+                say(&format!("   {}", line));
+            }
+        }
+    }
+
+    print_footer("Audit complete");
+}
+
+pub fn print_trace_report(report: TraceReport) {
+    if style::current().quiet {
+        fact("wallet", &report.wallet);
+        fact("hops", report.hops.len());
+        fact("source", report.source.as_deref().unwrap_or("unknown"));
+        fact("stopped", &report.stopped);
+        return;
+    }
+
+    let frame = Frame::new(16);
+    frame.top();
+    frame.row(&format!("{}  [Trace] {}", "🎯".bright_yellow(), report.wallet.bright_white().bold()));
+    frame.section(&format!("{} FUNDING CHAIN", "🔗".bright_blue()));
+
+    if report.hops.is_empty() {
+        frame.row(&"No funding transfer found".dimmed().to_string());
+    }
+    for (i, hop) in report.hops.iter().enumerate() {
+        frame.field(&format!("⬅️  Hop {}:", i + 1), &hop.funder, |s| s.bright_cyan().bold());
+        if hop.service {
+            frame.field("", "exchange or service, not followed further", |s| s.bright_yellow());
+        }
+        let when = hop.timestamp.map(format_date).unwrap_or_else(|| "unknown date".to_string());
+        frame.field("", &format!(
+            "sent {:.4} SOL to {} on {}",
+            hop.lamports as f64 / LAMPORTS_PER_SOL, short_address(&hop.wallet), when
+        ), |s| s.dimmed());
+        frame.field("", &hop.signature, |s| s.dimmed());
+    }
+
+    frame.blank();
+    frame.field("🏁 Source:", report.source.as_deref().unwrap_or("unknown"), |s| s.bright_green().bold());
+    frame.field("🛑 Stopped:", &report.stopped, |s| s.bright_yellow());
+    frame.bottom();
+
+    print_footer("Trace complete");
+}
+
+pub fn print_cluster_report(report: ClusterReport) {
+    // Quiet mode: one cluster per line, members separated by spaces
+    if style::current().quiet {
+        for cluster in report.clusters.iter() {
+            println!("{}", cluster.members.join(" "));
+        }
+        return;
+    }
+
+    say(&format!("\n🥷 {} {} wallets, {} clusters, {} unlinked\n",
+        "Wallet clustering:".bright_purple().bold(),
+        report.wallets.len().to_string().bright_white().bold(),
+        report.clusters.len().to_string().bright_red().bold(),
+        report.unclustered.len()
+    ));
+
+    for (i, cluster) in report.clusters.iter().enumerate() {
+        say(&format!("🕸️  {} ({} wallets)", format!("Cluster {}", i + 1).bright_yellow().bold(), cluster.members.len()));
+        for member in cluster.members.iter() {
+            let source = report.traces.iter()
+                .find(|t| &t.wallet == member)
+                .and_then(|t| t.source.as_deref())
+                .map(|s| format!("funded from {}", short_address(s)))
+                .unwrap_or_default();
+            say(&format!("   {} {}", member.bright_white(), source.dimmed()));
+        }
+        for link in cluster.links.iter() {
+            say(&format!("   ▸ {}", link.bright_cyan()));
+        }
+        println!();
+    }
+
+    if !report.unclustered.is_empty() {
+        say(&format!("{} {}", "Unlinked:".dimmed(), report.unclustered.join(", ")));
+    }
+    for warning in report.warnings.iter() {
+        say(&format!("⚠️  {}", warning.bright_yellow()));
+    }
+
+    print_footer("Clustering complete");
+}
+
+// Graphviz output: wallets as boxes, funders as ellipses, co-signers as diamonds.
+pub fn print_dot(graph: &LinkGraph) {
+    println!("digraph shinobi {{");
+    println!("  rankdir=LR;");
+    println!("  node [fontname=\"monospace\"];");
+    for node in graph.nodes.iter() {
+        let shape = match node.kind.as_str() {
+            "wallet" => "box",
+            "authority" => "diamond",
+            "service" => "octagon",
+            _ => "ellipse",
+        };
+        println!("  \"{}\" [label=\"{}\", shape={}];", node.id, short_address(&node.id), shape);
+    }
+    for edge in graph.edges.iter() {
+        let label = match edge.lamports {
+            Some(lamports) => format!("{} {:.4} SOL", edge.kind, lamports as f64 / LAMPORTS_PER_SOL),
+            None => edge.kind.clone(),
+        };
+        println!("  \"{}\" -> \"{}\" [label=\"{}\"];", edge.from, edge.to, label);
+    }
+    println!("}}");
+}
+
+pub fn print_compare_report(report: CompareReport) {
+    let verdict = match report.similarity {
+        60.. => "Very likely related",
+        30..=59 => "Possibly related",
+        _ => "No strong link",
+    };
+
+    if style::current().quiet {
+        fact("similarity", report.similarity);
+        fact("verdict", verdict);
+        fact("shared_collections", report.shared_collections.len());
+        fact("direct_transfers", report.transfers.len());
+        fact("common_counterparties", report.common_counterparties.len());
+        return;
+    }
+
+    let frame = Frame::new(20);
+    frame.top();
+    frame.row(&format!("{}  [Compare] {} ⇄ {}", "🎯".bright_yellow(),
+        short_address(&report.wallet_a).bright_white().bold(),
+        short_address(&report.wallet_b).bright_white().bold()
+    ));
+
+    frame.section(&format!("{} HOLDINGS", "📊".bright_blue()));
+    let partial = if report.partial { "+" } else { "" };
+    for (label, wallet, nfts, collections) in [
+        ("🅰️  Wallet A:", &report.wallet_a, report.nfts_a, report.collections_a),
+        ("🅱️  Wallet B:", &report.wallet_b, report.nfts_b, report.collections_b),
+    ] {
+        frame.field(label, wallet, |s| s.bright_white());
+        frame.field("", &format!("{}{} NFTs in {} collections", nfts, partial, collections), |s| s.dimmed());
+    }
+
+    frame.section(&format!("{} SHARED COLLECTIONS", "💎".bright_blue()));
+    if report.shared_collections.is_empty() {
+        frame.row(&"None".dimmed().to_string());
+    }
+    for collection in report.shared_collections.iter().take(10) {
+        frame.field(&format!("   ▸ {}", collection.name.bright_cyan().bold()),
+            &format!("A {} · B {}", collection.count_a, collection.count_b), |s| s.bright_white());
+    }
+
+    frame.section(&format!("{} DIRECT TRANSFERS", "🔁".bright_blue()));
+    if report.transfers.is_empty() {
+        frame.row(&"None found in the recent history".dimmed().to_string());
+    }
+    for transfer in report.transfers.iter().take(10) {
+        let direction = if transfer.from == report.wallet_a { "A → B" } else { "B → A" };
+        let what = match &transfer.mint {
+            Some(mint) => format!("{} × {}", format_amount(transfer.amount), short_address(mint)),
+            None => format!("{:.4} SOL", transfer.amount),
+        };
+        let when = transfer.timestamp.map(format_date).unwrap_or_default();
+        frame.field(&format!("   {}", direction.bright_yellow()), &format!("{} {}", what, when), |s| s.bright_white());
+    }
+
+    if !report.common_counterparties.is_empty() {
+        frame.section(&format!("{} COMMON COUNTERPARTIES", "🤝".bright_blue()));
+        for party in report.common_counterparties.iter().take(5) {
+            let label = party.name.clone().unwrap_or_else(|| short_address(&party.address));
+            frame.field(&format!("   ▸ {}", label.bright_cyan()),
+                &format!("A {} · B {} transfers", party.with_a, party.with_b), |s| s.bright_white());
+        }
+    }
+
+    frame.blank();
+    frame.field("🧮 Similarity:", &format!("{}/100 · {}", report.similarity, verdict), |s| s.bright_green().bold());
+    for factor in report.factors.iter() {
+        frame.field("   ▸", factor, |s| s.bright_white());
+    }
+    for warning in report.warnings.iter() {
+        frame.row(&format!("⚠️  {}", warning.bright_yellow()));
+    }
+    frame.bottom();
+
+    print_footer("Comparison complete");
+}
+
+pub fn print_collection_report(report: CollectionReport) {
+    let escrow_share = if report.supply == 0 { 0.0 } else { report.escrowed as f64 / report.supply as f64 };
+    let supply = if report.partial { format!("{}+", report.supply) } else { report.supply.to_string() };
+
+    if style::current().quiet {
+        fact("collection", &report.collection);
+        fact("name", report.name.as_deref().unwrap_or("?"));
+        fact("supply", &supply);
+        fact("burned", report.burned);
+        fact("unique_holders", report.unique_holders);
+        fact("top10_share", format!("{:.3}", report.top10_share));
+        fact("gini", format!("{:.3}", report.gini));
+        fact("escrowed_share", format!("{:.3}", escrow_share));
+        return;
+    }
+
+    let frame = Frame::new(20);
+    frame.top();
+    let title = match (&report.name, &report.symbol) {
+        (Some(name), Some(symbol)) => format!("{} ({})", name, symbol),
+        (Some(name), None) => name.clone(),
+        _ => report.collection.clone(),
+    };
+    frame.row(&format!("{}  [Collection] {}", "🎯".bright_yellow(), title.bright_white().bold()));
+    if report.name.is_some() {
+        frame.row(&format!("    {}", report.collection.dimmed()));
+    }
+
+    frame.section(&format!("{} SUPPLY", "📊".bright_blue()));
+    frame.field("🖼️  Supply:", &supply, |s| s.bright_magenta().bold());
+    if report.partial {
+        frame.row(&format!("   {} {}", "⚠️".bright_yellow(),
+            "PARTIAL: --max-assets cap reached, figures cover only the members fetched".bright_yellow()));
+    }
+    frame.field("🔥 Burned:", &report.burned.to_string(), |s| s.bright_white());
+    frame.field("👥 Holders:", &report.unique_holders.to_string(), |s| s.bright_white());
+    frame.field("🔒 In escrow:", &format!("{} ({:.1}%)", report.escrowed, escrow_share * 100.0), |s| s.bright_white());
+
+    frame.section(&format!("{} CONCENTRATION", "🧮".bright_blue()));
+    frame.field("🔝 Top 10 hold:", &format!("{:.1}% of the supply", report.top10_share * 100.0), |s| s.bright_white());
+    frame.field("⚖️  Gini:", &format!("{:.3}", report.gini), |s| s.bright_white());
+
+    frame.row("📈 Distribution:");
+    let peak = report.distribution.iter().map(|b| b.holders).max().unwrap_or(1).max(1);
+    for bucket in report.distribution.iter() {
+        let bar = "█".repeat(((bucket.holders * 20).div_ceil(peak)) as usize);
+        frame.row(&format!("    {} {} {} holders, {} NFTs",
+            format!("{:>6}", bucket.label).dimmed(), bar.bright_magenta(), bucket.holders, bucket.nfts));
+    }
+
+    frame.row("🐋 Top holders:");
+    for (i, holder) in report.top_holders.iter().enumerate() {
+        let rank = format!("{:<3}", format!("{}.", i + 1)).bright_yellow();
+        frame.row(&format!("    {} {} {} NFTs ({:.1}%)",
+            rank, holder.address.bright_cyan(), holder.count, holder.share * 100.0));
+    }
+
+    frame.blank();
+    frame.field("🛰️  Source:", &report.source, |s| s.dimmed());
+    for warning in report.warnings.iter() {
+        frame.row(&format!("⚠️  {}", warning.bright_yellow()));
+    }
+    frame.bottom();
+
+    print_footer("Survey complete");
+}
+
+// Goes to stderr so a snapshot piped from stdout stays clean.
+pub fn print_snapshot_summary(report: &SnapshotReport, output: Option<&std::path::Path>) {
+    if style::current().quiet {
+        return;
+    }
+    let mints: u32 = report.holders.iter().map(|h| h.count).sum();
+    eprintln!("{}", style::clean(&format!("📸 Snapshot of {} {} at slot {} (finished at {})",
+        report.kind, report.target, report.slot, report.end_slot)));
+    eprintln!("{}", style::clean(&format!("   {} holders, {} mints, {} burned, {} excluded{}",
+        report.holders.len(), mints, report.burned, report.excluded,
+        if report.partial { ", PARTIAL: --max-assets cap reached" } else { "" })));
+    if let Some(path) = output {
+        eprintln!("{}", style::clean(&format!("   💾 Written to {}", path.display())));
+    }
+    for warning in report.warnings.iter() {
+        eprintln!("{}", style::clean(&format!("⚠️  {}", warning)));
+    }
+}
+
+pub fn print_hashlist_diff(diff: &HashlistDiff) {
+    if style::current().quiet {
+        for mint in diff.added.iter() {
+            println!("+{}", mint);
+        }
+        for mint in diff.removed.iter() {
+            println!("-{}", mint);
+        }
+        return;
+    }
+
+    say(&format!("\n🥷 {} {} saved, {} now: {} added, {} removed\n",
+        "Hash list diff:".bright_purple().bold(),
+        diff.previous, diff.current,
+        diff.added.len().to_string().bright_green().bold(),
+        diff.removed.len().to_string().bright_red().bold()
+    ));
+    for mint in diff.added.iter() {
+        say(&format!("  {} {}", "+".bright_green().bold(), mint));
+    }
+    for mint in diff.removed.iter() {
+        say(&format!("  {} {}", "-".bright_red().bold(), mint));
+    }
+
+    print_footer("Diff complete");
+}
+
+pub fn print_rarity_report(report: RarityReport, top: usize) {
+    let members = if report.partial { format!("{}+", report.members) } else { report.members.to_string() };
+
+    if style::current().quiet {
+        fact("collection", &report.collection);
+        fact("members", &members);
+        fact("method", report.method.label());
+        for item in report.ranking.iter().take(top) {
+            fact("rank", format!("{} {} {}", item.rank, item.mint, report.method.format_score(item.score)));
+        }
+        return;
+    }
+
+    let frame = Frame::new(20);
+    frame.top();
+    let title = report.name.clone().unwrap_or_else(|| report.collection.clone());
+    frame.row(&format!("{}  [Rarity] {}", "💎".bright_cyan(), title.bright_white().bold()));
+    if report.name.is_some() {
+        frame.row(&format!("    {}", report.collection.dimmed()));
+    }
+
+    frame.section(&format!("{} TRAITS", "🧬".bright_blue()));
+    frame.field("🖼️  Members:", &members, |s| s.bright_magenta().bold());
+    if report.partial {
+        frame.row(&format!("   {} {}", "⚠️".bright_yellow(),
+            "PARTIAL: --max-assets cap reached, ranks cover only the members fetched".bright_yellow()));
+    }
+    frame.field("🧮 Method:", report.method.label(), |s| s.bright_white());
+    for summary in report.traits.iter() {
+        frame.row(&format!("   ▸ {} {} values, rarest {} ({})",
+            format!("{}:", summary.trait_type).bright_cyan().bold(), summary.values,
+            summary.rarest.bright_white(), summary.rarest_count));
+    }
+
+    frame.section(&format!("{} LEADERBOARD", "🏆".bright_blue()));
+    for item in report.ranking.iter().take(top) {
+        let rank = format!("{:<5}", format!("{}.", item.rank)).bright_yellow();
+        let name = item.name.as_deref().unwrap_or("");
+        frame.row(&format!("    {} {} {} {}", rank, name.bright_white(), short_address(&item.mint).dimmed(),
+            report.method.format_score(item.score).bright_magenta()));
+    }
+    if report.ranking.len() > top {
+        frame.row(&format!("    {}", format!("... {} more (use --format to export them all)", report.ranking.len() - top).dimmed()));
+    }
+
+    frame.blank();
+    let built = format!("{} via {}{}", format_date(report.built_at), report.source, if report.cached { ", cached" } else { "" });
+    frame.field("🛰️  Trait table:", &built, |s| s.dimmed());
+    frame.bottom();
+
+    print_footer("Ranking complete");
+}
+
+pub fn print_search_report(report: SearchReport) {
+    if style::current().quiet {
+        fact("collection", &report.collection);
+        fact("scanned", report.scanned);
+        fact("matches", report.matches.len());
+        for m in report.matches.iter() {
+            fact("match", format!("{} {}", m.mint, m.holder.as_deref().unwrap_or("?")));
+        }
+        return;
+    }
+
+    let frame = Frame::new(20);
+    frame.top();
+    frame.row(&format!("{}  [Search] {}", "🔎".bright_yellow(), report.collection.bright_white().bold()));
+
+    frame.section(&format!("{} FILTERS", "🧪".bright_blue()));
+    if report.filters.is_empty() {
+        frame.row(&format!("   {}", "none, every member matches".dimmed()));
+    }
+    for filter in report.filters.iter() {
+        frame.row(&format!("   ▸ {}", filter.bright_cyan()));
+    }
+    let scanned = if report.partial { format!("{}+", report.scanned) } else { report.scanned.to_string() };
+    frame.field("🖼️  Checked:", &scanned, |s| s.bright_white());
+    if report.partial {
+        frame.row(&format!("   {} {}", "⚠️".bright_yellow(),
+            "PARTIAL: --max-assets cap reached, only the members fetched were searched".bright_yellow()));
+    }
+    frame.field("🎯 Matches:", &report.matches.len().to_string(), |s| s.bright_magenta().bold());
+
+    if !report.matches.is_empty() {
+        frame.section(&format!("{} MATCHES", "📜".bright_blue()));
+        for m in report.matches.iter() {
+            let holder = m.holder.as_deref().map(short_address).unwrap_or_else(|| "?".to_string());
+            frame.row(&format!("   {} {}  held by {}", m.name.bright_white(), m.mint.dimmed(), holder.bright_cyan()));
+        }
+    }
+
+    frame.blank();
+    frame.field("🛰️  Source:", &report.source, |s| s.dimmed());
+    for warning in report.warnings.iter() {
+        frame.row(&format!("⚠️  {}", warning.bright_yellow()));
+    }
+    frame.bottom();
+
+    print_footer("Search complete");
+}
+
+pub fn print_authority_report(report: AuthorityReport) {
+    let total = if report.partial { format!("{}+", report.total) } else { report.total.to_string() };
+    let recent = report.recent_updates.iter().filter(|u| u.recent).count();
+
+    if style::current().quiet {
+        fact("authority", &report.authority);
+        fact("items", &total);
+        fact("collections", report.collections.len());
+        fact("mutable", report.mutable);
+        fact("recent_updates", recent);
+        return;
+    }
+
+    let frame = Frame::new(20);
+    frame.top();
+    frame.row(&format!("{}  [Authority] {}", "🗝️".bright_yellow(), report.authority.bright_white().bold()));
+
+    frame.section(&format!("{} FOOTPRINT", "📊".bright_blue()));
+    frame.field("🖼️  Items:", &total, |s| s.bright_magenta().bold());
+    if report.partial {
+        frame.row(&format!("   {} {}", "⚠️".bright_yellow(),
+            "PARTIAL: --max-accounts cap reached, figures cover only the accounts kept".bright_yellow()));
+    }
+    frame.field("✍️  As authority:", &report.by_authority.to_string(), |s| s.bright_white());
+    frame.field("🎨 As creator:", &report.by_creator.to_string(), |s| s.bright_white());
+    let share = report.mutable as f64 / report.total.max(1) as f64;
+    let mutable = format!("{} ({:.1}%)", report.mutable, share * 100.0);
+    if report.mutable > 0 {
+        frame.field("🔓 Mutable:", &mutable, |s| s.bright_yellow().bold());
+    } else {
+        frame.field("🔒 Mutable:", &mutable, |s| s.bright_green());
+    }
+    frame.field("💸 Primary sold:", &report.primary_sold.to_string(), |s| s.bright_white());
+
+    frame.row("👑 Royalties:");
+    for royalty in report.royalties.iter().take(5) {
+        frame.row(&format!("    {} {} items",
+            format!("{:>6}", format!("{:.2}%", royalty.basis_points as f64 / 100.0)).dimmed(), royalty.count));
+    }
+
+    frame.section(&format!("{} COLLECTIONS", "🗂️".bright_blue()));
+    for group in report.collections.iter() {
+        let title = group.name.clone()
+            .or_else(|| group.collection.as_deref().map(short_address))
+            .or_else(|| group.symbol.as_ref().map(|s| format!("{} (no collection)", s)))
+            .unwrap_or_else(|| "No collection".to_string());
+        let royalty = if group.min_royalty_bps == group.max_royalty_bps {
+            format!("{:.2}%", group.min_royalty_bps as f64 / 100.0)
+        } else {
+            format!("{:.2}-{:.2}%", group.min_royalty_bps as f64 / 100.0, group.max_royalty_bps as f64 / 100.0)
+        };
+        frame.row(&format!("   ▸ {} {} items, {} mutable, royalty {}",
+            title.bright_cyan().bold(), group.count, group.mutable, royalty));
+        if group.via_authority < group.count {
+            frame.row(&format!("      {}", format!("{} only list the key as creator", group.count - group.via_authority).dimmed()));
+        }
+    }
+
+    frame.section(&format!("{} UPDATES", "🛠️".bright_blue()));
+    if report.recent_updates.is_empty() {
+        frame.row(&format!("   {}", "No metadata updates in the transactions checked".dimmed()));
+    }
+    for update in report.recent_updates.iter() {
+        let when = update.timestamp.map(format_date).unwrap_or_else(|| "?".to_string());
+        let line = format!("{} {} {}", when, update.name, short_address(&update.mint));
+        if update.recent {
+            frame.row(&format!("   {} {}", "⚠️".bright_yellow(), line.bright_yellow()));
+        } else {
+            frame.row(&format!("   ▸ {}", line.dimmed()));
+        }
+    }
+
+    for warning in report.warnings.iter() {
+        frame.row(&format!("⚠️  {}", warning.bright_yellow()));
+    }
+    frame.bottom();
+
+    print_footer("Authority mapped");
+}
+
+pub fn print_rule_tests(results: &[RuleTestReport]) {
+    for report in results.iter() {
+        let kind = report.kind.as_deref().unwrap_or("unknown report");
+        if style::current().quiet {
+            for result in report.results.iter() {
+                fact(&result.rule, if result.error.is_some() { "error" } else if result.matched { "match" } else { "no match" });
+            }
+            continue;
+        }
+
+        println!("{}", style::clean(&format!("{} {} {}", "📄".bright_blue(), report.file.bright_white().bold(), format!("({})", kind).dimmed())));
+        if report.results.is_empty() {
+            println!("   {}", "no rules apply to this report".dimmed());
+        }
+        for result in report.results.iter() {
+            let origin = if result.builtin { " (built-in)" } else { "" };
+            match (&result.error, &result.message) {
+                (Some(error), _) => println!("   {} {}{} {}", "✗".bright_red(), result.rule.bright_red(), origin.dimmed(), error),
+                (None, Some(message)) => println!("   {} {}{} {}", "✓".bright_green(), result.rule.bright_green(), origin.dimmed(), message),
+                (None, None) => println!("   {} {}{}", "·".dimmed(), result.rule.dimmed(), origin.dimmed()),
+            }
+        }
+        println!();
+    }
+}
+
+pub fn print_rules(rule_set: &RuleSet) {
+    let source = rule_set.source.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| "built-in rules only".to_string());
+    if !style::current().quiet {
+        println!("{}\n", style::clean(&format!("{} {}", "📜".bright_blue(), source.dimmed())));
+    }
+    for rule in rule_set.rules.iter() {
+        let report = format!("{:?}", rule.report).to_lowercase();
+        let weight = if rule.weight > 0 { format!(" +{}", rule.weight) } else { String::new() };
+        let role = if rule.assessment { " assessment" } else { "" };
+        println!("{} {}{}{}{}", rule.id.bright_cyan().bold(), format!("[{}]", report).dimmed(), role.dimmed(), weight.bright_yellow(),
+            if rule.builtin { " (built-in)".dimmed().to_string() } else { String::new() });
+        println!("    {}", rule.when);
+    }
+}
+
+pub fn print_inspect_report(report: InspectReport) {
+    let owner = match &report.owner_name {
+        Some(name) => format!("{} ({})", name, short_address(&report.owner)),
+        None => report.owner.clone(),
+    };
+    let kind = report.kind.clone().unwrap_or_else(|| "unknown".to_string());
+
+    if style::current().quiet {
+        fact("address", &report.address);
+        fact("owner", &report.owner);
+        fact("kind", &kind);
+        fact("lamports", report.lamports);
+        fact("data_len", report.data_len);
+        for field in report.fields.iter() {
+            fact(&field.name, &field.value);
+        }
+    } else {
+        let frame = Frame::new(20);
+        frame.top();
+        frame.row(&format!("{}  [Account] {}", "🔬".bright_yellow(), report.address.bright_white().bold()));
+
+        frame.section(&format!("{} ACCOUNT", "📦".bright_blue()));
+        frame.field("🏷️  Type:", &kind, |s| if report.kind.is_some() { s.bright_cyan().bold() } else { s.dimmed() });
+        frame.field("🧭 Owner:", &owner, |s| s.bright_white());
+        frame.field("💰 Balance:", &format!("{:.9} SOL", report.lamports as f64 / LAMPORTS_PER_SOL), |s| s.bright_green());
+        frame.field("📏 Data:", &format!("{} bytes", report.data_len), |s| s.bright_white());
+        if report.executable {
+            frame.field("⚙️  Executable:", "yes", |s| s.bright_magenta());
+        }
+
+        if !report.fields.is_empty() {
+            frame.section(&format!("{} FIELDS", "🧩".bright_blue()));
+            let width = report.fields.iter().map(|f| f.name.len()).max().unwrap_or(0).min(28);
+            for field in report.fields.iter() {
+                let offset = field.offset.map(|o| format!("@{:<5}", o)).unwrap_or_default();
+                frame.row(&format!("   {} {} {}", offset.dimmed(), pad_to(&field.name, width).bright_cyan(), field.value));
+            }
+        }
+
+        for warning in report.warnings.iter() {
+            frame.row(&format!("⚠️  {}", warning.bright_yellow()));
+        }
+        frame.bottom();
+    }
+
+    if let Some(hex) = &report.hex {
+        println!();
+        print!("{}", hex);
+    }
+
+    print_footer("Account inspected");
+}
+
+pub fn print_pda_report(report: PdaReport) {
+    if style::current().quiet {
+        for derivation in report.derivations.iter() {
+            fact(&derivation.name.to_lowercase().replace(' ', "_"), &derivation.address);
+        }
+        return;
+    }
+
+    let frame = Frame::new(20);
+    frame.top();
+    frame.row(&format!("{}  [PDAs] {}", "🧮".bright_yellow(), report.mint.bright_white().bold()));
+    frame.section(&format!("{} DERIVED ADDRESSES", "📍".bright_blue()));
+    for derivation in report.derivations.iter() {
+        let status = match derivation.exists {
+            Some(true) => format!(" {}", "✓ exists".bright_green()),
+            Some(false) => format!(" {}", "✗ missing".dimmed()),
+            None => String::new(),
+        };
+        frame.row(&format!("   ▸ {}{}", derivation.name.bright_cyan().bold(), status));
+        frame.row(&format!("     {}", derivation.address.bright_white()));
+        frame.row(&format!("     {}", format!("seeds: {} | bump {}", derivation.seeds, derivation.bump).dimmed()));
+        if let Some(owner) = &derivation.owner {
+            let name = programs::name(owner).map(str::to_string).unwrap_or_else(|| short_address(owner));
+            frame.row(&format!("     {}", format!("owned by {}", name).dimmed()));
+        }
+    }
+    frame.bottom();
+
+    print_footer("Addresses derived");
+}
+
+pub fn print_decode_diagnosis(diagnosis: &DecodeDiagnosis) {
+    if style::current().quiet {
+        fact("data_len", diagnosis.data_len);
+        fact("decoded", diagnosis.decoded);
+        fact("stopped_at", diagnosis.stopped_at);
+        if let Some(error) = &diagnosis.error {
+            fact("error", error);
+        }
+        return;
+    }
+
+    let frame = Frame::new(20);
+    frame.top();
+    frame.row(&format!("{}  [Decode] {} bytes", "🩺".bright_yellow(), diagnosis.data_len.to_string().bright_white().bold()));
+    frame.section(&format!("{} FIELDS", "🧩".bright_blue()));
+    let width = diagnosis.fields.iter().map(|f| f.name.len()).max().unwrap_or(0).min(36);
+    for field in diagnosis.fields.iter() {
+        let offset = field.offset.map(|o| format!("@{:<5}", o)).unwrap_or_default();
+        frame.row(&format!("   {} {} {}", offset.dimmed(), pad_to(&field.name, width).bright_cyan(), field.value));
+    }
+
+    frame.section(&format!("{} RESULT", "🔎".bright_blue()));
+    match &diagnosis.error {
+        Some(error) => {
+            frame.row(&format!("   {} {}", "✗".bright_red(), error.bright_red()));
+        }
+        None => {
+            frame.row(&format!("   {} {}", "✓".bright_green(), "every field decoded".bright_green()));
+            if let Some(trailing) = diagnosis.trailing.filter(|t| *t > 0) {
+                frame.row(&format!("   {}", format!("{} trailing bytes after the last field", trailing).dimmed()));
+            }
+        }
+    }
+    if diagnosis.error.is_none() != diagnosis.decoded {
+        frame.row(&format!("   {} {}", "⚠️".bright_yellow(),
+            "the Borsh decoder disagrees with the field walk; please report this".bright_yellow()));
+    }
+    frame.bottom();
+
+    if let Some(context) = &diagnosis.context {
+        println!();
+        print!("{}", context);
+    }
+}
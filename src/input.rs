@@ -0,0 +1,48 @@
+use anyhow::{anyhow, Result};
+use solana_sdk::pubkey::Pubkey;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+pub fn validate_address(address: &str) -> Result<()> {
+    Pubkey::from_str(address)
+        .map(|_| ())
+        .map_err(|e| anyhow!("Invalid address '{}': {}", address, e))
+}
+
+// Collect addresses from command-line arguments and an optional file.
+// Files may be a JSON array (the usual hash list format) or one address per line,
+// with '#' starting a comment. Duplicates are dropped, first occurrence wins.
+pub fn collect_addresses(args: Vec<String>, file: Option<&Path>) -> Result<Vec<String>> {
+    let mut addresses = args;
+
+    if let Some(path) = file {
+        let text = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read address file '{}': {}", path.display(), e))?;
+        addresses.extend(parse_address_list(&text)?);
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    addresses.retain(|a| seen.insert(a.clone()));
+
+    if addresses.is_empty() {
+        return Err(anyhow!("No addresses given. Pass them as arguments or with --file."));
+    }
+    Ok(addresses)
+}
+
+pub fn parse_address_list(text: &str) -> Result<Vec<String>> {
+    let trimmed = text.trim_start();
+    if trimmed.starts_with('[') {
+        let list: Vec<String> = serde_json::from_str(trimmed)
+            .map_err(|e| anyhow!("Address file looks like JSON but is not an array of strings: {}", e))?;
+        return Ok(list.into_iter().map(|a| a.trim().to_string()).filter(|a| !a.is_empty()).collect());
+    }
+
+    Ok(text
+        .lines()
+        .map(|l| l.split('#').next().unwrap_or("").trim())
+        .filter(|l| !l.is_empty())
+        .map(|l| l.trim_matches(|c| c == ',' || c == '"').to_string())
+        .collect())
+}
@@ -0,0 +1,44 @@
+use anyhow::Result;
+use crate::jutsu::unmask;
+use crate::model::{AuditEntry, AuditReport};
+use tokio::task::JoinSet;
+
+// Run the unmask health checks over a list of mints, a few at a time.
+pub async fn run(mints: Vec<String>, concurrency: usize) -> Result<AuditReport> {
+    let concurrency = concurrency.max(1);
+    let mut entries = Vec::with_capacity(mints.len());
+
+    for chunk in mints.chunks(concurrency) {
+        let mut tasks = JoinSet::new();
+        for (offset, mint) in chunk.iter().enumerate() {
+            let mint = mint.clone();
+            tasks.spawn(async move {
+                let entry = match unmask::run(mint.clone(), false, true, None, false).await {
+                    Ok(report) => AuditEntry {
+                        mint,
+                        name: Some(report.off_chain.name),
+                        error: None,
+                        assets: report.asset_health,
+                    },
+                    Err(e) => AuditEntry {
+                        mint,
+                        name: None,
+                        error: Some(e.to_string()),
+                        assets: Vec::new(),
+                    },
+                };
+                (offset, entry)
+            });
+        }
+
+        // Keep the input order so the report lines up with the mint list
+        let mut finished = Vec::with_capacity(chunk.len());
+        while let Some(joined) = tasks.join_next().await {
+            finished.push(joined?);
+        }
+        finished.sort_by_key(|(offset, _)| *offset);
+        entries.extend(finished.into_iter().map(|(_, entry)| entry));
+    }
+
+    Ok(AuditReport { entries })
+}
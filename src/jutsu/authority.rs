@@ -0,0 +1,165 @@
+use anyhow::{anyhow, Result};
+use crate::activity;
+use crate::input;
+use crate::jutsu::{collection, unmask};
+use crate::model::{AuthorityCollection, AuthorityReport, Metadata, MetadataUpdate, RoyaltyShare};
+use crate::programs::TOKEN_METADATA_PROGRAM;
+use crate::rpc::create_rpc_client;
+use crate::style;
+use crate::transactions;
+use std::collections::{BTreeMap, HashSet};
+use std::io::{stderr, IsTerminal, Write};
+
+// Updates within this many days are flagged as recent.
+const RECENT_DAYS: i64 = 30;
+const DAY: i64 = 86_400;
+// Collection NFTs looked up for a name, biggest groups first.
+const NAME_LOOKUPS: usize = 25;
+
+// Everything `authority` can update or has signed as first creator, grouped by
+// collection, plus the metadata updates found in its recent transactions.
+pub async fn run(authority: String, max_accounts: usize, max_txs: usize) -> Result<AuthorityReport> {
+    input::validate_address(&authority)?;
+    let (client, rpc_url) = create_rpc_client();
+    let mut warnings = Vec::new();
+
+    let (by_authority, bad_authority) = collection::metadata_accounts(&client, &rpc_url, collection::authority_filters(&authority)).await?;
+    let (by_creator, bad_creator) = collection::metadata_accounts(&client, &rpc_url, collection::creator_filters(&authority)).await?;
+    if bad_authority + bad_creator > 0 {
+        warnings.push(format!("{} metadata accounts could not be decoded", bad_authority + bad_creator));
+    }
+
+    let authority_count = by_authority.len() as u32;
+    let creator_count = by_creator.len() as u32;
+    let mut accounts = by_authority;
+    for (address, metadata) in by_creator {
+        accounts.entry(address).or_insert(metadata);
+    }
+    if accounts.is_empty() {
+        return Err(anyhow!("No metadata accounts have {} as update authority or first creator", authority));
+    }
+    let partial = accounts.len() > max_accounts;
+    let accounts: BTreeMap<String, Metadata> = accounts.into_iter().take(max_accounts).collect();
+
+    let mut collections = group(&authority, &accounts);
+    for group in collections.iter_mut().filter(|c| c.collection.is_some()).take(NAME_LOOKUPS) {
+        let Some(address) = &group.collection else { continue };
+        if let Ok(metadata) = unmask::fetch_metadata(&client, &rpc_url, address).await {
+            group.name = Some(clean(&metadata.data.name)).filter(|n| !n.is_empty());
+        }
+    }
+
+    let recent_updates = match find_updates(&client, &rpc_url, &authority, &accounts, max_txs).await {
+        Ok(updates) => updates,
+        Err(e) => {
+            warnings.push(format!("update history: {}", e));
+            Vec::new()
+        }
+    };
+
+    let mut royalties: BTreeMap<u16, u32> = BTreeMap::new();
+    for metadata in accounts.values() {
+        *royalties.entry(metadata.data.seller_fee_basis_points).or_default() += 1;
+    }
+    let mut royalties: Vec<RoyaltyShare> = royalties
+        .into_iter()
+        .map(|(basis_points, count)| RoyaltyShare { basis_points, count })
+        .collect();
+    royalties.sort_by_key(|r| std::cmp::Reverse(r.count));
+
+    Ok(AuthorityReport {
+        total: accounts.len() as u32,
+        by_authority: authority_count,
+        by_creator: creator_count,
+        mutable: accounts.values().filter(|m| m.is_mutable).count() as u32,
+        primary_sold: accounts.values().filter(|m| m.primary_sale_happened).count() as u32,
+        authority,
+        collections,
+        royalties,
+        recent_updates,
+        partial,
+        warnings,
+    })
+}
+
+fn clean(text: &str) -> String {
+    text.trim_end_matches('\0').trim().to_string()
+}
+
+// Verified collection when there is one, otherwise the symbol the items share.
+fn group(authority: &str, accounts: &BTreeMap<String, Metadata>) -> Vec<AuthorityCollection> {
+    let mut groups: BTreeMap<(Option<String>, String), AuthorityCollection> = BTreeMap::new();
+    for metadata in accounts.values() {
+        let collection = metadata.collection.as_ref().filter(|c| c.verified).map(|c| c.key.to_string());
+        let symbol = clean(&metadata.data.symbol);
+        let key = (collection.clone(), if collection.is_some() { String::new() } else { symbol.clone() });
+        let bps = metadata.data.seller_fee_basis_points;
+
+        let entry = groups.entry(key).or_insert_with(|| AuthorityCollection {
+            collection,
+            name: None,
+            symbol: Some(symbol).filter(|s| !s.is_empty()),
+            count: 0,
+            mutable: 0,
+            via_authority: 0,
+            min_royalty_bps: bps,
+            max_royalty_bps: bps,
+        });
+        entry.count += 1;
+        if metadata.is_mutable {
+            entry.mutable += 1;
+        }
+        if metadata.update_authority.to_string() == authority {
+            entry.via_authority += 1;
+        }
+        entry.min_royalty_bps = entry.min_royalty_bps.min(bps);
+        entry.max_royalty_bps = entry.max_royalty_bps.max(bps);
+    }
+
+    let mut groups: Vec<AuthorityCollection> = groups.into_values().collect();
+    groups.sort_by_key(|g| std::cmp::Reverse(g.count));
+    groups
+}
+
+// Metadata updates have to be signed by the update authority, so its own history shows
+// them: any successful Token Metadata call that writes to one of the accounts. Updates
+// made by a different authority (items matched through the creator) are not seen.
+async fn find_updates(client: &reqwest::Client, rpc_url: &str, authority: &str, accounts: &BTreeMap<String, Metadata>, max_txs: usize) -> Result<Vec<MetadataUpdate>> {
+    let (signatures, _) = activity::fetch_signatures(client, rpc_url, authority, max_txs).await?;
+    let show_progress = stderr().is_terminal() && !style::current().quiet;
+    let now = activity::now();
+
+    let mut seen = HashSet::new();
+    let mut updates = Vec::new();
+    let candidates: Vec<_> = signatures.iter().filter(|s| s.err.is_none()).collect();
+    for (i, info) in candidates.iter().enumerate() {
+        if show_progress && i % 10 == 0 {
+            eprint!("\r  🛠️  {}/{} transactions checked for updates", i, candidates.len());
+            let _ = stderr().flush();
+        }
+        let Ok(tx) = transactions::fetch_parsed(client, rpc_url, &info.signature).await else { continue };
+        if !transactions::invokes_program(&tx, TOKEN_METADATA_PROGRAM) {
+            continue;
+        }
+        let timestamp = transactions::block_time(&tx).or(info.block_time);
+        for address in transactions::writable_accounts(&tx) {
+            let Some(metadata) = accounts.get(&address) else { continue };
+            // Signatures are newest first, so the first hit is the latest update
+            if !seen.insert(address.clone()) {
+                continue;
+            }
+            updates.push(MetadataUpdate {
+                mint: metadata.mint.to_string(),
+                name: clean(&metadata.data.name),
+                metadata_account: address,
+                signature: info.signature.clone(),
+                timestamp,
+                recent: timestamp.is_some_and(|t| now - t <= RECENT_DAYS * DAY),
+            });
+        }
+    }
+    if show_progress {
+        eprintln!("\r  🛠️  {}/{} transactions checked for updates", candidates.len(), candidates.len());
+    }
+    Ok(updates)
+}
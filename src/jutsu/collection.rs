@@ -0,0 +1,454 @@
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use borsh::BorshDeserialize;
+use crate::input;
+use crate::jutsu::{dossier, unmask};
+use crate::model::{CollectionReport, HeliusAsset, HolderBucket, HolderShare, Metadata};
+use crate::programs::TOKEN_METADATA_PROGRAM;
+use crate::rarity;
+use crate::rpc::{self, create_rpc_client, das_url};
+use crate::style;
+use reqwest::Client;
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::{stderr, IsTerminal, Write};
+use std::str::FromStr;
+
+// Metadata account layout up to the creators: key, update authority, mint, then the
+// name (32), symbol (10) and uri (200) strings, which the program pads to full length,
+// and the seller fee.
+const UPDATE_AUTHORITY_OFFSET: usize = 1;
+const MINT_OFFSET: usize = 1 + 32;
+const CREATORS_OFFSET: usize = 1 + 32 + 32 + (4 + 32) + (4 + 10) + (4 + 200) + 2;
+const CREATOR_LEN: usize = 34;
+const MAX_CREATORS: usize = 5;
+// base58 of a single 0x04 (Key::MetadataV1) and 0x01 (Some / verified) byte
+const METADATA_V1_TAG: &str = "5";
+const ONE_BYTE: &str = "2";
+// First creator's address, right after the Some tag and the vec length
+const FIRST_CREATOR_OFFSET: usize = CREATORS_OFFSET + 1 + 4;
+// getMultipleAccounts takes up to 100 keys
+const ACCOUNTS_BATCH: usize = 100;
+
+// One NFT and where it currently sits, however it was found.
+pub struct Member {
+    pub mint: String,
+    pub owner: Option<String>,
+    pub burned: bool,
+    // Owner is a PDA: marketplace escrow, staking vault, multisig...
+    pub program_owned: bool,
+    // Frozen under a delegate, the way escrowless listings work
+    pub locked: bool,
+    // What DAS says about the NFT; None when the listing came from metadata accounts
+    pub details: Option<MemberDetails>,
+}
+
+pub struct MemberDetails {
+    pub name: String,
+    pub mutable: Option<bool>,
+    pub primary_sale_happened: Option<bool>,
+    // Trait type -> value, as in the rarity table
+    pub attributes: BTreeMap<String, String>,
+}
+
+pub struct MemberListing {
+    pub members: Vec<Member>,
+    pub partial: bool,
+    // "das" or "getProgramAccounts"
+    pub source: &'static str,
+    pub warnings: Vec<String>,
+}
+
+pub async fn run(collection: String, max_assets: u32) -> Result<CollectionReport> {
+    input::validate_address(&collection)?;
+    let (client, rpc_url) = create_rpc_client();
+
+    let listing = collection_members(&client, &rpc_url, &collection, max_assets).await?;
+    let mut warnings = listing.warnings;
+
+    let (name, symbol) = match unmask::fetch_metadata(&client, &rpc_url, &collection).await {
+        Ok(metadata) => (
+            Some(metadata.data.name.trim_end_matches('\0').trim().to_string()).filter(|n| !n.is_empty()),
+            Some(metadata.data.symbol.trim_end_matches('\0').trim().to_string()).filter(|s| !s.is_empty()),
+        ),
+        Err(e) => {
+            warnings.push(format!("collection metadata: {}", e));
+            (None, None)
+        }
+    };
+
+    let mut report = survey(&listing.members);
+    report.collection = collection;
+    report.name = name;
+    report.symbol = symbol;
+    report.source = listing.source.to_string();
+    report.partial = listing.partial;
+    report.warnings = warnings;
+    Ok(report)
+}
+
+// Verified members of a collection.
+pub async fn collection_members(client: &Client, rpc_url: &str, collection: &str, max_assets: u32) -> Result<MemberListing> {
+    let params = json!({ "groupKey": "collection", "groupValue": collection });
+    find_members(client, rpc_url, "getAssetsByGroup", params, collection_filters(collection), max_assets).await
+}
+
+// NFTs whose first creator is `creator` and verified, which is how collections
+// were identified before on-chain collections existed.
+pub async fn creator_members(client: &Client, rpc_url: &str, creator: &str, max_assets: u32) -> Result<MemberListing> {
+    let params = json!({ "creatorAddress": creator, "onlyVerified": true });
+    find_members(client, rpc_url, "getAssetsByCreator", params, creator_filters(creator), max_assets).await
+}
+
+// getProgramAccounts filters matching metadata accounts with `collection` as their
+// verified collection, one set per possible offset of the collection key.
+pub fn collection_filters(collection: &str) -> Vec<Value> {
+    collection_key_offsets()
+        .into_iter()
+        .map(|offset| json!([
+            { "memcmp": { "offset": 0, "bytes": METADATA_V1_TAG } },
+            { "memcmp": { "offset": offset - 1, "bytes": ONE_BYTE } },
+            { "memcmp": { "offset": offset, "bytes": collection } },
+        ]))
+        .collect()
+}
+
+pub fn creator_filters(creator: &str) -> Vec<Value> {
+    vec![json!([
+        { "memcmp": { "offset": 0, "bytes": METADATA_V1_TAG } },
+        { "memcmp": { "offset": FIRST_CREATOR_OFFSET, "bytes": creator } },
+        { "memcmp": { "offset": FIRST_CREATOR_OFFSET + 32, "bytes": ONE_BYTE } },
+    ])]
+}
+
+// Metadata accounts whose update authority is `authority`.
+pub fn authority_filters(authority: &str) -> Vec<Value> {
+    vec![json!([
+        { "memcmp": { "offset": 0, "bytes": METADATA_V1_TAG } },
+        { "memcmp": { "offset": UPDATE_AUTHORITY_OFFSET, "bytes": authority } },
+    ])]
+}
+
+// DAS first; when it is unavailable or finds nothing, scan metadata accounts with
+// each of `filter_sets` and look the holders up one mint at a time.
+async fn find_members(client: &Client, rpc_url: &str, method: &str, params: Value, filter_sets: Vec<Value>, max_assets: u32) -> Result<MemberListing> {
+    let warning = match dossier::fetch_asset_pages(client, &das_url(), method, params, max_assets).await {
+        Ok((assets, partial)) if !assets.is_empty() => {
+            return Ok(MemberListing {
+                members: assets.iter().map(member_from_asset).collect(),
+                partial,
+                source: "das",
+                warnings: Vec::new(),
+            });
+        }
+        Err(e) => format!("DAS unavailable ({}), scanned metadata accounts instead", e),
+        Ok(_) => "DAS returned no members, scanned metadata accounts instead".to_string(),
+    };
+
+    let mints = metadata_mints(client, rpc_url, filter_sets).await?;
+    if mints.is_empty() {
+        return Err(anyhow!("No verified members found"));
+    }
+    let partial = mints.len() > max_assets as usize;
+    let mints: Vec<String> = mints.into_iter().take(max_assets as usize).collect();
+
+    Ok(MemberListing {
+        members: holders_of_mints(client, rpc_url, &mints).await?,
+        partial,
+        source: "getProgramAccounts",
+        warnings: vec![warning],
+    })
+}
+
+fn member_from_asset(asset: &HeliusAsset) -> Member {
+    let owner = asset.owner().map(str::to_string);
+    Member {
+        mint: asset.id.clone(),
+        program_owned: owner.as_deref().is_some_and(is_program_owned),
+        locked: asset.ownership.as_ref().map(|o| o.delegated && o.frozen).unwrap_or(false),
+        owner,
+        burned: asset.burnt,
+        details: Some(MemberDetails {
+            name: asset.name().unwrap_or_default().trim().to_string(),
+            mutable: asset.mutable,
+            primary_sale_happened: asset.royalty.as_ref().map(|r| r.primary_sale_happened),
+            attributes: rarity::parse_attributes(
+                asset.content.as_ref().and_then(|c| c.metadata.as_ref()).and_then(|m| m.attributes.as_deref()).unwrap_or_default(),
+            ),
+        }),
+    }
+}
+
+// Escrow accounts are PDAs, which by construction are off the ed25519 curve.
+// Multisig vaults are PDAs too, so this slightly overcounts listings.
+fn is_program_owned(address: &str) -> bool {
+    Pubkey::from_str(address).map(|p| !p.is_on_curve()).unwrap_or(false)
+}
+
+// Where the collection key can sit in a metadata account. Creators, edition nonce and
+// token standard are variable length, so every combination is tried.
+fn collection_key_offsets() -> BTreeSet<usize> {
+    let creators = std::iter::once(1).chain((0..=MAX_CREATORS).map(|n| 1 + 4 + n * CREATOR_LEN));
+    let mut offsets = BTreeSet::new();
+    for creators_len in creators {
+        // primary_sale_happened + is_mutable, then Option<u8> nonce and Option<u8> token
+        // standard (1 or 2 bytes each), then the collection's Some tag and verified flag
+        for options_len in 2..=4 {
+            offsets.insert(CREATORS_OFFSET + creators_len + 2 + options_len + 2);
+        }
+    }
+    offsets
+}
+
+// Mints of the metadata accounts matching any of the memcmp filter sets.
+pub async fn metadata_mints(client: &Client, rpc_url: &str, filter_sets: Vec<Value>) -> Result<BTreeSet<String>> {
+    let mut mints = BTreeSet::new();
+    for filters in filter_sets {
+        let config = json!({
+            "encoding": "base64",
+            "dataSlice": { "offset": MINT_OFFSET, "length": 32 },
+            "filters": filters,
+        });
+        let result = rpc::call(client, rpc_url, "getProgramAccounts", json!([TOKEN_METADATA_PROGRAM, config])).await?;
+        for item in result.as_array().into_iter().flatten() {
+            let Some(data) = item.pointer("/account/data/0").and_then(Value::as_str) else { continue };
+            if let Some(mint) = STANDARD.decode(data).ok().and_then(|b| Pubkey::try_from(b.as_slice()).ok()) {
+                mints.insert(mint.to_string());
+            }
+        }
+    }
+    Ok(mints)
+}
+
+// Whole metadata accounts matching any of the filter sets, decoded, keyed by the
+// metadata account address. Accounts that fail to decode are counted, not fatal.
+pub async fn metadata_accounts(client: &Client, rpc_url: &str, filter_sets: Vec<Value>) -> Result<(BTreeMap<String, Metadata>, usize)> {
+    let mut accounts = BTreeMap::new();
+    let mut undecodable = 0;
+    for filters in filter_sets {
+        let config = json!({ "encoding": "base64", "filters": filters });
+        let result = rpc::call(client, rpc_url, "getProgramAccounts", json!([TOKEN_METADATA_PROGRAM, config])).await?;
+        for item in result.as_array().into_iter().flatten() {
+            let Some(pubkey) = item.get("pubkey").and_then(Value::as_str) else { continue };
+            let Some(data) = item.pointer("/account/data/0").and_then(Value::as_str) else { continue };
+            let decoded = STANDARD.decode(data).ok().and_then(|bytes| Metadata::deserialize(&mut bytes.as_slice()).ok());
+            match decoded {
+                Some(metadata) => {
+                    accounts.insert(pubkey.to_string(), metadata);
+                }
+                None => undecodable += 1,
+            }
+        }
+    }
+    Ok((accounts, undecodable))
+}
+
+// Current holder of each mint through its largest token account; a mint with no
+// funded account left was burned. One RPC call per mint, plus one per 100 owners.
+pub async fn holders_of_mints(client: &Client, rpc_url: &str, mints: &[String]) -> Result<Vec<Member>> {
+    let show_progress = stderr().is_terminal() && !style::current().quiet;
+
+    let mut holdings: Vec<Option<String>> = Vec::new();
+    for (i, mint) in mints.iter().enumerate() {
+        if show_progress && i % 25 == 0 {
+            eprint!("\r  📦 {}/{} mints checked", i, mints.len());
+            let _ = stderr().flush();
+        }
+        let result = rpc::call(client, rpc_url, "getTokenLargestAccounts", json!([mint])).await?;
+        let account = result
+            .get("value")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .find(|a| a.get("amount").and_then(Value::as_str).is_some_and(|amt| amt != "0"))
+            .and_then(|a| a.get("address").and_then(Value::as_str))
+            .map(str::to_string);
+        holdings.push(account);
+    }
+    if show_progress {
+        eprintln!("\r  📦 {}/{} mints checked", mints.len(), mints.len());
+    }
+
+    let accounts: Vec<&String> = holdings.iter().flatten().collect();
+    let mut owners: HashMap<&str, (Option<String>, bool)> = HashMap::new();
+    for chunk in accounts.chunks(ACCOUNTS_BATCH) {
+        let result = rpc::call(client, rpc_url, "getMultipleAccounts", json!([chunk, { "encoding": "jsonParsed" }])).await?;
+        let values = result.get("value").and_then(Value::as_array).cloned().unwrap_or_default();
+        for (account, value) in chunk.iter().zip(values) {
+            let info = value.pointer("/data/parsed/info");
+            let owner = info.and_then(|i| i.get("owner")).and_then(Value::as_str).map(str::to_string);
+            let frozen = info.and_then(|i| i.get("state")).and_then(Value::as_str) == Some("frozen");
+            let delegated = info.and_then(|i| i.get("delegate")).is_some();
+            owners.insert(account.as_str(), (owner, frozen && delegated));
+        }
+    }
+
+    Ok(mints
+        .iter()
+        .zip(holdings.iter())
+        .map(|(mint, account)| {
+            let (owner, locked) = account
+                .as_deref()
+                .and_then(|a| owners.remove(a))
+                .unwrap_or((None, false));
+            Member {
+                mint: mint.clone(),
+                program_owned: owner.as_deref().is_some_and(is_program_owned),
+                burned: account.is_none(),
+                owner,
+                locked,
+                details: None,
+            }
+        })
+        .collect())
+}
+
+// Supply and concentration figures; identity fields are filled in by the caller.
+fn survey(members: &[Member]) -> CollectionReport {
+    let burned = members.iter().filter(|m| m.burned).count() as u32;
+    let live: Vec<&Member> = members.iter().filter(|m| !m.burned).collect();
+    let supply = live.len() as u32;
+    let escrowed = live.iter().filter(|m| m.program_owned || m.locked).count() as u32;
+
+    let mut holdings: HashMap<&str, u32> = HashMap::new();
+    for member in &live {
+        if let Some(owner) = &member.owner {
+            *holdings.entry(owner.as_str()).or_default() += 1;
+        }
+    }
+    let mut holders: Vec<(&str, u32)> = holdings.into_iter().collect();
+    holders.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+    let share = |count: u32| if supply == 0 { 0.0 } else { count as f64 / supply as f64 };
+    let top10: u32 = holders.iter().take(10).map(|(_, c)| c).sum();
+
+    CollectionReport {
+        collection: String::new(),
+        name: None,
+        symbol: None,
+        source: String::new(),
+        supply,
+        burned,
+        unique_holders: holders.len() as u32,
+        top10_share: share(top10),
+        gini: gini(&holders.iter().map(|(_, c)| *c).collect::<Vec<_>>()),
+        escrowed,
+        distribution: distribution(&holders),
+        top_holders: holders
+            .iter()
+            .take(10)
+            .map(|(address, count)| HolderShare { address: address.to_string(), count: *count, share: share(*count) })
+            .collect(),
+        partial: false,
+        warnings: Vec::new(),
+    }
+}
+
+// Gini coefficient of the per-holder counts.
+fn gini(counts: &[u32]) -> f64 {
+    let n = counts.len() as f64;
+    let total: f64 = counts.iter().map(|c| *c as f64).sum();
+    if n == 0.0 || total == 0.0 {
+        return 0.0;
+    }
+    let mut sorted = counts.to_vec();
+    sorted.sort_unstable();
+    let weighted: f64 = sorted
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (2.0 * (i as f64 + 1.0) - n - 1.0) * *c as f64)
+        .sum();
+    weighted / (n * total)
+}
+
+fn distribution(holders: &[(&str, u32)]) -> Vec<HolderBucket> {
+    [("1", 1, 1), ("2-5", 2, 5), ("6-10", 6, 10), ("11-50", 11, 50), ("51+", 51, u32::MAX)]
+        .into_iter()
+        .map(|(label, low, high)| {
+            let within: Vec<u32> = holders.iter().map(|(_, c)| *c).filter(|c| (low..=high).contains(c)).collect();
+            HolderBucket {
+                label: label.to_string(),
+                holders: within.len() as u32,
+                nfts: within.iter().sum(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+    use crate::model::{Collection, Creator, Data};
+
+    // Metadata as the program writes it: strings padded to their maximum length
+    fn account(creators: Option<usize>, edition_nonce: Option<u8>, token_standard: Option<u8>, collection: Pubkey) -> Vec<u8> {
+        let pad = |s: &str, len: usize| format!("{}{}", s, "\0".repeat(len - s.len()));
+        let metadata = Metadata {
+            key: 4,
+            update_authority: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            data: Data {
+                name: pad("Item", 32),
+                symbol: pad("ITM", 10),
+                uri: pad("https://arweave.net/item", 200),
+                seller_fee_basis_points: 500,
+                creators: creators.map(|n| {
+                    (0..n).map(|_| Creator { address: Pubkey::new_unique(), verified: true, share: 0 }).collect()
+                }),
+            },
+            primary_sale_happened: false,
+            is_mutable: true,
+            edition_nonce,
+            token_standard,
+            collection: Some(Collection { verified: true, key: collection }),
+            uses: None,
+            collection_details: None,
+            programmable_config: None,
+        };
+        metadata.try_to_vec().unwrap()
+    }
+
+    fn position(data: &[u8], key: &Pubkey) -> usize {
+        data.windows(32).position(|w| w == key.as_ref()).expect("key in account")
+    }
+
+    #[test]
+    fn collection_key_offsets_cover_every_layout() {
+        let offsets = collection_key_offsets();
+        let creator_counts = std::iter::once(None).chain((0..=MAX_CREATORS).map(Some));
+        for creators in creator_counts {
+            for edition_nonce in [None, Some(255)] {
+                for token_standard in [None, Some(0)] {
+                    let key = Pubkey::new_unique();
+                    let data = account(creators, edition_nonce, token_standard, key);
+                    let at = position(&data, &key);
+                    assert!(offsets.contains(&at), "{:?} {:?} {:?}: key at {}", creators, edition_nonce, token_standard, at);
+                }
+            }
+        }
+        // Nothing else: one offset per creator count and option combination
+        assert_eq!(offsets.len(), (MAX_CREATORS + 2) * 3);
+    }
+
+    #[test]
+    fn first_creator_offset() {
+        let data = account(Some(2), None, None, Pubkey::new_unique());
+        let first = Metadata::deserialize(&mut data.as_slice()).unwrap().data.creators.unwrap()[0].address;
+        assert_eq!(position(&data, &first), FIRST_CREATOR_OFFSET);
+    }
+
+    #[test]
+    fn gini_extremes() {
+        assert_eq!(gini(&[]), 0.0);
+        assert_eq!(gini(&[7, 7, 7, 7]), 0.0);
+        // A lone holder has nobody to be unequal to
+        assert_eq!(gini(&[10]), 0.0);
+        // One wallet holding everything tends to 1 as the holder count grows
+        assert_eq!(gini(&[0, 0, 0, 100]), 0.75);
+        assert!(gini(&[1, 1, 1, 97]) > 0.7);
+        assert!(gini(&[1, 2, 3, 4]) < gini(&[1, 1, 1, 97]));
+    }
+}
@@ -0,0 +1,168 @@
+use anyhow::{anyhow, Result};
+use crate::input;
+use crate::jutsu::dossier;
+use crate::model::{CollectionSummary, CommonCounterparty, CompareReport, Movement, SharedCollection};
+use crate::programs;
+use crate::rpc::{create_rpc_client, das_url};
+use crate::transactions;
+use std::collections::{HashMap, HashSet};
+
+// Score weights; they add up to 100.
+const COLLECTION_WEIGHT: f64 = 40.0;
+const TRANSFER_WEIGHT: f64 = 35.0;
+const COUNTERPARTY_WEIGHT: f64 = 25.0;
+// Past this many, more direct transfers or shared counterparties add nothing.
+const TRANSFER_CAP: usize = 5;
+const COUNTERPARTY_CAP: usize = 10;
+
+pub async fn run(wallet_a: String, wallet_b: String, max_assets: u32, max_txs: usize) -> Result<CompareReport> {
+    input::validate_address(&wallet_a)?;
+    input::validate_address(&wallet_b)?;
+    if wallet_a == wallet_b {
+        return Err(anyhow!("Both addresses are the same wallet"));
+    }
+
+    let (client, rpc_url) = create_rpc_client();
+    let helius_url = das_url();
+
+    // Same aggregation as the dossier, minus the spam
+    let (assets_a, partial_a) = dossier::fetch_owner_assets(&client, &helius_url, &wallet_a, max_assets).await?;
+    let (assets_b, partial_b) = dossier::fetch_owner_assets(&client, &helius_url, &wallet_b, max_assets).await?;
+    let (nfts_a, _, _) = dossier::sort_holdings(assets_a);
+    let (nfts_b, _, _) = dossier::sort_holdings(assets_b);
+    let (count_a, count_b) = (nfts_a.len() as u32, nfts_b.len() as u32);
+    let collections_a = dossier::summarize_collections(nfts_a);
+    let collections_b = dossier::summarize_collections(nfts_b);
+
+    let mut shared = shared_collections(&collections_a, &collections_b);
+    dossier::resolve_collection_names(&client, &rpc_url, &mut shared).await;
+    let shared: Vec<SharedCollection> = shared
+        .into_iter()
+        .map(|c| {
+            let count_b = collections_b.iter().find(|b| key(b) == key(&c)).map(|b| b.count).unwrap_or(0);
+            SharedCollection { name: c.name, address: c.address, count_a: c.count, count_b }
+        })
+        .collect();
+
+    let mut warnings = Vec::new();
+    let mut movements = Vec::new();
+    for wallet in [&wallet_a, &wallet_b] {
+        match transactions::recent_movements(&client, &rpc_url, wallet, max_txs).await {
+            Ok((m, skipped)) => {
+                warnings.extend(skipped);
+                movements.push(m);
+            }
+            Err(e) => {
+                warnings.push(format!("transaction history of {}: {}", wallet, e));
+                movements.push(Vec::new());
+            }
+        }
+    }
+
+    let transfers = direct_transfers(&wallet_a, &wallet_b, &movements);
+    let common_counterparties = common_counterparties(&wallet_a, &wallet_b, &movements[0], &movements[1]);
+
+    let union = collections_a.len() + collections_b.len() - shared.len();
+    let (similarity, factors) = score(shared.len(), union, transfers.len(), common_counterparties.len());
+
+    Ok(CompareReport {
+        wallet_a,
+        wallet_b,
+        nfts_a: count_a,
+        nfts_b: count_b,
+        collections_a: collections_a.len() as u32,
+        collections_b: collections_b.len() as u32,
+        shared_collections: shared,
+        transfers,
+        common_counterparties,
+        similarity,
+        factors,
+        partial: partial_a || partial_b,
+        warnings,
+    })
+}
+
+// Collection mint when there is one, otherwise the name the dossier grouped by.
+fn key(c: &CollectionSummary) -> &str {
+    c.address.as_deref().unwrap_or(&c.name)
+}
+
+// Wallet A's side of every collection both wallets hold, biggest overlap first.
+fn shared_collections(a: &[CollectionSummary], b: &[CollectionSummary]) -> Vec<CollectionSummary> {
+    let in_b: HashMap<&str, u32> = b.iter().map(|c| (key(c), c.count)).collect();
+    let mut shared: Vec<(u32, CollectionSummary)> = a
+        .iter()
+        .filter_map(|c| {
+            let other = in_b.get(key(c))?;
+            Some((c.count.min(*other), c.clone()))
+        })
+        .collect();
+    shared.sort_by(|x, y| y.0.cmp(&x.0).then_with(|| x.1.name.cmp(&y.1.name)));
+    shared.into_iter().map(|(_, c)| c).collect()
+}
+
+// Movements between the two wallets. Both histories can hold the same transaction.
+fn direct_transfers(a: &str, b: &str, movements: &[Vec<Movement>]) -> Vec<Movement> {
+    let mut seen = HashSet::new();
+    let mut transfers: Vec<Movement> = movements
+        .iter()
+        .flatten()
+        .filter(|m| (m.from == a && m.to == b) || (m.from == b && m.to == a))
+        .filter(|m| seen.insert((m.signature.clone(), m.from.clone(), m.mint.clone())))
+        .cloned()
+        .collect();
+    transfers.sort_by_key(|m| std::cmp::Reverse(m.timestamp));
+    transfers
+}
+
+fn counterparties<'a>(wallet: &str, movements: &'a [Movement]) -> HashMap<&'a str, u32> {
+    let mut counts = HashMap::new();
+    for m in movements {
+        let other = if m.from == wallet { m.to.as_str() } else { m.from.as_str() };
+        *counts.entry(other).or_default() += 1;
+    }
+    counts
+}
+
+fn common_counterparties(a: &str, b: &str, movements_a: &[Movement], movements_b: &[Movement]) -> Vec<CommonCounterparty> {
+    let with_a = counterparties(a, movements_a);
+    let with_b = counterparties(b, movements_b);
+
+    let mut common: Vec<CommonCounterparty> = with_a
+        .iter()
+        .filter(|(address, _)| **address != a && **address != b)
+        .filter_map(|(address, count_a)| {
+            let count_b = with_b.get(address)?;
+            Some(CommonCounterparty {
+                address: address.to_string(),
+                name: programs::name(address).map(str::to_string),
+                with_a: *count_a,
+                with_b: *count_b,
+            })
+        })
+        .collect();
+    common.sort_by(|x, y| (y.with_a + y.with_b).cmp(&(x.with_a + x.with_b)).then_with(|| x.address.cmp(&y.address)));
+    common
+}
+
+// 0-100, plus one line per signal that contributed.
+fn score(shared: usize, union: usize, transfers: usize, counterparties: usize) -> (u32, Vec<String>) {
+    let mut total = 0.0;
+    let mut factors = Vec::new();
+
+    if shared > 0 && union > 0 {
+        let overlap = shared as f64 / union as f64;
+        total += COLLECTION_WEIGHT * overlap;
+        factors.push(format!("{} of {} collections held by both ({:.0}% overlap)", shared, union, overlap * 100.0));
+    }
+    if transfers > 0 {
+        total += TRANSFER_WEIGHT * transfers.min(TRANSFER_CAP) as f64 / TRANSFER_CAP as f64;
+        factors.push(format!("{} direct transfers between the wallets", transfers));
+    }
+    if counterparties > 0 {
+        total += COUNTERPARTY_WEIGHT * counterparties.min(COUNTERPARTY_CAP) as f64 / COUNTERPARTY_CAP as f64;
+        factors.push(format!("{} counterparties in common", counterparties));
+    }
+
+    (total.round() as u32, factors)
+}
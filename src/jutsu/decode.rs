@@ -0,0 +1,100 @@
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use clap::ValueEnum;
+use crate::accounts::{self, Reader};
+use crate::jutsu::unmask;
+use crate::model::{DecodeDiagnosis, UnmaskReport};
+use serde_json::Value;
+use std::io::Read;
+use std::path::Path;
+
+// Bytes shown on either side of the point where a diagnosis stopped.
+const CONTEXT_BYTES: usize = 48;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum DecodeType {
+    /// Token Metadata account, as read by unmask
+    Metadata,
+}
+
+// Account bytes from --base64, or from a file ("-" for stdin) holding raw bytes,
+// base64 text, or the JSON of `solana account --output json` / getAccountInfo.
+pub fn read_input(base64: Option<String>, file: Option<&Path>) -> Result<Vec<u8>> {
+    if let Some(text) = base64 {
+        return decode_base64(&text);
+    }
+    let path = file.ok_or_else(|| anyhow!("Give the account data with --base64 or --file"))?;
+    let mut bytes = Vec::new();
+    if path == Path::new("-") {
+        std::io::stdin().read_to_end(&mut bytes)?;
+    } else {
+        bytes = std::fs::read(path).map_err(|e| anyhow!("Failed to read '{}': {}", path.display(), e))?;
+    }
+
+    let Ok(text) = std::str::from_utf8(&bytes) else { return Ok(bytes) };
+    let text = text.trim();
+    if text.starts_with('{') {
+        let dump: Value = serde_json::from_str(text).map_err(|e| anyhow!("'{}' looks like JSON but doesn't parse: {}", path.display(), e))?;
+        return from_json_dump(&dump);
+    }
+    // Text that happens to be valid base64 is taken as such; anything else is raw
+    Ok(decode_base64(text).unwrap_or(bytes))
+}
+
+fn decode_base64(text: &str) -> Result<Vec<u8>> {
+    let compact: String = text.split_whitespace().collect();
+    STANDARD.decode(compact).map_err(|e| anyhow!("base64 decode failed: {}", e))
+}
+
+// `solana account` nests the account under "account", RPC responses under "value";
+// either way data is [payload, encoding].
+fn from_json_dump(dump: &Value) -> Result<Vec<u8>> {
+    let account = dump.get("account").or_else(|| dump.pointer("/result/value")).or_else(|| dump.get("value")).unwrap_or(dump);
+    let data = account.get("data").ok_or_else(|| anyhow!("No \"data\" field in the account dump"))?;
+    let (payload, encoding) = match data {
+        Value::Array(parts) => (parts.first().and_then(Value::as_str), parts.get(1).and_then(Value::as_str).unwrap_or("base64")),
+        Value::String(payload) => (Some(payload.as_str()), "base64"),
+        _ => (None, ""),
+    };
+    match (payload, encoding) {
+        (Some(payload), "base64") => decode_base64(payload),
+        (Some(_), other) => Err(anyhow!("Account data is encoded as {}; dump it with base64 encoding", other)),
+        (None, _) => Err(anyhow!("Account data in the dump is already parsed; dump it with base64 encoding")),
+    }
+}
+
+// The same decoding and report unmask produces, without touching the network.
+pub fn run(kind: DecodeType, data: &[u8]) -> Result<UnmaskReport> {
+    match kind {
+        DecodeType::Metadata => {
+            let metadata = accounts::decode_metadata(data)
+                .map_err(|e| anyhow!("{} (run again with --diagnose to see which field fails)", e))?;
+            Ok(unmask::offline_report(metadata))
+        }
+    }
+}
+
+// Walk the layout field by field and report how far it got.
+pub fn diagnose(kind: DecodeType, data: &[u8]) -> DecodeDiagnosis {
+    let mut reader = Reader::new(data);
+    let result = match kind {
+        DecodeType::Metadata => accounts::trace_metadata(&mut reader),
+    };
+    let stopped_at = reader.offset();
+    let error = result.err().map(|e| e.to_string());
+    let context = error.as_ref().map(|_| {
+        let start = stopped_at.saturating_sub(CONTEXT_BYTES);
+        accounts::hex_dump(data, start, stopped_at - start + CONTEXT_BYTES)
+    });
+    DecodeDiagnosis {
+        data_len: data.len(),
+        decoded: match kind {
+            DecodeType::Metadata => accounts::decode_metadata(data).is_ok(),
+        },
+        trailing: error.is_none().then(|| reader.remaining()),
+        fields: reader.fields,
+        error,
+        stopped_at,
+        context,
+    }
+}
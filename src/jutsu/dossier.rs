@@ -1,85 +1,325 @@
 use anyhow::{anyhow, Result};
-use crate::rpc::create_rpc_client;
-use crate::model::{DossierReport, CollectionSummary, HeliusAssetsResponse};
-use serde_json::json;
-use std::collections::HashMap;
-use std::env;
+use crate::rpc::{create_rpc_client, das_url};
+use crate::model::{AssetBreakdown, AssetClass, DossierReport, CollectionSummary, HeliusAsset, HeliusAssetsResponse, SpamAsset, WalletBalances, WalletShare};
+use crate::spam;
+use crate::activity;
+use crate::balances;
+use crate::input;
+use crate::jutsu::unmask;
+use crate::style;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{stderr, IsTerminal, Write};
 
-pub async fn run(wallet_address: String) -> Result<DossierReport> {
-    let (rpc_client, _) = create_rpc_client();
+// DAS caps a page at 1000 items.
+const PAGE_LIMIT: u32 = 1000;
 
-    let helius_api_key = env::var("HELIUS_API_KEY")
-        .unwrap_or_else(|_| "".to_string());
+// On-chain name lookups are one RPC call each, so only the biggest collections get them.
+const NAME_LOOKUPS: usize = 25;
 
-    let helius_url = if helius_api_key.is_empty() {
-        "https://api.mainnet-beta.solana.com".to_string()
+// Several wallets are treated as one portfolio. `max_txs` of None skips the
+// transaction history entirely; it is also skipped for multi-wallet dossiers,
+// as a behavioural profile of several wallets at once means little.
+pub async fn run(wallets: Vec<String>, max_assets: u32, with_balances: bool, max_txs: Option<usize>) -> Result<DossierReport> {
+    for wallet in &wallets {
+        input::validate_address(wallet)?;
+    }
+    let (rpc_client, rpc_url) = create_rpc_client();
+    let helius_url = das_url();
+    let multi = wallets.len() > 1;
+
+    let mut listings = Vec::new();
+    for wallet in &wallets {
+        let (assets, partial) = fetch_owner_assets(&rpc_client, &helius_url, wallet, max_assets).await?;
+        listings.push((wallet.clone(), assets, partial));
+    }
+    let (assets, mut shares) = merge_listings(&wallets, listings);
+    let partial = shares.iter().any(|s| s.partial);
+
+    let (nfts, breakdown, spam) = sort_holdings(assets);
+    let total_nfts = nfts.len() as u32;
+    for nft in &nfts {
+        if let Some(share) = shares.iter_mut().find(|s| Some(s.address.as_str()) == nft.owner()) {
+            share.nfts += 1;
+        }
+    }
+
+    let mut collections = summarize_collections(nfts);
+    resolve_collection_names(&rpc_client, &rpc_url, &mut collections).await;
+
+    let balances = if with_balances {
+        let mut total = WalletBalances::default();
+        for share in shares.iter_mut() {
+            let mut wallet_balances = balances::fetch(&rpc_client, &rpc_url, &helius_url, &share.address).await;
+            if multi {
+                for warning in wallet_balances.warnings.iter_mut() {
+                    *warning = format!("{}: {}", share.address, warning);
+                }
+            }
+            share.sol_lamports = Some(wallet_balances.sol_lamports);
+            balances::merge(&mut total, wallet_balances);
+        }
+        Some(total)
     } else {
-        format!("https://mainnet.helius-rpc.com/?api-key={}", helius_api_key)
+        None
+    };
+
+    let activity = match max_txs {
+        Some(max) if !multi => Some(activity::profile(&rpc_client, &rpc_url, &wallets[0], max).await),
+        _ => None,
     };
 
-    let request_body = json!({
-        "jsonrpc": "2.0",
-        "id": "dossier-query",
-        "method": "getAssetsByOwner",
-        "params": {
-            "ownerAddress": wallet_address,
-            "page": 1,
-            "limit": 1000,
-            "displayOptions": {
-                "showCollectionMetadata": true
+    if !multi {
+        shares.clear();
+    }
+
+    Ok(DossierReport {
+        wallet_address: wallets[0].clone(),
+        wallets: shares,
+        total_nfts,
+        collections,
+        breakdown,
+        spam,
+        balances,
+        activity,
+        partial,
+        assessment: String::new(),
+        flags: Vec::new(),
+        warnings: Vec::new(),
+    })
+}
+
+// One asset list for the whole set. An asset delegated or escrowed between the
+// wallets can be listed under more than one of them; it is kept once.
+fn merge_listings(wallets: &[String], listings: Vec<(String, Vec<HeliusAsset>, bool)>) -> (Vec<HeliusAsset>, Vec<WalletShare>) {
+    let mut seen = HashSet::new();
+    let mut merged = Vec::new();
+    let mut shares = Vec::new();
+
+    for (wallet, assets, partial) in listings {
+        let mut share = WalletShare { address: wallet.clone(), partial, ..Default::default() };
+        for asset in assets {
+            let delegate = asset.ownership.as_ref()
+                .filter(|o| o.delegated)
+                .and_then(|o| o.delegate.as_deref());
+            if delegate.is_some_and(|d| d != wallet && wallets.iter().any(|w| w == d)) {
+                share.delegated_within += 1;
+            }
+            if seen.insert(asset.id.clone()) {
+                merged.push(asset);
+            } else {
+                share.duplicates += 1;
             }
         }
-    });
+        shares.push(share);
+    }
+    (merged, shares)
+}
 
-    let response = rpc_client
-        .post(&helius_url)
-        .json(&request_body)
-        .send()
-        .await?;
-
-    if !response.status().is_success() {
-        return Err(anyhow!(
-            "Helius API request failed with status: {}. Make sure HELIUS_API_KEY is set.",
-            response.status()
-        ));
+pub fn classify(asset: &HeliusAsset) -> AssetClass {
+    match asset.interface.as_deref() {
+        Some("FungibleToken" | "FungibleAsset") => AssetClass::Fungible,
+        Some("MplCoreAsset") => AssetClass::CoreAsset,
+        _ if asset.token_info.as_ref().and_then(|t| t.decimals).unwrap_or(0) > 0 => AssetClass::Fungible,
+        _ if asset.is_compressed() => AssetClass::CompressedNft,
+        Some("V1_NFT" | "V2_NFT" | "LEGACY_NFT" | "ProgrammableNFT" | "V1_PRINT" | "Custom") => AssetClass::Nft,
+        _ => AssetClass::Other,
     }
+}
+
+// Split a wallet listing into the NFTs worth assessing, per-class counts, and spam.
+// Burnt assets and fungibles are counted but dropped; spam is kept aside with its reasons.
+pub fn sort_holdings(assets: Vec<HeliusAsset>) -> (Vec<HeliusAsset>, AssetBreakdown, Vec<SpamAsset>) {
+    let airdrop_creators = spam::airdrop_creator_counts(&assets);
+    let mut breakdown = AssetBreakdown::default();
+    let mut nfts = Vec::new();
+    let mut spam_assets = Vec::new();
 
-    let assets_response: HeliusAssetsResponse = response.json().await?;
-    let assets = assets_response.result.items;
-    let total_nfts = assets_response.result.total;
+    for asset in assets {
+        if asset.burnt {
+            breakdown.burnt += 1;
+            continue;
+        }
 
-    let mut collection_counts: HashMap<String, u32> = HashMap::new();
+        let class = classify(&asset);
+        if class != AssetClass::Fungible {
+            let reasons = spam::asset_spam_reasons(&asset, &airdrop_creators);
+            if !reasons.is_empty() {
+                breakdown.spam += 1;
+                spam_assets.push(SpamAsset {
+                    name: asset.name().unwrap_or("?").to_string(),
+                    id: asset.id,
+                    reasons,
+                });
+                continue;
+            }
+        }
+
+        match class {
+            AssetClass::Nft => breakdown.nfts += 1,
+            AssetClass::CompressedNft => breakdown.compressed_nfts += 1,
+            AssetClass::CoreAsset => breakdown.core_assets += 1,
+            AssetClass::Fungible => breakdown.fungibles += 1,
+            AssetClass::Other => breakdown.other += 1,
+        }
+        if matches!(class, AssetClass::Nft | AssetClass::CompressedNft | AssetClass::CoreAsset) {
+            nfts.push(asset);
+        }
+    }
+
+    (nfts, breakdown, spam_assets)
+}
+
+// Group assets by collection mint, keeping whatever collection metadata DAS sent along.
+pub fn summarize_collections(assets: Vec<HeliusAsset>) -> Vec<CollectionSummary> {
+    let mut by_key: HashMap<String, CollectionSummary> = HashMap::new();
 
     for asset in assets {
-        let collection_name = if let Some(groupings) = asset.grouping {
-            groupings
-                .iter()
-                .find(|g| g.group_key == "collection")
-                .and_then(|g| Some(g.group_value.clone()))
-                .or_else(|| {
-                    // Fallback to metadata name
-                    asset.content
-                        .and_then(|c| c.metadata)
-                        .and_then(|m| m.name)
+        let owner = asset.owner().map(str::to_string);
+        let grouping = asset.grouping
+            .unwrap_or_default()
+            .into_iter()
+            .find(|g| g.group_key == "collection");
+
+        let summary = match grouping {
+            Some(g) => {
+                let meta = g.collection_metadata.clone();
+                let name = meta.as_ref()
+                    .and_then(|m| m.name.clone())
+                    .filter(|n| !n.trim().is_empty())
+                    .unwrap_or_else(|| g.group_value.clone());
+                by_key.entry(g.group_value.clone()).or_insert_with(|| CollectionSummary {
+                    name,
+                    address: Some(g.group_value.clone()),
+                    symbol: meta.as_ref().and_then(|m| m.symbol.clone()).filter(|s| !s.is_empty()),
+                    image: meta.and_then(|m| m.image).filter(|i| !i.is_empty()),
+                    count: 0,
+                    by_wallet: BTreeMap::new(),
+                })
+            }
+            None => {
+                // Fallback to metadata name
+                let name = asset.content
+                    .and_then(|c| c.metadata)
+                    .and_then(|m| m.name)
+                    .unwrap_or_else(|| "Unknown Collection".to_string());
+                by_key.entry(name.clone()).or_insert_with(|| CollectionSummary {
+                    name,
+                    address: None,
+                    symbol: None,
+                    image: None,
+                    count: 0,
+                    by_wallet: BTreeMap::new(),
                 })
-                .unwrap_or_else(|| "Unknown Collection".to_string())
-        } else {
-            "Unknown Collection".to_string()
+            }
         };
+        summary.count += 1;
+        if let Some(owner) = owner {
+            *summary.by_wallet.entry(owner).or_default() += 1;
+        }
+    }
+
+    let mut collections: Vec<CollectionSummary> = by_key.into_values().collect();
+    collections.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    collections
+}
+
+// Collections DAS had no metadata for still show a base58 address as their name;
+// read the collection NFT's own metadata account to get a human name instead.
+pub async fn resolve_collection_names(client: &Client, rpc_url: &str, collections: &mut [CollectionSummary]) {
+    let unnamed = collections
+        .iter_mut()
+        .filter(|c| c.address.as_deref() == Some(c.name.as_str()))
+        .take(NAME_LOOKUPS);
 
-        *collection_counts.entry(collection_name).or_insert(0) += 1;
+    for collection in unnamed {
+        let Some(address) = collection.address.clone() else { continue };
+        if let Ok(metadata) = unmask::fetch_metadata(client, rpc_url, &address).await {
+            let name = metadata.data.name.trim_end_matches('\0').trim().to_string();
+            let symbol = metadata.data.symbol.trim_end_matches('\0').trim().to_string();
+            if !name.is_empty() {
+                collection.name = name;
+            }
+            if collection.symbol.is_none() && !symbol.is_empty() {
+                collection.symbol = Some(symbol);
+            }
+        }
     }
+}
 
-    let mut collections: Vec<CollectionSummary> = collection_counts
-        .into_iter()
-        .map(|(name, count)| CollectionSummary { name, count })
-        .collect();
+// Page through getAssetsByOwner until the wallet is exhausted or `max_assets` is reached.
+// Returns the assets and whether the cap cut the listing short.
+pub async fn fetch_owner_assets(client: &Client, helius_url: &str, owner: &str, max_assets: u32) -> Result<(Vec<HeliusAsset>, bool)> {
+    let params = json!({
+        "ownerAddress": owner,
+        "displayOptions": {
+            "showCollectionMetadata": true,
+            "showFungible": true,
+            "showUnverifiedCollections": true
+        }
+    });
+    fetch_asset_pages(client, helius_url, "getAssetsByOwner", params, max_assets).await
+}
 
-    collections.sort_by(|a, b| b.count.cmp(&a.count));
+// Shared pager for the DAS listing methods; `params` gets `page` and `limit` added.
+pub async fn fetch_asset_pages(client: &Client, helius_url: &str, method: &str, params: Value, max_assets: u32) -> Result<(Vec<HeliusAsset>, bool)> {
+    let show_progress = stderr().is_terminal() && !style::current().quiet;
+    let mut assets: Vec<HeliusAsset> = Vec::new();
+    let mut page = 1;
 
-    Ok(DossierReport {
-        wallet_address,
-        total_nfts,
-        collections,
-    })
+    loop {
+        let mut params = params.clone();
+        params["page"] = json!(page);
+        params["limit"] = json!(PAGE_LIMIT);
+        let request_body = json!({
+            "jsonrpc": "2.0",
+            "id": "shinobi",
+            "method": method,
+            "params": params,
+        });
+
+        let response = client
+            .post(helius_url)
+            .json(&request_body)
+            .send()
+            .await
+            .map_err(|e| e.without_url())?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Helius API request failed with status: {}. Make sure HELIUS_API_KEY is set.",
+                response.status()
+            ));
+        }
+
+        let assets_response: HeliusAssetsResponse = response.json().await.map_err(|e| e.without_url())?;
+        let items = assets_response.result.items;
+        let last_page = (items.len() as u32) < PAGE_LIMIT;
+        assets.extend(items);
+
+        if show_progress {
+            eprint!("\r  📦 {} assets fetched (page {})", assets.len(), page);
+            let _ = stderr().flush();
+        }
+
+        if assets.len() as u32 >= max_assets {
+            // Only partial if there is something beyond the cap
+            let partial = assets.len() as u32 > max_assets || !last_page;
+            assets.truncate(max_assets as usize);
+            if show_progress {
+                eprintln!();
+            }
+            return Ok((assets, partial));
+        }
+        if last_page {
+            break;
+        }
+        page += 1;
+    }
+
+    if show_progress {
+        eprintln!();
+    }
+    Ok((assets, false))
 }
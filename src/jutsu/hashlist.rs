@@ -0,0 +1,70 @@
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use crate::input;
+use crate::jutsu::collection;
+use crate::model::HashlistDiff;
+use crate::programs::{CANDY_MACHINE_V2_PROGRAM, CANDY_MACHINE_V3_PROGRAM};
+use crate::rpc::create_rpc_client;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum HashlistKind {
+    /// Verified collection key (collection NFT mint)
+    Collection,
+    /// Verified first creator
+    Creator,
+    /// Candy machine id; its creator PDA is used as the first creator
+    CandyMachine,
+}
+
+// Every mint whose metadata matches, sorted, straight from getProgramAccounts.
+pub async fn run(address: String, kind: HashlistKind) -> Result<Vec<String>> {
+    input::validate_address(&address)?;
+    let (client, rpc_url) = create_rpc_client();
+
+    let filter_sets = match kind {
+        HashlistKind::Collection => collection::collection_filters(&address),
+        HashlistKind::Creator => collection::creator_filters(&address),
+        HashlistKind::CandyMachine => candy_machine_creators(&address)?
+            .iter()
+            .flat_map(|creator| collection::creator_filters(&creator.to_string()))
+            .collect(),
+    };
+
+    let mints = collection::metadata_mints(&client, &rpc_url, filter_sets).await?;
+    if mints.is_empty() {
+        return Err(anyhow!("No mints found for {}. Check --by, or whether your RPC allows getProgramAccounts", address));
+    }
+    Ok(mints.into_iter().collect())
+}
+
+// Candy Machine v2 and v3 both sign as a PDA of ["candy_machine", id]. Which
+// version the id belongs to doesn't matter: the wrong PDA simply matches nothing.
+fn candy_machine_creators(candy_machine: &str) -> Result<Vec<Pubkey>> {
+    let id = Pubkey::from_str(candy_machine)?;
+    [CANDY_MACHINE_V2_PROGRAM, CANDY_MACHINE_V3_PROGRAM]
+        .iter()
+        .map(|program| {
+            let program = Pubkey::from_str(program)?;
+            Ok(Pubkey::find_program_address(&[b"candy_machine", id.as_ref()], &program).0)
+        })
+        .collect()
+}
+
+pub fn diff(previous_path: &Path, current: &[String]) -> Result<HashlistDiff> {
+    let text = fs::read_to_string(previous_path)
+        .map_err(|e| anyhow!("Failed to read hash list '{}': {}", previous_path.display(), e))?;
+    let previous: BTreeSet<String> = input::parse_address_list(&text)?.into_iter().collect();
+    let current_set: BTreeSet<String> = current.iter().cloned().collect();
+
+    Ok(HashlistDiff {
+        previous: previous.len() as u32,
+        current: current_set.len() as u32,
+        added: current_set.difference(&previous).cloned().collect(),
+        removed: previous.difference(&current_set).cloned().collect(),
+    })
+}
@@ -0,0 +1,72 @@
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use crate::accounts;
+use crate::input;
+use crate::model::InspectReport;
+use crate::programs;
+use crate::rpc::{self, create_rpc_client};
+use serde_json::{json, Value};
+
+// Enough to see the layout of anything small and the start of anything big.
+const HEX_DUMP_BYTES: usize = 4096;
+
+// Fetch any account and decode it when its owner is a program we know the layouts
+// of. The RPC node parses SPL Token, stake, vote, sysvar and loader accounts itself;
+// the Metaplex programs are decoded here. Anything else is shown as a hex dump.
+pub async fn run(address: String, hex: bool) -> Result<InspectReport> {
+    input::validate_address(&address)?;
+    let (client, rpc_url) = create_rpc_client();
+    let mut warnings = Vec::new();
+
+    let result = rpc::call(&client, &rpc_url, "getAccountInfo", json!([address, { "encoding": "base64" }])).await?;
+    let account = result
+        .get("value")
+        .filter(|v| !v.is_null())
+        .ok_or_else(|| anyhow!("Account {} not found (no lamports, never created or closed)", address))?;
+    let owner = account.get("owner").and_then(Value::as_str).unwrap_or_default().to_string();
+    let data = account
+        .pointer("/data/0")
+        .and_then(Value::as_str)
+        .map(|b64| STANDARD.decode(b64))
+        .transpose()
+        .map_err(|e| anyhow!("base64 decode failed: {}", e))?
+        .unwrap_or_default();
+
+    let (mut kind, mut fields) = (None, Vec::new());
+    match accounts::decode(&owner, &data) {
+        Some(Ok(decoded)) => {
+            kind = Some(decoded.kind);
+            fields = decoded.fields;
+        }
+        Some(Err(e)) => warnings.push(format!("could not decode as a {} account: {}", programs::name(&owner).unwrap_or("known"), e)),
+        None => {
+            // Not ours; see whether the node can parse it
+            let parsed = rpc::call(&client, &rpc_url, "getAccountInfo", json!([address, { "encoding": "jsonParsed" }])).await;
+            match parsed {
+                Ok(parsed) => {
+                    if let Some(data) = parsed.pointer("/value/data").filter(|d| d.is_object()) {
+                        let program = data.get("program").and_then(Value::as_str).unwrap_or("parsed");
+                        let account_type = data.pointer("/parsed/type").and_then(Value::as_str).unwrap_or("account");
+                        kind = Some(format!("{} {}", program, account_type));
+                        fields = accounts::flatten_parsed(data.get("parsed").unwrap_or(data));
+                    }
+                }
+                Err(e) => warnings.push(format!("parsed lookup: {}", e)),
+            }
+        }
+    }
+
+    let show_hex = hex || (kind.is_none() && !data.is_empty());
+    Ok(InspectReport {
+        address,
+        lamports: account.get("lamports").and_then(Value::as_u64).unwrap_or(0),
+        owner_name: programs::name(&owner).map(str::to_string),
+        owner,
+        executable: account.get("executable").and_then(Value::as_bool).unwrap_or(false),
+        data_len: data.len(),
+        kind,
+        fields,
+        hex: show_hex.then(|| accounts::hex_dump(&data, 0, HEX_DUMP_BYTES)),
+        warnings,
+    })
+}
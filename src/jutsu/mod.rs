@@ -1,2 +1,15 @@
 pub mod unmask;
-pub mod dossier;
\ No newline at end of file
+pub mod dossier;
+pub mod audit;
+pub mod trace;
+pub mod compare;
+pub mod collection;
+pub mod snapshot;
+pub mod hashlist;
+pub mod rarity;
+pub mod search;
+pub mod authority;
+pub mod rules;
+pub mod inspect;
+pub mod pda;
+pub mod decode;
@@ -0,0 +1,80 @@
+use anyhow::{anyhow, Result};
+use crate::model::{DerivedAddress, PdaReport};
+use crate::pda::{self, Derived};
+use crate::programs::{TOKEN_2022_PROGRAM, TOKEN_PROGRAM};
+use crate::rpc::{self, create_rpc_client};
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+// Keys besides the mint that some of the addresses are derived from. Addresses whose
+// key wasn't given are left out.
+pub struct PdaInputs {
+    pub owner: Option<String>,
+    pub authority: Option<String>,
+    pub user: Option<String>,
+    pub edition: Option<u64>,
+    pub tree: Option<String>,
+}
+
+fn parse(label: &str, address: &str) -> Result<Pubkey> {
+    Pubkey::from_str(address).map_err(|e| anyhow!("Invalid {} '{}': {}", label, address, e))
+}
+
+// Every Metaplex and SPL address derived from a mint, and with `check` whether
+// each one exists on chain.
+pub async fn run(mint: String, inputs: PdaInputs, check: bool) -> Result<PdaReport> {
+    let mint_key = parse("mint address", &mint)?;
+    let mut derived: Vec<(&str, Derived)> = vec![
+        ("Metadata", pda::metadata(&mint_key)),
+        ("Master Edition", pda::edition(&mint_key)),
+    ];
+    if let Some(edition) = inputs.edition {
+        derived.push(("Edition Marker", pda::edition_marker(&mint_key, edition)));
+    }
+    if let Some(owner) = &inputs.owner {
+        let owner = parse("owner", owner)?;
+        let token_account = pda::associated_token_account(&owner, &mint_key, TOKEN_PROGRAM);
+        // Token records only exist for programmable NFTs, which live under SPL Token
+        let token_record = pda::token_record(&mint_key, &token_account.address);
+        derived.push(("Token Account", token_account));
+        derived.push(("Token-2022 Account", pda::associated_token_account(&owner, &mint_key, TOKEN_2022_PROGRAM)));
+        derived.push(("Token Record", token_record));
+    }
+    if let Some(authority) = &inputs.authority {
+        derived.push(("Collection Authority Record", pda::collection_authority_record(&mint_key, &parse("authority", authority)?)));
+    }
+    if let Some(user) = &inputs.user {
+        derived.push(("Use Authority Record", pda::use_authority_record(&mint_key, &parse("user", user)?)));
+    }
+    if let Some(tree) = &inputs.tree {
+        derived.push(("Tree Config", pda::tree_config(&parse("tree", tree)?)));
+    }
+
+    let mut derivations: Vec<DerivedAddress> = derived
+        .into_iter()
+        .map(|(name, d)| DerivedAddress {
+            name: name.to_string(),
+            address: d.address.to_string(),
+            bump: d.bump,
+            seeds: d.seeds,
+            exists: None,
+            owner: None,
+        })
+        .collect();
+
+    if check {
+        let (client, rpc_url) = create_rpc_client();
+        let addresses: Vec<&str> = derivations.iter().map(|d| d.address.as_str()).collect();
+        // Zero-length slice: only existence and owner are wanted
+        let result = rpc::call(&client, &rpc_url, "getMultipleAccounts",
+            json!([addresses, { "encoding": "base64", "dataSlice": { "offset": 0, "length": 0 } }])).await?;
+        let values = result.get("value").and_then(Value::as_array).cloned().unwrap_or_default();
+        for (derivation, value) in derivations.iter_mut().zip(values) {
+            derivation.exists = Some(!value.is_null());
+            derivation.owner = value.get("owner").and_then(Value::as_str).map(str::to_string);
+        }
+    }
+
+    Ok(PdaReport { mint, derivations })
+}
@@ -0,0 +1,78 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use crate::input;
+use crate::jutsu::unmask;
+use crate::model::{RarityReport, TraitSummary};
+use crate::rarity::{self, RarityMethod};
+use crate::rpc::create_rpc_client;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum LeaderboardFormat {
+    /// rank,mint,name,score
+    Csv,
+    /// The full report
+    Json,
+}
+
+// Every member of the collection ranked by rarity, from the cached trait table
+// unless it is stale or `refresh` is set.
+pub async fn run(collection: String, method: RarityMethod, max_assets: u32, refresh: bool) -> Result<RarityReport> {
+    input::validate_address(&collection)?;
+    let (client, rpc_url) = create_rpc_client();
+
+    let (table, cached) = rarity::load_table(&client, &rpc_url, &collection, max_assets, refresh).await?;
+    let name = unmask::fetch_metadata(&client, &rpc_url, &collection)
+        .await
+        .ok()
+        .map(|m| m.data.name.trim_end_matches('\0').trim().to_string())
+        .filter(|n| !n.is_empty());
+
+    let traits = table
+        .traits
+        .iter()
+        .filter_map(|(trait_type, values)| {
+            let (rarest, count) = values.iter().min_by_key(|(_, count)| **count)?;
+            Some(TraitSummary {
+                trait_type: trait_type.clone(),
+                values: values.len() as u32,
+                rarest: rarest.clone(),
+                rarest_count: *count,
+            })
+        })
+        .collect();
+
+    Ok(RarityReport {
+        collection,
+        name,
+        method,
+        members: table.items.len() as u32,
+        built_at: table.built_at,
+        cached,
+        source: table.source.clone(),
+        partial: table.partial,
+        traits,
+        ranking: rarity::rank(&table, method),
+    })
+}
+
+pub fn export(report: &RarityReport, format: LeaderboardFormat) -> Result<String> {
+    match format {
+        LeaderboardFormat::Json => Ok(serde_json::to_string_pretty(report)?),
+        LeaderboardFormat::Csv => {
+            let mut out = String::from("rank,mint,name,score\n");
+            for item in &report.ranking {
+                out.push_str(&format!("{},{},{},{}\n", item.rank, item.mint, csv_field(item.name.as_deref().unwrap_or("")), item.score));
+            }
+            Ok(out)
+        }
+    }
+}
+
+// Names are free text and can carry commas or quotes.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
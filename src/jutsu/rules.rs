@@ -0,0 +1,40 @@
+use anyhow::{anyhow, Result};
+use crate::model::{RuleResult, RuleTestReport};
+use crate::rules::{self, Outcome, ReportKind, RuleSet};
+use serde_json::Value;
+use std::path::PathBuf;
+
+// Run every rule that applies to each saved report (the output of `--json`) and
+// say what it made of it. Nothing is fetched.
+pub fn test(rule_set: &RuleSet, reports: Vec<PathBuf>) -> Result<Vec<RuleTestReport>> {
+    let mut tested = Vec::new();
+    for path in reports {
+        let text = std::fs::read_to_string(&path).map_err(|e| anyhow!("Failed to read '{}': {}", path.display(), e))?;
+        let report: Value = serde_json::from_str(&text).map_err(|e| anyhow!("'{}' is not JSON: {}", path.display(), e))?;
+        let kind = rules::report_kind(&report);
+
+        let results = rule_set
+            .rules
+            .iter()
+            .filter(|r| Some(r.report) == kind)
+            .map(|rule| {
+                let (matched, message, error) = match rules::check(rule, &report) {
+                    Outcome::Matched(message) => (true, Some(message), None),
+                    Outcome::NotMatched => (false, None, None),
+                    Outcome::Failed(e) => (false, None, Some(e)),
+                };
+                RuleResult { rule: rule.id.clone(), builtin: rule.builtin, matched, message, error }
+            })
+            .collect();
+
+        tested.push(RuleTestReport {
+            file: path.display().to_string(),
+            kind: kind.map(|k| match k {
+                ReportKind::Unmask => "unmask".to_string(),
+                ReportKind::Dossier => "dossier".to_string(),
+            }),
+            results,
+        });
+    }
+    Ok(tested)
+}
@@ -0,0 +1,212 @@
+use anyhow::{anyhow, Result};
+use crate::input;
+use crate::jutsu::collection::{self, Member, MemberDetails};
+use crate::jutsu::unmask;
+use crate::model::{SearchMatch, SearchReport};
+use crate::rarity;
+use crate::rpc::{create_probe_client, create_rpc_client};
+use crate::style;
+use regex::Regex;
+use reqwest::Client;
+use std::collections::BTreeMap;
+use std::io::{stderr, IsTerminal, Write};
+use tokio::task::JoinSet;
+
+// Members whose metadata is read at once when the listing doesn't carry it.
+const FETCH_CONCURRENCY: usize = 8;
+
+// What a member has to satisfy to be listed. Empty filters match everything.
+pub struct SearchFilters {
+    // (lowercased trait type, value); several values for one type are alternatives
+    pub traits: Vec<(String, String)>,
+    pub name: Option<Regex>,
+    pub mutable: Option<bool>,
+    pub primary_sale: Option<bool>,
+    pub holder: Option<String>,
+}
+
+impl SearchFilters {
+    pub fn new(traits: Vec<String>, name: Option<String>, mutable: Option<bool>, primary_sale: Option<bool>, holder: Option<String>) -> Result<Self> {
+        let mut traits = traits
+            .iter()
+            .map(|t| {
+                let (trait_type, value) = t
+                    .split_once('=')
+                    .ok_or_else(|| anyhow!("Invalid --trait '{}', expected TYPE=VALUE", t))?;
+                Ok((trait_type.trim().to_lowercase(), value.trim().to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // Grouped so that alternatives for one trait type sit next to each other
+        traits.sort_by(|a, b| a.0.cmp(&b.0));
+        let name = name
+            .map(|pattern| Regex::new(&pattern).map_err(|e| anyhow!("Invalid --name pattern: {}", e)))
+            .transpose()?;
+        if let Some(holder) = &holder {
+            input::validate_address(holder)?;
+        }
+        Ok(SearchFilters { traits, name, mutable, primary_sale, holder })
+    }
+
+    fn describe(&self) -> Vec<String> {
+        let mut filters: Vec<String> = self.traits.iter().map(|(t, v)| format!("{} = {}", t, v)).collect();
+        if let Some(name) = &self.name {
+            filters.push(format!("name ~ /{}/", name.as_str()));
+        }
+        if let Some(mutable) = self.mutable {
+            filters.push(if mutable { "mutable" } else { "immutable" }.to_string());
+        }
+        if let Some(primary_sale) = self.primary_sale {
+            filters.push(format!("primary sale happened = {}", primary_sale));
+        }
+        if let Some(holder) = &self.holder {
+            filters.push(format!("held by {}", holder));
+        }
+        filters
+    }
+
+    // Trait types were lowercased in `new`; values compare case-insensitively and
+    // "None" matches a missing trait.
+    fn traits_match(&self, attributes: &BTreeMap<String, String>) -> bool {
+        let mut types: Vec<&str> = self.traits.iter().map(|(t, _)| t.as_str()).collect();
+        types.dedup();
+        types.iter().all(|trait_type| {
+            let actual = attributes
+                .iter()
+                .find(|(t, _)| t.eq_ignore_ascii_case(trait_type))
+                .map(|(_, v)| v.as_str())
+                .unwrap_or(rarity::NONE_VALUE);
+            self.traits
+                .iter()
+                .filter(|(t, _)| t == trait_type)
+                .any(|(_, wanted)| wanted.eq_ignore_ascii_case(actual))
+        })
+    }
+
+    fn matches(&self, details: &MemberDetails) -> bool {
+        !(self.mutable.is_some_and(|m| details.mutable != Some(m))
+            || self.primary_sale.is_some_and(|p| details.primary_sale_happened != Some(p))
+            || self.name.as_ref().is_some_and(|re| !re.is_match(&details.name))
+            || (!self.traits.is_empty() && !self.traits_match(&details.attributes)))
+    }
+
+    // Whether the listing's details are enough to decide, or the metadata account
+    // has to be read.
+    fn answerable(&self, details: &MemberDetails) -> bool {
+        !(self.mutable.is_some() && details.mutable.is_none()
+            || self.primary_sale.is_some() && details.primary_sale_happened.is_none())
+    }
+}
+
+// Members of the collection matching every filter, with their current holders.
+// DAS listings carry name, traits and mutability, so those are filtered in place;
+// members listed from metadata accounts are read a few at a time, off-chain JSON
+// included only when traits are asked for.
+pub async fn run(collection: String, filters: SearchFilters, max_assets: u32) -> Result<SearchReport> {
+    input::validate_address(&collection)?;
+
+    let (client, rpc_url) = create_rpc_client();
+    let probe_client = create_probe_client();
+    let listing = collection::collection_members(&client, &rpc_url, &collection, max_assets).await?;
+    let mut warnings = listing.warnings;
+
+    let candidates: Vec<Member> = listing
+        .members
+        .into_iter()
+        .filter(|m| !m.burned)
+        .filter(|m| filters.holder.is_none() || m.owner == filters.holder)
+        .collect();
+    let scanned = candidates.len() as u32;
+
+    let mut matches = Vec::new();
+    let mut to_fetch = Vec::new();
+    for member in candidates {
+        match &member.details {
+            Some(details) if filters.answerable(details) => {
+                if filters.matches(details) {
+                    matches.push(search_match(&member, details, &filters));
+                }
+            }
+            _ => to_fetch.push(member),
+        }
+    }
+
+    let show_progress = stderr().is_terminal() && !style::current().quiet && !to_fetch.is_empty();
+    let want_traits = !filters.traits.is_empty();
+    let mut unreadable = 0;
+    for (i, chunk) in to_fetch.chunks(FETCH_CONCURRENCY).enumerate() {
+        if show_progress {
+            eprint!("\r  🔎 {}/{} members checked, {} matches", i * FETCH_CONCURRENCY, to_fetch.len(), matches.len());
+            let _ = stderr().flush();
+        }
+        let mut tasks = JoinSet::new();
+        for (offset, member) in chunk.iter().enumerate() {
+            let (client, rpc_url, probe_client, mint) = (client.clone(), rpc_url.clone(), probe_client.clone(), member.mint.clone());
+            tasks.spawn(async move { (offset, read_details(&client, &rpc_url, &probe_client, &mint, want_traits).await) });
+        }
+        let mut finished = Vec::with_capacity(chunk.len());
+        while let Some(joined) = tasks.join_next().await {
+            finished.push(joined?);
+        }
+        // Keep the listing order
+        finished.sort_by_key(|(offset, _)| *offset);
+        for (offset, details) in finished {
+            match details {
+                Some(details) if filters.matches(&details) => matches.push(search_match(&chunk[offset], &details, &filters)),
+                Some(_) => {}
+                None => unreadable += 1,
+            }
+        }
+    }
+    if show_progress {
+        eprintln!("\r  🔎 {}/{} members checked, {} matches", to_fetch.len(), to_fetch.len(), matches.len());
+    }
+
+    if unreadable > 0 {
+        warnings.push(format!("{} members skipped: metadata could not be read", unreadable));
+    }
+
+    Ok(SearchReport {
+        collection,
+        filters: filters.describe(),
+        scanned,
+        matches,
+        partial: listing.partial,
+        source: listing.source.to_string(),
+        warnings,
+    })
+}
+
+// Name, mutability and (when wanted) traits from the metadata account and its JSON.
+async fn read_details(client: &Client, rpc_url: &str, probe_client: &Client, mint: &str, want_traits: bool) -> Option<MemberDetails> {
+    let metadata = unmask::fetch_metadata(client, rpc_url, mint).await.ok()?;
+    let mut attributes = BTreeMap::new();
+    if want_traits {
+        let (off_chain, _) = unmask::fetch_off_chain(probe_client, &metadata).await;
+        for attribute in off_chain?.attributes {
+            let value = attribute.value.trim();
+            let value = if value.is_empty() { rarity::NONE_VALUE } else { value };
+            attributes.insert(attribute.trait_type.trim().to_string(), value.to_string());
+        }
+    }
+    Some(MemberDetails {
+        name: metadata.data.name.trim_end_matches('\0').trim().to_string(),
+        mutable: Some(metadata.is_mutable),
+        primary_sale_happened: Some(metadata.primary_sale_happened),
+        attributes,
+    })
+}
+
+fn search_match(member: &Member, details: &MemberDetails, filters: &SearchFilters) -> SearchMatch {
+    SearchMatch {
+        mint: member.mint.clone(),
+        name: details.name.clone(),
+        holder: member.owner.clone(),
+        mutable: details.mutable,
+        primary_sale_happened: details.primary_sale_happened,
+        attributes: if filters.traits.is_empty() {
+            Vec::new()
+        } else {
+            details.attributes.iter().map(|(t, v)| (t.clone(), v.clone())).collect()
+        },
+    }
+}
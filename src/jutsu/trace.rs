@@ -0,0 +1,417 @@
+use anyhow::Result;
+use crate::activity;
+use crate::input;
+use crate::model::{ClusterReport, FundingHop, GraphEdge, GraphNode, LinkGraph, SignatureInfo, TraceReport, WalletCluster};
+use crate::rpc::create_rpc_client;
+use crate::style;
+use crate::transactions::{self, SolTransfer};
+use reqwest::Client;
+use std::collections::{BTreeMap, HashSet};
+use std::io::{stderr, IsTerminal, Write};
+
+// Wallets with more history than this are treated as exchanges or services:
+// their first funder says nothing about who controls them.
+const HISTORY_LIMIT: usize = 10_000;
+// Earliest transactions searched for the funding transfer.
+const FUNDING_SCAN: usize = 10;
+// Recent transactions searched for co-signers and transfers in cluster mode.
+const LINK_SAMPLE: usize = 25;
+
+// What a wallet's recent transactions say about who it deals with.
+#[derive(Default)]
+struct Links {
+    // Other accounts that signed its transactions
+    signers: HashSet<String>,
+    // With the signature they came from, as both ends may have sampled the same transaction
+    transfers: Vec<(String, SolTransfer)>,
+}
+
+pub async fn run(wallet: String, max_hops: u32) -> Result<TraceReport> {
+    input::validate_address(&wallet)?;
+    let (client, rpc_url) = create_rpc_client();
+    Ok(trace_chain(&client, &rpc_url, &wallet, max_hops).await)
+}
+
+pub async fn cluster(wallets: Vec<String>, max_hops: u32) -> Result<ClusterReport> {
+    for wallet in &wallets {
+        input::validate_address(wallet)?;
+    }
+    let (client, rpc_url) = create_rpc_client();
+    let show_progress = stderr().is_terminal() && !style::current().quiet;
+
+    let mut traces = Vec::new();
+    let mut links = Vec::new();
+    let mut warnings = Vec::new();
+    for (i, wallet) in wallets.iter().enumerate() {
+        if show_progress {
+            eprint!("\r  🔎 tracing wallet {}/{}", i + 1, wallets.len());
+            let _ = stderr().flush();
+        }
+        traces.push(trace_chain(&client, &rpc_url, wallet, max_hops).await);
+        links.push(match recent_links(&client, &rpc_url, wallet).await {
+            Ok((l, skipped)) => {
+                warnings.extend(skipped);
+                l
+            }
+            Err(e) => {
+                warnings.push(format!("recent transactions of {}: {}", wallet, e));
+                Links::default()
+            }
+        });
+    }
+    if show_progress {
+        eprintln!();
+    }
+
+    // Marketplaces and relayers co-sign for countless unrelated users, so a signer
+    // shared by several wallets only links them when it isn't that busy itself
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for signer in links.iter().flat_map(|l| l.signers.iter()) {
+        *counts.entry(signer.as_str()).or_default() += 1;
+    }
+    let mut services = HashSet::new();
+    for (signer, count) in counts {
+        if count < 2 || wallets.iter().any(|w| w == signer) {
+            continue;
+        }
+        match history(&client, &rpc_url, signer).await {
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                services.insert(signer.to_string());
+            }
+            Err(e) => {
+                warnings.push(format!("could not check whether co-signer {} is a service, not linking on it: {}", signer, e));
+                services.insert(signer.to_string());
+            }
+        }
+    }
+
+    Ok(build_clusters(wallets, traces, links, &services, warnings))
+}
+
+// Follow first-funding transfers back until the hop limit, a loop, a busy
+// wallet or a wallet whose funding can't be found.
+async fn trace_chain(client: &Client, rpc_url: &str, wallet: &str, max_hops: u32) -> TraceReport {
+    let mut hops: Vec<FundingHop> = Vec::new();
+    let mut seen = HashSet::from([wallet.to_string()]);
+    let mut current = wallet.to_string();
+    // History of `current` when the busy check already fetched it
+    let mut known_history = None;
+
+    let stopped = loop {
+        if hops.len() as u32 >= max_hops {
+            break format!("hop limit of {} reached", max_hops);
+        }
+        let signatures = match known_history.take() {
+            Some(signatures) => signatures,
+            None => match history(client, rpc_url, &current).await {
+                Ok(Some(signatures)) => signatures,
+                Ok(None) => break busy(&current),
+                Err(e) => break format!("lookup failed for {}: {}", current, e),
+            },
+        };
+        match find_funder(client, rpc_url, &current, &signatures).await {
+            Ok(Some(mut hop)) => {
+                // Checked before the hop is recorded: an exchange paying out to many
+                // wallets must end the chain, not become a funder they all share
+                let funder = hop.funder.clone();
+                let funder_history = history(client, rpc_url, &funder).await;
+                hop.service = matches!(funder_history, Ok(None));
+                hops.push(hop);
+                if matches!(funder_history, Ok(None)) {
+                    break busy(&funder);
+                }
+                if !seen.insert(funder.clone()) {
+                    break format!("{} loops back into the chain", funder);
+                }
+                known_history = funder_history.ok().flatten();
+                current = funder;
+            }
+            Ok(None) => {
+                break format!("no inbound SOL transfer in the earliest transactions of {}", current)
+            }
+            Err(e) => break format!("lookup failed for {}: {}", current, e),
+        }
+    };
+
+    TraceReport {
+        wallet: wallet.to_string(),
+        source: hops.last().map(|h| h.funder.clone()),
+        hops,
+        stopped,
+    }
+}
+
+fn busy(wallet: &str) -> String {
+    format!("{} has more than {} transactions, likely an exchange or service", wallet, HISTORY_LIMIT)
+}
+
+// A wallet's signatures, or None when there are too many for it to be a person's wallet.
+async fn history(client: &Client, rpc_url: &str, wallet: &str) -> Result<Option<Vec<SignatureInfo>>> {
+    let (signatures, truncated) = activity::fetch_signatures(client, rpc_url, wallet, HISTORY_LIMIT).await?;
+    Ok((!truncated).then_some(signatures))
+}
+
+// The first SOL a wallet ever received, searched for in its oldest successful transactions.
+async fn find_funder(client: &Client, rpc_url: &str, wallet: &str, signatures: &[SignatureInfo]) -> Result<Option<FundingHop>> {
+    let earliest = signatures.iter().rev().filter(|s| s.err.is_none()).take(FUNDING_SCAN);
+    for sig in earliest {
+        let tx = transactions::fetch_parsed(client, rpc_url, &sig.signature).await?;
+        let inbound = transactions::sol_transfers(&tx)
+            .into_iter()
+            .find(|t| t.to == wallet && t.from != wallet);
+        if let Some(transfer) = inbound {
+            return Ok(Some(FundingHop {
+                wallet: wallet.to_string(),
+                funder: transfer.from,
+                lamports: transfer.lamports,
+                signature: sig.signature.clone(),
+                timestamp: transactions::block_time(&tx).or(sig.block_time),
+                service: false,
+            }));
+        }
+    }
+    Ok(None)
+}
+
+// Transactions that fail to load are skipped and counted in the returned warnings.
+async fn recent_links(client: &Client, rpc_url: &str, wallet: &str) -> Result<(Links, Vec<String>)> {
+    let (signatures, _) = activity::fetch_signatures(client, rpc_url, wallet, LINK_SAMPLE).await?;
+    let mut links = Links::default();
+    let mut warnings = Vec::new();
+    let mut failed = 0;
+    let mut last_error = None;
+
+    for sig in signatures.iter().filter(|s| s.err.is_none()) {
+        let tx = match transactions::fetch_parsed(client, rpc_url, &sig.signature).await {
+            Ok(tx) => tx,
+            Err(e) => {
+                failed += 1;
+                last_error = Some(e);
+                continue;
+            }
+        };
+        links.signers.extend(transactions::signers(&tx).into_iter().filter(|s| s != wallet));
+        links.transfers.extend(
+            transactions::sol_transfers(&tx)
+                .into_iter()
+                .filter(|t| t.from == wallet || t.to == wallet)
+                .map(|t| (sig.signature.clone(), t)),
+        );
+    }
+    if let Some(e) = last_error {
+        warnings.push(format!("{} transaction(s) of {} could not be fetched and were skipped (last error: {})", failed, wallet, e));
+    }
+    Ok((links, warnings))
+}
+
+fn find(parent: &mut [usize], i: usize) -> usize {
+    let mut root = i;
+    while parent[root] != root {
+        root = parent[root];
+    }
+    parent[i] = root;
+    root
+}
+
+fn join(parent: &mut [usize], members: &[usize]) {
+    let Some(&first) = members.first() else { return };
+    let root = find(parent, first);
+    for &m in &members[1..] {
+        let other = find(parent, m);
+        parent[other] = root;
+    }
+}
+
+// Wallets end up in one cluster when they share a funder anywhere along their
+// chains, share a co-signer that isn't a service, or sent SOL to each other.
+fn build_clusters(wallets: Vec<String>, traces: Vec<TraceReport>, links: Vec<Links>, services: &HashSet<String>, warnings: Vec<String>) -> ClusterReport {
+    let index = |address: &str| wallets.iter().position(|w| w == address);
+    let mut parent: Vec<usize> = (0..wallets.len()).collect();
+    // (a member, why it is linked); resolved to clusters once all joins are done
+    let mut reasons: Vec<(usize, String)> = Vec::new();
+
+    let mut nodes: BTreeMap<String, &str> = wallets.iter().map(|w| (w.clone(), "wallet")).collect();
+    let mut edges: BTreeMap<(String, String, &str), Option<u64>> = BTreeMap::new();
+
+    let mut funded: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (i, trace) in traces.iter().enumerate() {
+        for hop in &trace.hops {
+            nodes.entry(hop.funder.clone()).or_insert(if hop.service { "service" } else { "funder" });
+            edges.insert((hop.funder.clone(), hop.wallet.clone(), "funded"), Some(hop.lamports));
+            // An exchange funds countless unrelated wallets, so it never links them
+            if hop.service {
+                continue;
+            }
+            let members = funded.entry(hop.funder.as_str()).or_default();
+            if !members.contains(&i) {
+                members.push(i);
+            }
+        }
+    }
+    for (funder, mut members) in funded {
+        // A funder that is itself on the list belongs with the wallets it funded
+        if let Some(j) = index(funder) {
+            if !members.contains(&j) {
+                members.push(j);
+            }
+        }
+        if members.len() > 1 {
+            join(&mut parent, &members);
+            reasons.push((members[0], format!("funding chain runs through {}", funder)));
+        }
+    }
+
+    let mut signed: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (i, l) in links.iter().enumerate() {
+        for signer in &l.signers {
+            signed.entry(signer.as_str()).or_default().push(i);
+        }
+    }
+    for (signer, mut members) in signed {
+        if let Some(j) = index(signer) {
+            members.push(j);
+        }
+        if members.len() < 2 {
+            continue;
+        }
+        let service = services.contains(signer);
+        nodes.entry(signer.to_string()).or_insert(if service { "service" } else { "authority" });
+        for &m in &members {
+            if wallets[m] != signer {
+                edges.insert((signer.to_string(), wallets[m].clone(), "signed"), None);
+            }
+        }
+        if service {
+            continue;
+        }
+        join(&mut parent, &members);
+        reasons.push((members[0], format!("{} signed transactions for {} wallets", signer, members.len())));
+    }
+
+    let mut counted = HashSet::new();
+    for (i, l) in links.iter().enumerate() {
+        for (signature, transfer) in &l.transfers {
+            let other = if transfer.from == wallets[i] { &transfer.to } else { &transfer.from };
+            let Some(j) = index(other).filter(|j| *j != i) else { continue };
+            if !counted.insert((signature, &transfer.from, &transfer.to, transfer.lamports)) {
+                continue;
+            }
+            let key = (transfer.from.clone(), transfer.to.clone(), "transfer");
+            if !edges.contains_key(&key) {
+                reasons.push((i, format!("{} sent SOL to {}", transfer.from, transfer.to)));
+            }
+            let total = edges.entry(key).or_insert(Some(0));
+            *total = Some(total.unwrap_or(0) + transfer.lamports);
+            join(&mut parent, &[i, j]);
+        }
+    }
+
+    let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for i in 0..wallets.len() {
+        let root = find(&mut parent, i);
+        groups.entry(root).or_default().push(i);
+    }
+
+    let mut clusters = Vec::new();
+    let mut unclustered = Vec::new();
+    for (root, members) in groups {
+        if members.len() == 1 {
+            unclustered.push(wallets[members[0]].clone());
+            continue;
+        }
+        let links: Vec<String> = reasons
+            .iter()
+            .filter(|(m, _)| find(&mut parent, *m) == root)
+            .map(|(_, why)| why.clone())
+            .collect();
+        clusters.push(WalletCluster {
+            members: members.iter().map(|&m| wallets[m].clone()).collect(),
+            links,
+        });
+    }
+    clusters.sort_by_key(|c| std::cmp::Reverse(c.members.len()));
+
+    let graph = LinkGraph {
+        nodes: nodes
+            .into_iter()
+            .map(|(id, kind)| GraphNode { id, kind: kind.to_string() })
+            .collect(),
+        edges: edges
+            .into_iter()
+            .map(|((from, to, kind), lamports)| GraphEdge { from, to, kind: kind.to_string(), lamports })
+            .collect(),
+    };
+
+    ClusterReport {
+        wallets,
+        clusters,
+        unclustered,
+        traces,
+        graph,
+        warnings,
+    }
+}
+
+// The funding chain of a single trace as a graph, for DOT output.
+pub fn chain_graph(report: &TraceReport) -> LinkGraph {
+    let mut graph = LinkGraph::default();
+    graph.nodes.push(GraphNode { id: report.wallet.clone(), kind: "wallet".to_string() });
+    for hop in &report.hops {
+        if !graph.nodes.iter().any(|n| n.id == hop.funder) {
+            let kind = if hop.service { "service" } else { "funder" };
+            graph.nodes.push(GraphNode { id: hop.funder.clone(), kind: kind.to_string() });
+        }
+        graph.edges.push(GraphEdge {
+            from: hop.funder.clone(),
+            to: hop.wallet.clone(),
+            kind: "funded".to_string(),
+            lamports: Some(hop.lamports),
+        });
+    }
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: &str = "WaLLetA111111111111111111111111111111111111";
+    const B: &str = "WaLLetB111111111111111111111111111111111111";
+    const SIGNER: &str = "MarketpLace1111111111111111111111111111111";
+
+    fn untraced(wallet: &str) -> TraceReport {
+        TraceReport { wallet: wallet.to_string(), source: None, hops: Vec::new(), stopped: String::new() }
+    }
+
+    fn signed_by(signer: &str) -> Links {
+        Links { signers: HashSet::from([signer.to_string()]), transfers: Vec::new() }
+    }
+
+    fn cluster_of(services: &HashSet<String>) -> ClusterReport {
+        build_clusters(
+            vec![A.to_string(), B.to_string()],
+            vec![untraced(A), untraced(B)],
+            vec![signed_by(SIGNER), signed_by(SIGNER)],
+            services,
+            Vec::new(),
+        )
+    }
+
+    #[test]
+    fn shared_signer_links_wallets() {
+        let report = cluster_of(&HashSet::new());
+        assert_eq!(report.clusters.len(), 1);
+        assert_eq!(report.clusters[0].members, vec![A, B]);
+        assert!(report.clusters[0].links[0].contains(SIGNER));
+    }
+
+    #[test]
+    fn busy_signer_does_not_link_wallets() {
+        let report = cluster_of(&HashSet::from([SIGNER.to_string()]));
+        assert!(report.clusters.is_empty());
+        assert_eq!(report.unclustered, vec![A, B]);
+        // Still drawn, as a service
+        assert!(report.graph.nodes.iter().any(|n| n.id == SIGNER && n.kind == "service"));
+    }
+}
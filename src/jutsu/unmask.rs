@@ -1,179 +1,321 @@
 use anyhow::{anyhow, Result};
-use crate::rpc::create_rpc_client;
+use crate::rpc::{self, create_probe_client, create_rpc_client};
+use crate::probe::{self, resolve_uri};
+use reqwest::Client;
 use serde_json::{json, Value};
 use base64::engine::general_purpose::STANDARD;
 use base64::Engine as _;
-use borsh::BorshDeserialize;
 use solana_sdk::pubkey::Pubkey;
 use std::str::FromStr;
-use crate::model::{OffChainMetadata, UnmaskReport, Metadata};
-pub async fn run(mint_address:String, fetch_image: bool)->Result<UnmaskReport>{
+use crate::model::{AnimationMedia, AssetHealth, ItemRarity, OffChainMetadata, UnmaskReport, Metadata};
+use crate::media::{self, Preview};
+use crate::accounts;
+use crate::pda;
+use crate::rarity::{self, RarityMethod};
+use crate::risk;
+use crate::spam::{self, SpamText};
+use std::collections::BTreeMap;
+
+// Members read when a collection's trait table has to be built for --rarity.
+const RARITY_MAX_ASSETS: u32 = 25_000;
+
+pub async fn run(mint_address:String, fetch_image: bool, check_health: bool, rarity_method: Option<RarityMethod>, assess_risk: bool)->Result<UnmaskReport>{
 
     let (rpc_client, rpc_url) = create_rpc_client();
-    // Derive the metadata PDA for the mint so we query the metadata account (where URI lives).
-    let mint_pubkey = Pubkey::from_str(&mint_address)
-        .map_err(|e| anyhow!("Invalid mint address '{}': {}", mint_address, e))?;
+    let metadata = fetch_metadata(&rpc_client, &rpc_url, &mint_address).await?;
+
+    let probe_client = create_probe_client();
+    let mut asset_health = Vec::new();
 
-    // Metaplex Token Metadata program id (canonical)
-    let metadata_program_id = Pubkey::from_str("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s")?;
-
-    let seeds: &[&[u8]] = &[
-        b"metadata",
-        metadata_program_id.as_ref(),
-        mint_pubkey.as_ref(),
-    ];
-
-    let (metadata_pubkey, _bump) = Pubkey::find_program_address(seeds, &metadata_program_id);
-
-    let message = json!({
-        "jsonrpc":"2.0",
-        "id":1,
-        "method":"getAccountInfo",
-        "params":[
-            metadata_pubkey.to_string(),
-            {
-                "encoding":"base64"
+    let mut off_chain = on_chain_fallback(&metadata);
+
+    let (parsed, health) = fetch_off_chain(&probe_client, &metadata).await;
+    if let Some(parsed) = parsed {
+        off_chain = parsed;
+    }
+    // A failed metadata fetch is always worth showing; a healthy one only when asked
+    asset_health.extend(health.filter(|h| check_health || !h.is_ok()));
+
+    // Image bytes are downloaded when rendering or when a health check was asked for;
+    // either way failures end up in the health section instead of vanishing.
+    let mut image_data = None;
+    if (fetch_image || check_health) && !off_chain.image.is_empty() {
+        match resolve_uri(&off_chain.image) {
+            Some(img_url) => {
+                let (health, body) = probe::fetch(&probe_client, "image", &img_url).await;
+                if fetch_image && health.is_ok() {
+                    image_data = body;
+                }
+                asset_health.push(health);
             }
-        ]
-    });
-
-    // Send RPC and read raw text so we can inspect unexpected responses (errors, rate limits, etc.)
-    let resp_text = rpc_client
-        .post(&rpc_url)
-        .json(&message)
-        .send()
-        .await?
-        .text()
-        .await?;
-
-    // Parse into a Value so we can handle both success and error shapes gracefully.
-    let v: Value = serde_json::from_str(&resp_text)
-        .map_err(|e| anyhow!("Failed to parse RPC response as JSON: {}\nResponse text: {}", e, resp_text))?;
-
-    // Check for JSON-RPC error first
-    if let Some(err) = v.get("error") {
-        return Err(anyhow!("RPC returned error: {}", err));
+            None => asset_health.push(unfetchable("image", &off_chain.image)),
+        }
     }
 
-    let result = v.get("result").ok_or_else(|| anyhow!("Missing `result` field in RPC response: {}", resp_text))?;
+    let mut animation = None;
+    if fetch_image || check_health {
+        if let Some(animation_url) = off_chain.animation_url.clone().filter(|u| !u.is_empty()) {
+            match resolve_uri(&animation_url) {
+                Some(url) => {
+                    let (health, body) = probe::fetch(&probe_client, "animation", &url).await;
+                    if fetch_image && health.is_ok() {
+                        animation = Some(describe_animation(&probe_client, &url, &health, body).await);
+                    }
+                    if check_health {
+                        asset_health.push(health);
+                    }
+                }
+                None if check_health => asset_health.push(unfetchable("animation", &animation_url)),
+                None => {}
+            }
+        }
+    }
 
-    // If metadata PDA doesn't exist, the RPC returns value: null. Try a helpful fallback check.
-    if result.get("value").map(|v| v.is_null()).unwrap_or(true) {
-    // Try fetching the mint account itself so we can tell the user whether the mint exists.
-
-        let fallback_msg = json!({
-            "jsonrpc":"2.0",
-            "id":1,
-            "method":"getAccountInfo",
-            "params":[
-                mint_pubkey.to_string(),
-                { "encoding":"base64" }
-            ]
-        });
-
-        let fb_text = rpc_client
-            .post(&rpc_url)
-            .json(&fallback_msg)
-            .send()
-            .await?
-            .text()
-            .await?;
-
-        let fb_v: Value = serde_json::from_str(&fb_text)
-            .unwrap_or(Value::String(fb_text.clone()));
-
-        return Err(anyhow!("Metadata PDA {} not found. Fallback mint query returned: {}", metadata_pubkey, fb_v));
+    let mut warnings = Vec::new();
+    let mut rarity = None;
+    if let Some(method) = rarity_method {
+        match metadata.collection.as_ref().filter(|c| c.verified) {
+            Some(collection) => {
+                match item_rarity(&rpc_client, &rpc_url, &collection.key.to_string(), &mint_address, &off_chain, method).await {
+                    Ok(r) => rarity = Some(r),
+                    Err(e) => warnings.push(format!("rarity: {}", e)),
+                }
+            }
+            None => warnings.push("rarity: the NFT has no verified collection to rank it against".to_string()),
+        }
     }
 
-    // Extract the encrypted data scroll.
-    let base64_data = result
-        .get("value")
-        .and_then(|val| val.get("data"))
-        .and_then(|d| d.get(0))
-        .and_then(|s| s.as_str())
-        .ok_or_else(|| anyhow!("Failed to find account data in RPC result: {}", resp_text))?;
+    let mut risk = None;
+    if assess_risk {
+        let (report, risk_warnings) = risk::assess(&rpc_client, &rpc_url, &mint_address, &metadata, &off_chain.image).await;
+        risk = Some(report);
+        warnings.extend(risk_warnings.into_iter().map(|w| format!("risk: {}", w)));
+    }
 
-    let bytes = STANDARD.decode(base64_data)
-        .map_err(|e| anyhow!("base64 decode failed: {}", e))?;
-    
-    // Deserialize with a mutable slice reference to allow partial reads
-    let mut slice: &[u8] = &bytes;
-    let metadata = Metadata::deserialize(&mut slice)
-        .map_err(|e| anyhow!("Failed to parse metadata from bytes: {}", e))?;
-
-    let mut off_chain_uri = metadata.data.uri.trim_end_matches('\0').trim().to_string();
-
-    // Resolve common schemes and guard against empty/invalid URIs
-    if off_chain_uri.is_empty() {
-    // No off-chain URI present; return a partial report built from on-chain fields.
-        let off_chain_fallback = OffChainMetadata {
-            name: metadata.data.name.trim_end_matches('\0').trim().to_string(),
-            symbol: metadata.data.symbol.trim_end_matches('\0').trim().to_string(),
-            image: String::new(),
-            attributes: Vec::new(),
-        };
-        let report = UnmaskReport { on_chain: metadata, off_chain: off_chain_fallback, image_data: None };
-        return Ok(report);
+    let mut spam = spam_reasons(&off_chain);
+    if image_data.as_deref().is_some_and(spam::looks_like_text_image) {
+        spam.push("image looks like a text banner".to_string());
     }
 
-    // Handle IPFS URIs
-    if off_chain_uri.starts_with("ipfs://") {
-        off_chain_uri = off_chain_uri.replace("ipfs://", "https://ipfs.io/ipfs/");
-    } else if off_chain_uri.starts_with("ar://") {
-        off_chain_uri = off_chain_uri.replacen("ar://", "https://arweave.net/", 1);
-    } else if off_chain_uri.starts_with('/') {
-        // Relative arweave path
-        off_chain_uri = format!("https://arweave.net{}", off_chain_uri);
+    let report = UnmaskReport {
+        on_chain: metadata,
+        off_chain,
+        image_data,
+        animation,
+        asset_health,
+        rarity,
+        risk,
+        spam,
+        warnings,
+    };
+    Ok(report)
+}
+
+// Off-chain fields built from on-chain ones, used whenever the off-chain JSON can't be had.
+fn on_chain_fallback(metadata: &Metadata) -> OffChainMetadata {
+    OffChainMetadata {
+        name: metadata.data.name.trim_end_matches('\0').trim().to_string(),
+        symbol: metadata.data.symbol.trim_end_matches('\0').trim().to_string(),
+        image: String::new(),
+        animation_url: None,
+        description: None,
+        external_url: None,
+        attributes: Vec::new(),
     }
+}
+
+fn spam_reasons(off_chain: &OffChainMetadata) -> Vec<String> {
+    spam::text_spam_reasons(&SpamText {
+        name: &off_chain.name,
+        symbol: &off_chain.symbol,
+        description: off_chain.description.as_deref().unwrap_or(""),
+        external_url: off_chain.external_url.as_deref().unwrap_or(""),
+        attributes: off_chain.attributes.iter().map(|a| a.value.clone()).collect(),
+    })
+}
 
-    if !(off_chain_uri.starts_with("http://") || off_chain_uri.starts_with("https://")) {
-        // Off-chain URI is not HTTP(S); return a partial report without fetching.
-        let off_chain_fallback = OffChainMetadata {
-            name: metadata.data.name.trim_end_matches('\0').trim().to_string(),
-            symbol: metadata.data.symbol.trim_end_matches('\0').trim().to_string(),
-            image: String::new(),
-            attributes: Vec::new(),
-        };
-        let report = UnmaskReport { on_chain: metadata, off_chain: off_chain_fallback, image_data: None };
-        return Ok(report);
+// A report from the account alone, for data decoded offline: nothing is fetched,
+// so the off-chain side only has what the account itself says.
+pub fn offline_report(metadata: Metadata) -> UnmaskReport {
+    let off_chain = on_chain_fallback(&metadata);
+    UnmaskReport {
+        spam: spam_reasons(&off_chain),
+        on_chain: metadata,
+        off_chain,
+        image_data: None,
+        animation: None,
+        asset_health: Vec::new(),
+        rarity: None,
+        risk: None,
+        warnings: Vec::new(),
     }
-    let off_chain_response = rpc_client
-        .get(off_chain_uri)
-        .send()
-        .await?
-        .json::<OffChainMetadata>()
-        .await?;
-
-    // Optionally fetch image data if requested
-    let image_data = if fetch_image && !off_chain_response.image.is_empty() {
-        let mut img_url = off_chain_response.image.clone();
-
-        // Normalize image URL schemes
-        if img_url.starts_with("ipfs://") {
-            img_url = img_url.replace("ipfs://", "https://ipfs.io/ipfs/");
-        } else if img_url.starts_with("ar://") {
-            img_url = img_url.replacen("ar://", "https://arweave.net/", 1);
+}
+
+// The off-chain JSON behind a metadata account, plus the health of its URI
+// (None when the account has no URI at all).
+pub async fn fetch_off_chain(probe_client: &Client, metadata: &Metadata) -> (Option<OffChainMetadata>, Option<AssetHealth>) {
+    let Some(off_chain_uri) = resolve_uri(&metadata.data.uri) else {
+        if metadata.data.uri.trim_end_matches('\0').trim().is_empty() {
+            return (None, None);
         }
+        return (None, Some(unfetchable("metadata", &metadata.data.uri)));
+    };
 
-        if img_url.starts_with("http://") || img_url.starts_with("https://") {
-            match rpc_client.get(&img_url).send().await {
-                Ok(resp) => match resp.bytes().await {
-                    Ok(bytes) => Some(bytes.to_vec()),
-                    Err(_) => None,
-                },
-                Err(_) => None,
+    let (mut health, body) = probe::fetch(probe_client, "metadata", &off_chain_uri).await;
+    let mut parsed = None;
+    if let Some(body) = body.filter(|_| health.is_ok()) {
+        match serde_json::from_slice::<OffChainMetadata>(&body) {
+            Ok(metadata) => parsed = Some(metadata),
+            Err(e) => health.error = Some(format!("invalid metadata JSON: {}", e)),
+        }
+    }
+    (parsed, Some(health))
+}
+
+// The on-chain account holds raw pubkeys and the image bytes can be large, so --json
+// gets a flattened view instead of the report as is.
+pub fn to_json(report: &UnmaskReport) -> Value {
+    let metadata = &report.on_chain;
+    let creators: Vec<Value> = metadata.data.creators.iter().flatten()
+        .map(|c| json!({ "address": c.address.to_string(), "verified": c.verified, "share": c.share }))
+        .collect();
+    let attributes: Vec<Value> = report.off_chain.attributes.iter()
+        .map(|a| json!({ "trait_type": a.trait_type, "value": a.value }))
+        .collect();
+    json!({
+        "mint": metadata.mint.to_string(),
+        "name": report.off_chain.name,
+        "symbol": metadata.data.symbol.trim_end_matches('\0').trim(),
+        "uri": metadata.data.uri.trim_end_matches('\0').trim(),
+        "update_authority": metadata.update_authority.to_string(),
+        "mutable": metadata.is_mutable,
+        "primary_sale_happened": metadata.primary_sale_happened,
+        "seller_fee_basis_points": metadata.data.seller_fee_basis_points,
+        "creators": creators,
+        "collection": metadata.collection.as_ref().map(|c| json!({ "key": c.key.to_string(), "verified": c.verified })),
+        "image": report.off_chain.image,
+        "animation_url": report.off_chain.animation_url,
+        "attributes": attributes,
+        "asset_health": report.asset_health,
+        "rarity": report.rarity,
+        "risk": report.risk,
+        "spam": report.spam,
+        "warnings": report.warnings,
+    })
+}
+
+// Trait frequencies and rank of one NFT within its collection's (cached) trait table.
+async fn item_rarity(client: &Client, rpc_url: &str, collection: &str, mint: &str, off_chain: &OffChainMetadata, method: RarityMethod) -> Result<ItemRarity> {
+    let (table, _) = rarity::load_table(client, rpc_url, collection, RARITY_MAX_ASSETS, false).await?;
+
+    // The table's view of the NFT is normalized already; its own JSON is the fallback
+    // when a partial table doesn't include it
+    let traits = match table.items.get(mint) {
+        Some(item) => item.traits.clone(),
+        None => {
+            let mut traits: BTreeMap<String, String> = table.traits.keys().map(|t| (t.clone(), rarity::NONE_VALUE.to_string())).collect();
+            for attr in off_chain.attributes.iter() {
+                traits.insert(attr.trait_type.trim().to_string(), attr.value.trim().to_string());
             }
-        } else {
-            None
+            traits
         }
-    } else {
-        None
     };
 
-    let report = UnmaskReport {
-        on_chain: metadata,
-        off_chain: off_chain_response,
-        image_data,
+    let traits = rarity::trait_rarities(&table, &traits);
+    let rank = rarity::rank(&table, method).into_iter().find(|r| r.mint == mint).map(|r| r.rank);
+    Ok(ItemRarity {
+        collection: collection.to_string(),
+        method,
+        score: rarity::score(&traits, method),
+        rank,
+        members: table.items.len() as u32,
+        partial: table.partial,
+        traits,
+    })
+}
+
+// Classify the animation_url payload and dig out a still preview where that's possible
+// without external tools (embedded textures, cover art, HTML poster images).
+async fn describe_animation(client: &Client, url: &str, health: &AssetHealth, body: Option<Vec<u8>>) -> AnimationMedia {
+    let mut media = AnimationMedia {
+        format: health.detected_format.clone(),
+        size_bytes: health.size_bytes,
+        data: None,
+        preview: None,
+        preview_source: None,
     };
-    Ok(report)
-}
\ No newline at end of file
+    let Some(body) = body else { return media };
+
+    match health.detected_format.as_deref() {
+        Some("gif" | "png" | "webp" | "jpeg" | "bmp") => media.data = Some(body),
+        Some(format) => match media::extract_preview(format, &body) {
+            Some(Preview::Embedded(bytes, source)) => {
+                media.preview = Some(bytes);
+                media.preview_source = Some(source.to_string());
+            }
+            Some(Preview::Linked(link, source)) => {
+                // Preview links in HTML are usually relative to the page
+                let resolved = reqwest::Url::parse(url)
+                    .and_then(|base| base.join(&link))
+                    .map(|u| u.to_string())
+                    .ok()
+                    .or_else(|| resolve_uri(&link));
+                if let Some(preview_url) = resolved {
+                    let (preview_health, preview_body) = probe::fetch(client, "preview", &preview_url).await;
+                    if preview_health.is_ok() {
+                        media.preview = preview_body;
+                        media.preview_source = Some(source.to_string());
+                    }
+                }
+            }
+            None => {}
+        },
+        None => {}
+    }
+    media
+}
+
+fn unfetchable(label: &str, uri: &str) -> AssetHealth {
+    AssetHealth {
+        label: label.to_string(),
+        url: uri.to_string(),
+        status: None,
+        redirects: Vec::new(),
+        content_type: None,
+        detected_format: None,
+        size_bytes: None,
+        dimensions: None,
+        latency_ms: 0,
+        error: Some("unsupported URI scheme".to_string()),
+    }
+}
+
+// Fetch and decode the Token Metadata account for a mint.
+pub async fn fetch_metadata(rpc_client: &Client, rpc_url: &str, mint_address: &str) -> Result<Metadata> {
+    let mint_pubkey = Pubkey::from_str(mint_address)
+        .map_err(|e| anyhow!("Invalid mint address '{}': {}", mint_address, e))?;
+
+    let metadata_pubkey = pda::metadata_address(&mint_pubkey);
+
+    let result = rpc::call(rpc_client, rpc_url, "getAccountInfo", json!([metadata_pubkey.to_string(), { "encoding": "base64" }])).await?;
+
+    // If metadata PDA doesn't exist, the RPC returns value: null. Try a helpful fallback check.
+    if result.get("value").map(|v| v.is_null()).unwrap_or(true) {
+        // Try fetching the mint account itself so we can tell the user whether the mint exists.
+        let fallback = rpc::call(rpc_client, rpc_url, "getAccountInfo", json!([mint_pubkey.to_string(), { "encoding": "base64" }]))
+            .await
+            .unwrap_or_else(|e| Value::String(e.to_string()));
+        return Err(anyhow!("Metadata PDA {} not found. Fallback mint query returned: {}", metadata_pubkey, fallback));
+    }
+
+    // Extract the encrypted data scroll.
+    let base64_data = result
+        .pointer("/value/data/0")
+        .and_then(|s| s.as_str())
+        .ok_or_else(|| anyhow!("Failed to find account data in RPC result: {}", result))?;
+
+    let bytes = STANDARD.decode(base64_data)
+        .map_err(|e| anyhow!("base64 decode failed: {}", e))?;
+
+    accounts::decode_metadata(&bytes)
+}
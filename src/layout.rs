@@ -0,0 +1,250 @@
+use crate::style;
+use colored::*;
+use std::env;
+use terminal_size::{terminal_size, Width};
+use unicode_width::UnicodeWidthStr;
+
+// Boxes never grow past this, even on very wide terminals.
+const MAX_BOX_WIDTH: usize = 80;
+const MIN_BOX_WIDTH: usize = 40;
+
+pub fn terminal_width() -> usize {
+    if let Some((Width(w), _)) = terminal_size() {
+        return w as usize;
+    }
+    env::var("COLUMNS")
+        .ok()
+        .and_then(|c| c.parse().ok())
+        .unwrap_or(80)
+}
+
+// Remove ANSI escape sequences (CSI, OSC and other ESC-prefixed strings).
+pub fn strip_ansi(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            // CSI: parameters until a final byte in @..~
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC / APC / DCS: until BEL or ST (ESC \)
+            Some(']' | '_' | 'P') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+// Columns a string occupies on screen: escapes are free, emoji and CJK take two.
+pub fn display_width(s: &str) -> usize {
+    UnicodeWidthStr::width(strip_ansi(s).as_str())
+}
+
+// Cut a possibly styled string down to `max` columns, ending with an ellipsis.
+// Escape sequences are kept so colours stay balanced; a reset is appended when cutting.
+pub fn truncate(s: &str, max: usize) -> String {
+    if display_width(s) <= max {
+        return s.to_string();
+    }
+    if max == 0 {
+        return String::new();
+    }
+
+    // Width is measured on the accumulated text, not per char, so that
+    // emoji + variation selector sequences count as the two columns they take.
+    let mut out = String::new();
+    let mut plain = String::new();
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Copy the escape through untouched
+            out.push(c);
+            if chars.peek() == Some(&'[') {
+                out.push(chars.next().unwrap_or('['));
+                for c in chars.by_ref() {
+                    out.push(c);
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            continue;
+        }
+        plain.push(c);
+        if UnicodeWidthStr::width(plain.as_str()) > max - 1 {
+            break;
+        }
+        out.push(c);
+    }
+    out.push('…');
+    out.push_str("\x1b[0m");
+    out
+}
+
+// Truncate or right-pad a styled string to exactly `width` columns.
+pub fn pad_to(s: &str, width: usize) -> String {
+    let cut = truncate(s, width);
+    let pad = width.saturating_sub(display_width(&cut));
+    format!("{}{}", cut, " ".repeat(pad))
+}
+
+// Greedy word wrap of plain text to `width` columns. Words longer than a line
+// (pubkeys, URLs) are broken at character boundaries.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut current_w = 0;
+
+    for word in text.split_whitespace() {
+        let word_w = UnicodeWidthStr::width(word);
+        let needed = if current.is_empty() { word_w } else { current_w + 1 + word_w };
+
+        if needed <= width {
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(word);
+            current_w = needed;
+            continue;
+        }
+
+        if !current.is_empty() {
+            lines.push(std::mem::take(&mut current));
+            current_w = 0;
+        }
+
+        if word_w <= width {
+            current.push_str(word);
+            current_w = word_w;
+        } else {
+            for c in word.chars() {
+                current.push(c);
+                let w = UnicodeWidthStr::width(current.as_str());
+                if w > width && current.chars().count() > 1 {
+                    current.pop();
+                    lines.push(std::mem::take(&mut current));
+                    current.push(c);
+                }
+                current_w = UnicodeWidthStr::width(current.as_str());
+            }
+        }
+    }
+
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+// A bordered box whose right edge always lines up, sized to the terminal.
+// In plain mode the same calls produce unadorned "label value" lines instead.
+pub struct Frame {
+    // Columns available between "║ " and " ║"
+    inner: usize,
+    // Width of the label column used by `field`
+    label_width: usize,
+    plain: bool,
+}
+
+impl Frame {
+    pub fn new(label_width: usize) -> Self {
+        let total = terminal_width().clamp(MIN_BOX_WIDTH, MAX_BOX_WIDTH);
+        Frame {
+            inner: total - 4,
+            label_width,
+            plain: style::current().plain,
+        }
+    }
+
+    pub fn inner_width(&self) -> usize {
+        self.inner
+    }
+
+    pub fn top(&self) {
+        if !self.plain {
+            println!("{}", format!("╔{}╗", "═".repeat(self.inner + 2)).bright_cyan());
+        }
+    }
+
+    pub fn divider(&self) {
+        if !self.plain {
+            println!("{}", format!("╟{}╢", "─".repeat(self.inner + 2)).bright_cyan());
+        }
+    }
+
+    pub fn bottom(&self) {
+        if self.plain {
+            println!();
+        } else {
+            println!("{}", format!("╚{}╝", "═".repeat(self.inner + 2)).bright_cyan());
+        }
+    }
+
+    // One line of (possibly styled) content, truncated if it doesn't fit.
+    pub fn row(&self, content: &str) {
+        if self.plain {
+            println!("{}", style::clean(content));
+            return;
+        }
+        let content = truncate(content, self.inner);
+        let pad = self.inner - display_width(&content);
+        println!("{} {}{} {}", "║".bright_cyan(), content, " ".repeat(pad), "║".bright_cyan());
+    }
+
+    pub fn blank(&self) {
+        self.row("");
+    }
+
+    // Section title between two dividers.
+    pub fn section(&self, title: &str) {
+        if self.plain {
+            let title = style::clean(title);
+            println!("\n{}\n{}", title, "-".repeat(display_width(&title)));
+            return;
+        }
+        self.divider();
+        self.row(title);
+        self.divider();
+    }
+
+    // "label  value" with the value wrapped under itself when it's too long.
+    // Plain output never wraps, so every fact stays on one greppable line.
+    pub fn field(&self, label: &str, value: &str, paint: impl Fn(&str) -> ColoredString) {
+        if self.plain {
+            println!("{} {}", style::clean(label), paint(value));
+            return;
+        }
+        let label_w = display_width(label);
+        let column = self.label_width.max(label_w + 1);
+        let value_w = self.inner.saturating_sub(column).max(8);
+
+        for (i, chunk) in wrap(value, value_w).iter().enumerate() {
+            let lead = if i == 0 {
+                format!("{}{}", label, " ".repeat(column - label_w))
+            } else {
+                " ".repeat(column)
+            };
+            self.row(&format!("{}{}", lead, paint(chunk)));
+        }
+    }
+}
@@ -4,23 +4,230 @@ mod display;
 mod errors;
 mod jutsu;
 mod model;
-use cli::Commands;
+mod probe;
+mod input;
+mod render;
+mod media;
+mod layout;
+mod style;
+mod spam;
+mod programs;
+mod balances;
+mod activity;
+mod transactions;
+mod rarity;
+mod risk;
+mod rules;
+mod accounts;
+mod pda;
+use cli::{Commands, RulesAction};
 use clap::Parser;
 #[tokio::main]
 async fn main()->anyhow::Result<()> {
     let cli = cli::Cli::parse();
+    style::init(cli.plain, cli.quiet, cli.theatrics);
 
     //it decides which specialist to send the thing
     match cli.command{
-     Commands::Unmask {mint_address, image}=>{
+     Commands::Unmask {mint_address, image, image_args, health, rarity, scan_image, no_risk, json}=>{
+        let image_opts = render::ImageOptions::new(
+            image_args.renderer,
+            image_args.width,
+            image_args.height,
+            &image_args.ramp,
+            image_args.dither,
+            image_args.animation,
+            image_args.max_frames,
+        )?;
+        let fetch_image = image || image_args.save_image.is_some() || scan_image;
+
         //we dispatch the unmask command to the jutsu module
-       let report = jutsu::unmask::run(mint_address, image).await?;
-        display::print_unmask_report(report).await;
+       let rule_set = rules::load(cli.rules.as_deref())?;
+       let mut report = jutsu::unmask::run(mint_address, fetch_image, health, rarity, !no_risk).await?;
+        rule_set.apply_unmask(&mut report);
+
+        if let Some(path) = &image_args.save_image {
+            match &report.image_data {
+                Some(bytes) => render::save_image(bytes, path, image_args.save_width)?,
+                None => eprintln!("⚠️  No image could be downloaded, nothing saved to {}", path.display()),
+            }
+        }
+        if json {
+            display::print_json(&jutsu::unmask::to_json(&report))?;
+        } else {
+            display::print_unmask_report(report, image.then_some(&image_opts)).await;
+        }
+     }
+     Commands::Dossier {wallets, file, max_assets, skip_balances, skip_activity, max_txs, json}=>{
+        //we dispatch the dossier command to analyze a wallet (or several)
+       let wallets = input::collect_addresses(wallets, file.as_deref())?;
+       let max_txs = (!skip_activity).then_some(max_txs);
+       let rule_set = rules::load(cli.rules.as_deref())?;
+       let mut report = jutsu::dossier::run(wallets, max_assets, !skip_balances, max_txs).await?;
+        rule_set.apply_dossier(&mut report);
+        if json {
+            display::print_json(&report)?;
+        } else {
+            display::print_dossier_report(report).await;
+        }
+     }
+     Commands::AuditAssets {mints, file, concurrency}=>{
+       let mints = input::collect_addresses(mints, file.as_deref())?;
+       let report = jutsu::audit::run(mints, concurrency).await?;
+        display::print_audit_report(report);
+     }
+     Commands::Trace {wallets, file, hops, cluster, json, dot}=>{
+       let wallets = input::collect_addresses(wallets, file.as_deref())?;
+        if cluster {
+            let report = jutsu::trace::cluster(wallets, hops).await?;
+            if json {
+                display::print_json(&report)?;
+            } else if dot {
+                display::print_dot(&report.graph);
+            } else {
+                display::print_cluster_report(report);
+            }
+        } else {
+            let [wallet] = <[String; 1]>::try_from(wallets)
+                .map_err(|_| anyhow::anyhow!("trace follows one wallet at a time; pass --cluster to group several"))?;
+            let report = jutsu::trace::run(wallet, hops).await?;
+            if json {
+                display::print_json(&report)?;
+            } else if dot {
+                display::print_dot(&jutsu::trace::chain_graph(&report));
+            } else {
+                display::print_trace_report(report);
+            }
+        }
+     }
+     Commands::Compare {wallet_a, wallet_b, max_assets, max_txs, json}=>{
+       let report = jutsu::compare::run(wallet_a, wallet_b, max_assets, max_txs).await?;
+        if json {
+            display::print_json(&report)?;
+        } else {
+            display::print_compare_report(report);
+        }
+     }
+     Commands::Collection {collection_mint, max_assets, json}=>{
+       let report = jutsu::collection::run(collection_mint, max_assets).await?;
+        if json {
+            display::print_json(&report)?;
+        } else {
+            display::print_collection_report(report);
+        }
+     }
+     Commands::Snapshot {targets, by, file, exclude_escrow, format, output, max_assets}=>{
+       let targets = input::collect_addresses(targets, file.as_deref())?;
+       let report = jutsu::snapshot::run(by, targets, exclude_escrow, max_assets).await?;
+       let text = jutsu::snapshot::export(&report, format)?;
+        match &output {
+            Some(path) => std::fs::write(path, text)
+                .map_err(|e| anyhow::anyhow!("Failed to write '{}': {}", path.display(), e))?,
+            None => print!("{}", text),
+        }
+        display::print_snapshot_summary(&report, output.as_deref());
+     }
+     Commands::Hashlist {address, by, output, diff, json}=>{
+       let mints = jutsu::hashlist::run(address, by).await?;
+        if let Some(path) = &output {
+            std::fs::write(path, serde_json::to_string_pretty(&mints)?)
+                .map_err(|e| anyhow::anyhow!("Failed to write '{}': {}", path.display(), e))?;
+        }
+        match &diff {
+            Some(previous) => {
+                let changes = jutsu::hashlist::diff(previous, &mints)?;
+                if json {
+                    display::print_json(&changes)?;
+                } else {
+                    display::print_hashlist_diff(&changes);
+                }
+            }
+            None if output.is_none() => display::print_json(&mints)?,
+            None => {}
+        }
+     }
+     Commands::Rarity {collection, method, refresh, top, format, output, max_assets}=>{
+       let report = jutsu::rarity::run(collection, method, max_assets, refresh).await?;
+        match format {
+            Some(format) => {
+                let text = jutsu::rarity::export(&report, format)?;
+                match &output {
+                    Some(path) => std::fs::write(path, text)
+                        .map_err(|e| anyhow::anyhow!("Failed to write '{}': {}", path.display(), e))?,
+                    None => print!("{}", text),
+                }
+            }
+            None => display::print_rarity_report(report, top),
+        }
+     }
+     Commands::Search {collection, traits, name, mutable, immutable, primary_sale, holder, max_assets, json}=>{
+       let mutable = (mutable || immutable).then_some(mutable);
+       let filters = jutsu::search::SearchFilters::new(traits, name, mutable, primary_sale, holder)?;
+       let report = jutsu::search::run(collection, filters, max_assets).await?;
+        if json {
+            display::print_json(&report)?;
+        } else {
+            display::print_search_report(report);
+        }
+     }
+     Commands::Authority {address, max_accounts, max_txs, json}=>{
+       let report = jutsu::authority::run(address, max_accounts, max_txs).await?;
+        if json {
+            display::print_json(&report)?;
+        } else {
+            display::print_authority_report(report);
+        }
+     }
+     Commands::Rules {action}=>{
+       let rule_set = rules::load(cli.rules.as_deref())?;
+        match action {
+            RulesAction::Test {reports, json} => {
+                let results = jutsu::rules::test(&rule_set, reports)?;
+                if json {
+                    display::print_json(&results)?;
+                } else {
+                    display::print_rule_tests(&results);
+                }
+            }
+            RulesAction::List => display::print_rules(&rule_set),
+        }
+     }
+     Commands::Inspect {address, hex, json}=>{
+       let report = jutsu::inspect::run(address, hex).await?;
+        if json {
+            display::print_json(&report)?;
+        } else {
+            display::print_inspect_report(report);
+        }
+     }
+     Commands::Pda {mint, owner, authority, user, edition, tree, check, json}=>{
+       let inputs = jutsu::pda::PdaInputs {owner, authority, user, edition, tree};
+       let report = jutsu::pda::run(mint, inputs, check).await?;
+        if json {
+            display::print_json(&report)?;
+        } else {
+            display::print_pda_report(report);
+        }
      }
-     Commands::Dossier {wallet_address}=>{
-        //we dispatch the dossier command to analyze a wallet
-       let report = jutsu::dossier::run(wallet_address).await?;
-        display::print_dossier_report(report).await;
+     Commands::Decode {kind, base64, file, diagnose, json}=>{
+       let data = jutsu::decode::read_input(base64, file.as_deref())?;
+        if diagnose {
+            let diagnosis = jutsu::decode::diagnose(kind, &data);
+            if json {
+                display::print_json(&diagnosis)?;
+            } else {
+                display::print_decode_diagnosis(&diagnosis);
+            }
+        } else {
+            let rule_set = rules::load(cli.rules.as_deref())?;
+            let mut report = jutsu::decode::run(kind, &data)?;
+            rule_set.apply_unmask(&mut report);
+            if json {
+                display::print_json(&jutsu::unmask::to_json(&report))?;
+            } else {
+                display::print_unmask_report(report, None).await;
+            }
+        }
      }
     }
     Ok(())
@@ -0,0 +1,127 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use serde_json::Value;
+
+// Where a still preview for an animation_url can come from without external tools.
+pub enum Preview {
+    // Image bytes found inside the payload itself
+    Embedded(Vec<u8>, &'static str),
+    // A URL (absolute or relative to the page) that should be fetched
+    Linked(String, &'static str),
+}
+
+pub fn extract_preview(format: &str, bytes: &[u8]) -> Option<Preview> {
+    match format {
+        "glb" => glb_first_image(bytes).map(|b| Preview::Embedded(b, "embedded glTF texture")),
+        "mp4" | "mov" => mp4_cover_art(bytes).map(|b| Preview::Embedded(b, "embedded cover art")),
+        "html" => html_preview_url(&String::from_utf8_lossy(bytes)),
+        _ => None,
+    }
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    bytes
+        .get(at..at + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn read_u32_be(bytes: &[u8], at: usize) -> Option<u32> {
+    bytes
+        .get(at..at + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+// First image of a binary glTF: either a bufferView into the BIN chunk or a data: URI.
+pub fn glb_first_image(bytes: &[u8]) -> Option<Vec<u8>> {
+    if !bytes.starts_with(b"glTF") {
+        return None;
+    }
+    let total = (read_u32(bytes, 8)? as usize).min(bytes.len());
+
+    let mut json: Option<Value> = None;
+    let mut bin: Option<&[u8]> = None;
+    let mut offset = 12;
+    while offset + 8 <= total {
+        let len = read_u32(bytes, offset)? as usize;
+        let kind = bytes.get(offset + 4..offset + 8)?;
+        let data = bytes.get(offset + 8..offset + 8 + len)?;
+        match kind {
+            b"JSON" => json = serde_json::from_slice(data).ok(),
+            b"BIN\0" => bin = Some(data),
+            _ => {}
+        }
+        offset += 8 + len;
+    }
+
+    let json = json?;
+    let image = json.get("images")?.as_array()?.first()?;
+
+    if let Some(uri) = image.get("uri").and_then(|u| u.as_str()) {
+        let (_, payload) = uri.strip_prefix("data:")?.split_once(";base64,")?;
+        return STANDARD.decode(payload).ok();
+    }
+
+    let view_index = image.get("bufferView")?.as_u64()? as usize;
+    let view = json.get("bufferViews")?.as_array()?.get(view_index)?;
+    let start = view.get("byteOffset").and_then(|o| o.as_u64()).unwrap_or(0) as usize;
+    let len = view.get("byteLength")?.as_u64()? as usize;
+    bin?.get(start..start + len).map(|b| b.to_vec())
+}
+
+// Cover art stored in an MP4/MOV `covr` atom (moov/udta/meta/ilst/covr/data).
+pub fn mp4_cover_art(bytes: &[u8]) -> Option<Vec<u8>> {
+    let pos = bytes.windows(4).position(|w| w == b"covr")?;
+    let data_size = read_u32_be(bytes, pos + 4)? as usize;
+    if bytes.get(pos + 8..pos + 12)? != b"data" || data_size < 16 {
+        return None;
+    }
+    // data atom: size, 'data', type, locale, payload
+    bytes.get(pos + 20..pos + 4 + data_size).map(|b| b.to_vec())
+}
+
+// og:image, a <video poster>, or the first <img>, in that order of preference.
+pub fn html_preview_url(html: &str) -> Option<Preview> {
+    let lower = html.to_ascii_lowercase();
+
+    if let Some(meta) = find_tag(&lower, "og:image") {
+        if let Some(url) = attr_value(&html[meta.0..meta.1], &lower[meta.0..meta.1], "content") {
+            return Some(Preview::Linked(url, "og:image"));
+        }
+    }
+    if let Some(at) = lower.find("poster=") {
+        if let Some(url) = attr_value(&html[at..], &lower[at..], "poster") {
+            return Some(Preview::Linked(url, "video poster"));
+        }
+    }
+    if let Some(at) = lower.find("<img") {
+        let end = lower[at..].find('>').map(|e| at + e).unwrap_or(lower.len());
+        if let Some(url) = attr_value(&html[at..end], &lower[at..end], "src") {
+            return Some(Preview::Linked(url, "first <img>"));
+        }
+    }
+    None
+}
+
+// Bounds of the tag containing `needle`.
+fn find_tag(lower: &str, needle: &str) -> Option<(usize, usize)> {
+    let at = lower.find(needle)?;
+    let start = lower[..at].rfind('<')?;
+    let end = lower[at..].find('>').map(|e| at + e)?;
+    Some((start, end))
+}
+
+fn attr_value(original: &str, lower: &str, name: &str) -> Option<String> {
+    let key = format!("{}=", name);
+    let at = lower.find(&key)? + key.len();
+    let rest = &original[at..];
+    let value = match rest.chars().next()? {
+        q @ ('"' | '\'') => rest[1..].split(q).next()?,
+        _ => rest.split(|c: char| c.is_whitespace() || c == '>').next()?,
+    };
+    let value = value.trim();
+    if value.is_empty() || value.starts_with("data:") {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
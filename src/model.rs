@@ -1,12 +1,22 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use borsh::{BorshDeserialize, BorshSerialize};
+use crate::rarity::RarityMethod;
 
 #[derive(Deserialize, Debug)]
 pub struct OffChainMetadata {
     pub name: String,
     #[allow(dead_code)]
     pub symbol: String,
+    #[serde(default)]
     pub image: String,
+    #[serde(default)]
+    pub animation_url: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub external_url: Option<String>,
+    #[serde(default)]
     pub attributes: Vec<Attribute>,
 }
 
@@ -14,9 +24,19 @@ pub struct OffChainMetadata {
 #[derive(Deserialize, Debug)]
 pub struct Attribute {
     pub trait_type: String,
+    #[serde(deserialize_with = "attribute_value")]
     pub value: String,
 }
 
+// Values are strings by convention, but numbers and booleans turn up too.
+fn attribute_value<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(s) => s,
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    })
+}
+
 // Simplified Metadata struct for mpl-token-metadata v3.2
 #[derive(BorshDeserialize, BorshSerialize, Debug, Clone)]
 pub struct Metadata {
@@ -79,6 +99,76 @@ pub struct UnmaskReport{
     pub on_chain: Metadata,
     pub off_chain: OffChainMetadata,
     pub image_data: Option<Vec<u8>>,
+    pub animation: Option<AnimationMedia>,
+    pub asset_health: Vec<AssetHealth>,
+    pub rarity: Option<ItemRarity>,
+    pub risk: Option<RiskReport>,
+    // Why the NFT looks like a phishing airdrop; empty when it doesn't
+    pub spam: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+// What we could learn about the content behind `animation_url`.
+pub struct AnimationMedia {
+    pub format: Option<String>,
+    pub size_bytes: Option<u64>,
+    pub data: Option<Vec<u8>>,
+    pub preview: Option<Vec<u8>>,
+    pub preview_source: Option<String>,
+}
+
+// Result of probing one off-chain URL (metadata JSON, image or animation).
+#[derive(Serialize, Debug, Clone)]
+pub struct AssetHealth {
+    pub label: String,
+    pub url: String,
+    pub status: Option<u16>,
+    pub redirects: Vec<String>,
+    pub content_type: Option<String>,
+    pub detected_format: Option<String>,
+    pub size_bytes: Option<u64>,
+    pub dimensions: Option<(u32, u32)>,
+    pub latency_ms: u128,
+    pub error: Option<String>,
+}
+
+impl AssetHealth {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none() && self.status.map(|s| (200..300).contains(&s)).unwrap_or(false)
+    }
+
+    // Human readable problems; empty when the asset looks healthy.
+    pub fn issues(&self) -> Vec<String> {
+        let mut issues = Vec::new();
+        if let Some(err) = &self.error {
+            issues.push(err.clone());
+        }
+        if let Some(status) = self.status {
+            if !(200..300).contains(&status) {
+                issues.push(format!("HTTP {}", status));
+            }
+        }
+        if let (Some(ct), Some(fmt)) = (&self.content_type, &self.detected_format) {
+            if !crate::probe::content_type_matches(ct, fmt) {
+                issues.push(format!("content-type '{}' but bytes look like {}", ct, fmt));
+            }
+        }
+        if self.size_bytes == Some(0) {
+            issues.push("empty body".to_string());
+        }
+        issues
+    }
+}
+
+pub struct AuditEntry {
+    pub mint: String,
+    pub name: Option<String>,
+    pub error: Option<String>,
+    pub assets: Vec<AssetHealth>,
+}
+
+pub struct AuditReport {
+    pub entries: Vec<AuditEntry>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -89,42 +179,830 @@ pub struct HeliusAssetsResponse {
 #[derive(Deserialize, Debug)]
 pub struct HeliusAssetsList {
     pub items: Vec<HeliusAsset>,
-    pub total: u32,
 }
 
 #[derive(Deserialize, Debug)]
 pub struct HeliusAsset {
-    #[allow(dead_code)]
     pub id: String,
+    // "V1_NFT", "ProgrammableNFT", "MplCoreAsset", "FungibleToken", ...
+    pub interface: Option<String>,
     pub content: Option<HeliusContent>,
     pub grouping: Option<Vec<HeliusGrouping>>,
+    pub compression: Option<HeliusCompression>,
+    pub token_info: Option<HeliusTokenInfo>,
+    #[serde(default)]
+    pub creators: Vec<HeliusCreator>,
+    #[serde(default)]
+    pub burnt: bool,
+    pub ownership: Option<HeliusOwnership>,
+    pub mutable: Option<bool>,
+    pub royalty: Option<HeliusRoyalty>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct HeliusRoyalty {
+    #[serde(default)]
+    pub primary_sale_happened: bool,
+}
+
+impl HeliusAsset {
+    pub fn name(&self) -> Option<&str> {
+        self.content.as_ref()?.metadata.as_ref()?.name.as_deref()
+    }
+
+    pub fn collection(&self) -> Option<&HeliusGrouping> {
+        self.grouping.as_ref()?.iter().find(|g| g.group_key == "collection")
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        self.compression.as_ref().map(|c| c.compressed).unwrap_or(false)
+    }
+
+    pub fn owner(&self) -> Option<&str> {
+        self.ownership.as_ref().map(|o| o.owner.as_str())
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct HeliusOwnership {
+    pub owner: String,
+    #[serde(default)]
+    pub delegate: Option<String>,
+    #[serde(default)]
+    pub delegated: bool,
+    #[serde(default)]
+    pub frozen: bool,
 }
 
 #[derive(Deserialize, Debug)]
 pub struct HeliusContent {
     pub metadata: Option<HeliusMetadata>,
+    pub links: Option<HeliusLinks>,
 }
 
 #[derive(Deserialize, Debug)]
 pub struct HeliusMetadata {
     pub name: Option<String>,
-    #[allow(dead_code)]
     pub symbol: Option<String>,
+    pub description: Option<String>,
+    // Values come through as the JSON had them: strings, numbers, sometimes bools
+    pub attributes: Option<Vec<serde_json::Value>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct HeliusLinks {
+    pub external_url: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct HeliusCompression {
+    #[serde(default)]
+    pub compressed: bool,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct HeliusTokenInfo {
+    pub symbol: Option<String>,
+    pub decimals: Option<u8>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct HeliusCreator {
+    pub address: String,
+    #[serde(default)]
+    pub verified: bool,
 }
 
 #[derive(Deserialize, Debug)]
 pub struct HeliusGrouping {
     pub group_key: String,
     pub group_value: String,
+    // Unverified groupings only appear with displayOptions.showUnverifiedCollections
+    pub verified: Option<bool>,
+    // Only present when the request sets displayOptions.showCollectionMetadata
+    pub collection_metadata: Option<HeliusCollectionMetadata>,
 }
 
+#[derive(Deserialize, Debug, Clone)]
+pub struct HeliusCollectionMetadata {
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub image: Option<String>,
+}
+
+#[derive(Serialize)]
 pub struct DossierReport {
+    // The first of `wallets` when several were given
     pub wallet_address: String,
+    // Per-wallet attribution; only filled for a multi-wallet dossier
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub wallets: Vec<WalletShare>,
+    // NFTs of every kind (regular, compressed, Core), spam excluded
     pub total_nfts: u32,
     pub collections: Vec<CollectionSummary>,
+    pub breakdown: AssetBreakdown,
+    pub spam: Vec<SpamAsset>,
+    pub balances: Option<WalletBalances>,
+    pub activity: Option<ActivityProfile>,
+    // The --max-assets cap was hit, so the wallet holds more than we looked at
+    pub partial: bool,
+    // Set by the assessment rules (see rules.rs)
+    pub assessment: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub flags: Vec<RiskFlag>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetClass {
+    Nft,
+    CompressedNft,
+    CoreAsset,
+    Fungible,
+    Other,
 }
 
+// Counts per asset class. Spam is counted on its own and not in the other buckets.
+#[derive(Serialize, Default)]
+pub struct AssetBreakdown {
+    pub nfts: u32,
+    pub compressed_nfts: u32,
+    pub core_assets: u32,
+    pub fungibles: u32,
+    pub other: u32,
+    pub burnt: u32,
+    pub spam: u32,
+}
+
+#[derive(Serialize)]
+pub struct SpamAsset {
+    pub id: String,
+    pub name: String,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Serialize, Clone)]
 pub struct CollectionSummary {
     pub name: String,
+    // Collection mint; None for assets without a collection grouping
+    pub address: Option<String>,
+    pub symbol: Option<String>,
+    pub image: Option<String>,
+    pub count: u32,
+    // Owner -> count, left out of JSON unless the holdings span several wallets
+    #[serde(skip_serializing_if = "single_owner")]
+    pub by_wallet: BTreeMap<String, u32>,
+}
+
+fn single_owner(by_wallet: &BTreeMap<String, u32>) -> bool {
+    by_wallet.len() <= 1
+}
+
+#[derive(Serialize, Default)]
+pub struct WalletShare {
+    pub address: String,
+    pub nfts: u32,
+    pub sol_lamports: Option<u64>,
+    // Assets already listed under an earlier wallet in the set
+    pub duplicates: u32,
+    // Assets of this wallet delegated to another wallet in the set
+    pub delegated_within: u32,
+    // This wallet's listing hit --max-assets
+    pub partial: bool,
+}
+
+pub const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+#[derive(Serialize, Default)]
+pub struct WalletBalances {
+    pub sol_lamports: u64,
+    pub tokens: Vec<TokenBalance>,
+    pub stakes: Vec<StakeAccount>,
+    // Zero-balance token accounts whose rent can be reclaimed by closing them
+    pub empty_accounts: Vec<EmptyTokenAccount>,
+    // Parts that could not be fetched; the rest of the balances are still valid
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct TokenBalance {
+    pub mint: String,
+    pub symbol: Option<String>,
+    pub program: String,
+    pub raw_amount: String,
+    pub decimals: u8,
+    pub ui_amount: f64,
+}
+
+#[derive(Serialize)]
+pub struct StakeAccount {
+    pub address: String,
+    pub lamports: u64,
+    pub delegated_lamports: Option<u64>,
+    pub voter: Option<String>,
+    pub state: String,
+}
+
+#[derive(Serialize)]
+pub struct EmptyTokenAccount {
+    pub address: String,
+    pub mint: String,
+    pub program: String,
+    pub lamports: u64,
+}
+
+// One row of getSignaturesForAddress
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureInfo {
+    pub signature: String,
+    pub block_time: Option<i64>,
+    #[serde(default)]
+    pub err: Option<serde_json::Value>,
+}
+
+// Helius enhanced transaction (v0/addresses/{address}/transactions), only the fields we use
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EnhancedTransaction {
+    pub signature: String,
+    #[serde(default)]
+    pub timestamp: i64,
+    #[serde(rename = "type", default)]
+    pub tx_type: String,
+    #[serde(default)]
+    pub fee_payer: String,
+    #[serde(default)]
+    pub instructions: Vec<EnhancedInstruction>,
+    #[serde(default)]
+    pub native_transfers: Vec<EnhancedNativeTransfer>,
+    #[serde(default)]
+    pub token_transfers: Vec<EnhancedTokenTransfer>,
+    #[serde(default)]
+    pub events: EnhancedEvents,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EnhancedInstruction {
+    pub program_id: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EnhancedNativeTransfer {
+    #[serde(default)]
+    pub from_user_account: Option<String>,
+    #[serde(default)]
+    pub to_user_account: Option<String>,
+    // Lamports
+    #[serde(default)]
+    pub amount: u64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EnhancedTokenTransfer {
+    #[serde(default)]
+    pub from_user_account: Option<String>,
+    #[serde(default)]
+    pub to_user_account: Option<String>,
+    pub mint: String,
+    #[serde(default)]
+    pub token_amount: f64,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct EnhancedEvents {
+    #[serde(default)]
+    pub nft: Option<EnhancedNftEvent>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct EnhancedNftEvent {
+    #[serde(rename = "type", default)]
+    pub event_type: String,
+    #[serde(default)]
+    pub buyer: Option<String>,
+    #[serde(default)]
+    pub seller: Option<String>,
+    #[serde(default)]
+    pub nfts: Vec<EnhancedNft>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct EnhancedNft {
+    pub mint: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Behaviour {
+    Flipper,
+    LongTermHolder,
+    MinterBot,
+    Dormant,
+    Collector,
+    // No history to judge from
+    Unknown,
+}
+
+impl Behaviour {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Behaviour::Flipper => "Flipper",
+            Behaviour::LongTermHolder => "Long-term holder",
+            Behaviour::MinterBot => "Minter / bot",
+            Behaviour::Dormant => "Dormant",
+            Behaviour::Collector => "Active collector",
+            Behaviour::Unknown => "Unknown",
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ActivityProfile {
+    // Unix timestamps; first_seen is only the oldest transaction we looked at when `truncated`
+    pub first_seen: Option<i64>,
+    pub last_active: Option<i64>,
+    pub tx_count: u32,
+    pub failed_tx_count: u32,
+    // Signature history was cut off by --max-txs
+    pub truncated: bool,
+    // "YYYY-MM" -> transactions, oldest first
+    pub monthly: Vec<MonthlyCount>,
+    pub programs: Vec<ProgramUsage>,
+    pub trades: TradeCounts,
+    pub avg_holding_days: Option<f64>,
+    pub behaviour: Behaviour,
+    pub evidence: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct MonthlyCount {
+    pub month: String,
+    pub count: u32,
+}
+
+#[derive(Serialize)]
+pub struct ProgramUsage {
+    pub program: String,
+    pub name: Option<String>,
+    pub count: u32,
+}
+
+// NFT activity seen in the enhanced transaction history
+#[derive(Serialize, Default)]
+pub struct TradeCounts {
+    pub mints: u32,
+    pub buys: u32,
+    pub sells: u32,
+    pub transfers_in: u32,
+    pub transfers_out: u32,
+    // Transactions the trade breakdown is based on
+    pub analysed: u32,
+}
+
+// One step back along the money: `funder` sent `wallet` its first SOL.
+#[derive(Serialize)]
+pub struct FundingHop {
+    pub wallet: String,
+    pub funder: String,
+    pub lamports: u64,
+    pub signature: String,
+    pub timestamp: Option<i64>,
+    // The funder is an exchange or service; the chain ends here
+    pub service: bool,
+}
+
+#[derive(Serialize)]
+pub struct TraceReport {
+    pub wallet: String,
+    pub hops: Vec<FundingHop>,
+    // Last funder reached, if any
+    pub source: Option<String>,
+    // Why the trace ended where it did
+    pub stopped: String,
+}
+
+#[derive(Serialize)]
+pub struct ClusterReport {
+    pub wallets: Vec<String>,
+    pub clusters: Vec<WalletCluster>,
+    // Wallets that share nothing with the rest of the list
+    pub unclustered: Vec<String>,
+    pub traces: Vec<TraceReport>,
+    pub graph: LinkGraph,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct WalletCluster {
+    pub members: Vec<String>,
+    // What ties the members together
+    pub links: Vec<String>,
+}
+
+#[derive(Serialize, Default)]
+pub struct LinkGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+#[derive(Serialize)]
+pub struct GraphNode {
+    pub id: String,
+    // "wallet", "funder" or "authority"
+    pub kind: String,
+}
+
+#[derive(Serialize)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    // "funded", "signed" or "transfer"
+    pub kind: String,
+    pub lamports: Option<u64>,
+}
+
+// SOL (mint None) or a token moving from one wallet to another.
+#[derive(Serialize, Clone)]
+pub struct Movement {
+    pub signature: String,
+    pub timestamp: Option<i64>,
+    pub from: String,
+    pub to: String,
+    pub mint: Option<String>,
+    // SOL for native transfers, UI units for tokens
+    pub amount: f64,
+}
+
+#[derive(Serialize)]
+pub struct CompareReport {
+    pub wallet_a: String,
+    pub wallet_b: String,
+    pub nfts_a: u32,
+    pub nfts_b: u32,
+    pub collections_a: u32,
+    pub collections_b: u32,
+    pub shared_collections: Vec<SharedCollection>,
+    // NFTs, tokens and SOL sent directly between the two
+    pub transfers: Vec<Movement>,
+    pub common_counterparties: Vec<CommonCounterparty>,
+    // 0-100
+    pub similarity: u32,
+    pub factors: Vec<String>,
+    // Either asset listing hit --max-assets
+    pub partial: bool,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct SharedCollection {
+    pub name: String,
+    pub address: Option<String>,
+    pub count_a: u32,
+    pub count_b: u32,
+}
+
+#[derive(Serialize)]
+pub struct CommonCounterparty {
+    pub address: String,
+    pub name: Option<String>,
+    // Transfers seen with each wallet
+    pub with_a: u32,
+    pub with_b: u32,
+}
+
+#[derive(Serialize)]
+pub struct CollectionReport {
+    pub collection: String,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    // "das" or "getProgramAccounts"
+    pub source: String,
+    // Members that still exist
+    pub supply: u32,
+    pub burned: u32,
+    pub unique_holders: u32,
+    // Fraction of the supply held by the ten biggest holders
+    pub top10_share: f64,
+    // 0 = every holder has the same number, 1 = one holder has everything
+    pub gini: f64,
+    // Held by a program-owned (escrow) account, or frozen under a delegate
+    pub escrowed: u32,
+    pub distribution: Vec<HolderBucket>,
+    pub top_holders: Vec<HolderShare>,
+    // The --max-assets cap was hit
+    pub partial: bool,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct HolderBucket {
+    pub label: String,
+    pub holders: u32,
+    pub nfts: u32,
+}
+
+#[derive(Serialize)]
+pub struct HolderShare {
+    pub address: String,
+    pub count: u32,
+    pub share: f64,
+}
+
+#[derive(Serialize)]
+pub struct SnapshotReport {
+    // "collection", "creator" or "mints"
+    pub kind: String,
+    pub target: String,
+    // Slots at the start and end of the run; owners were read somewhere in between
+    pub slot: u64,
+    pub end_slot: u64,
+    pub source: String,
+    pub total_mints: u32,
+    pub burned: u32,
+    // Mints left out because their owner is program-owned (--exclude-escrow)
+    pub excluded: u32,
+    pub holders: Vec<SnapshotHolder>,
+    pub partial: bool,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct SnapshotHolder {
+    pub owner: String,
+    pub count: u32,
+    pub mints: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct HashlistDiff {
+    pub previous: u32,
+    pub current: u32,
+    // In the current list but not the saved one
+    pub added: Vec<String>,
+    // In the saved list but gone now (burned, or no longer verified)
+    pub removed: Vec<String>,
+}
+
+// Trait frequencies for one collection, cached on disk between runs.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TraitTable {
+    pub collection: String,
+    pub built_at: i64,
+    pub max_assets: u32,
+    pub partial: bool,
+    pub source: String,
+    // trait type -> value -> members carrying it; a member without the trait counts as "None"
+    pub traits: BTreeMap<String, BTreeMap<String, u32>>,
+    // mint -> its traits
+    pub items: BTreeMap<String, TraitItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TraitItem {
+    pub name: Option<String>,
+    pub traits: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TraitRarity {
+    pub trait_type: String,
+    pub value: String,
     pub count: u32,
-}
\ No newline at end of file
+    pub frequency: f64,
+}
+
+// Where one NFT sits within its collection.
+#[derive(Serialize, Debug)]
+pub struct ItemRarity {
+    pub collection: String,
+    pub method: RarityMethod,
+    pub score: f64,
+    // None when the NFT is not in the (possibly partial) trait table
+    pub rank: Option<u32>,
+    pub members: u32,
+    pub partial: bool,
+    pub traits: Vec<TraitRarity>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RarityReport {
+    pub collection: String,
+    pub name: Option<String>,
+    pub method: RarityMethod,
+    pub members: u32,
+    pub built_at: i64,
+    pub cached: bool,
+    pub source: String,
+    pub partial: bool,
+    pub traits: Vec<TraitSummary>,
+    pub ranking: Vec<RankedItem>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TraitSummary {
+    pub trait_type: String,
+    pub values: u32,
+    // Least common value of this trait type and how many carry it
+    pub rarest: String,
+    pub rarest_count: u32,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RankedItem {
+    pub rank: u32,
+    pub mint: String,
+    pub name: Option<String>,
+    pub score: f64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SearchReport {
+    pub collection: String,
+    pub filters: Vec<String>,
+    // Live members the metadata filters were run against
+    pub scanned: u32,
+    pub matches: Vec<SearchMatch>,
+    pub partial: bool,
+    pub source: String,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SearchMatch {
+    pub mint: String,
+    pub name: String,
+    pub holder: Option<String>,
+    // None when the listing didn't say and no filter needed it
+    pub mutable: Option<bool>,
+    pub primary_sale_happened: Option<bool>,
+    // Only filled in when traits were searched on
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<(String, String)>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AuthorityReport {
+    pub authority: String,
+    pub total: u32,
+    // Matches per filter; an item can match both
+    pub by_authority: u32,
+    pub by_creator: u32,
+    pub mutable: u32,
+    pub primary_sold: u32,
+    pub collections: Vec<AuthorityCollection>,
+    pub royalties: Vec<RoyaltyShare>,
+    pub recent_updates: Vec<MetadataUpdate>,
+    pub partial: bool,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AuthorityCollection {
+    // Verified collection key; None groups items by symbol instead
+    pub collection: Option<String>,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub count: u32,
+    pub mutable: u32,
+    // Items the key can still update (the rest only list it as first creator)
+    pub via_authority: u32,
+    pub min_royalty_bps: u16,
+    pub max_royalty_bps: u16,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RoyaltyShare {
+    pub basis_points: u16,
+    pub count: u32,
+}
+
+#[derive(Serialize, Debug)]
+pub struct MetadataUpdate {
+    pub mint: String,
+    pub name: String,
+    pub metadata_account: String,
+    pub signature: String,
+    pub timestamp: Option<i64>,
+    pub recent: bool,
+}
+
+// Everything the risk rules look at, gathered once per NFT.
+#[derive(Serialize, Debug, Clone)]
+pub struct RiskSignals {
+    pub mutable: bool,
+    pub creators: u32,
+    pub verified_creators: u32,
+    pub has_collection: bool,
+    pub collection_verified: bool,
+    pub royalty_bps: u16,
+    pub token_program: Option<String>,
+    // Only set when held by something other than the master edition
+    pub mint_authority: Option<String>,
+    pub freeze_authority: Option<String>,
+    pub permanent_delegate: Option<String>,
+    pub image_host: Option<String>,
+    pub centralized_image: bool,
+    pub metadata_changed_at: Option<i64>,
+    // Other assets the update authority controls, counted up to a small cap (DAS only)
+    pub authority_assets: Option<u32>,
+    pub checked_at: i64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RiskFlag {
+    pub rule: String,
+    pub weight: u32,
+    pub detail: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RiskReport {
+    // 0-100, sum of the triggered weights
+    pub score: u32,
+    pub level: String,
+    pub flags: Vec<RiskFlag>,
+    pub signals: RiskSignals,
+}
+
+// `rules test` result for one saved report.
+#[derive(Serialize, Debug)]
+pub struct RuleTestReport {
+    pub file: String,
+    pub kind: Option<String>,
+    pub results: Vec<RuleResult>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RuleResult {
+    pub rule: String,
+    pub builtin: bool,
+    pub matched: bool,
+    pub message: Option<String>,
+    pub error: Option<String>,
+}
+
+// One decoded field of an account; offset is where it starts in the data when we
+// read it ourselves.
+#[derive(Serialize, Debug)]
+pub struct AccountField {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+    pub value: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct InspectReport {
+    pub address: String,
+    pub lamports: u64,
+    pub owner: String,
+    pub owner_name: Option<String>,
+    pub executable: bool,
+    pub data_len: usize,
+    // What the data was decoded as; None leaves only the hex dump
+    pub kind: Option<String>,
+    pub fields: Vec<AccountField>,
+    pub hex: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DerivedAddress {
+    pub name: String,
+    pub address: String,
+    pub bump: u8,
+    pub seeds: String,
+    // Only filled in with --check
+    pub exists: Option<bool>,
+    pub owner: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PdaReport {
+    pub mint: String,
+    pub derivations: Vec<DerivedAddress>,
+}
+
+// Field-by-field walk of raw account data, for `decode --diagnose`.
+#[derive(Serialize, Debug)]
+pub struct DecodeDiagnosis {
+    pub data_len: usize,
+    // Whether the regular Borsh decoding succeeds
+    pub decoded: bool,
+    pub fields: Vec<AccountField>,
+    pub error: Option<String>,
+    // Offset reached when the walk ended
+    pub stopped_at: usize,
+    // Bytes left after the last field (padding on real accounts)
+    pub trailing: Option<usize>,
+    // Hex dump around the failure
+    pub context: Option<String>,
+}
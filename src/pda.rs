@@ -0,0 +1,130 @@
+use crate::programs::{ASSOCIATED_TOKEN_PROGRAM, BUBBLEGUM_PROGRAM, TOKEN_METADATA_PROGRAM};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+// Editions covered by one edition marker account.
+pub const EDITIONS_PER_MARKER: u64 = 248;
+
+pub fn program_id(id: &str) -> Pubkey {
+    Pubkey::from_str(id).expect("valid program id")
+}
+
+pub fn metadata_program_id() -> Pubkey {
+    program_id(TOKEN_METADATA_PROGRAM)
+}
+
+// A derived address, with the seeds spelled out for display.
+pub struct Derived {
+    pub address: Pubkey,
+    pub bump: u8,
+    pub seeds: String,
+}
+
+// Token Metadata PDAs all start with "metadata", the program id and the mint.
+fn metadata_pda(mint: &Pubkey, extra: &[&[u8]], described: &[&str]) -> Derived {
+    let program = metadata_program_id();
+    let mut seeds: Vec<&[u8]> = vec![b"metadata", program.as_ref(), mint.as_ref()];
+    seeds.extend_from_slice(extra);
+    let (address, bump) = Pubkey::find_program_address(&seeds, &program);
+    let mut parts = vec!["\"metadata\"", "program", "mint"];
+    parts.extend_from_slice(described);
+    Derived { address, bump, seeds: parts.join(", ") }
+}
+
+pub fn metadata(mint: &Pubkey) -> Derived {
+    metadata_pda(mint, &[], &[])
+}
+
+// Master edition for originals, edition for prints: same address either way.
+pub fn edition(mint: &Pubkey) -> Derived {
+    metadata_pda(mint, &[b"edition"], &["\"edition\""])
+}
+
+// The marker recording which prints of a master edition exist, 248 per account.
+pub fn edition_marker(master_mint: &Pubkey, edition_number: u64) -> Derived {
+    let page = (edition_number / EDITIONS_PER_MARKER).to_string();
+    metadata_pda(master_mint, &[b"edition", page.as_bytes()], &["\"edition\"", &format!("\"{}\"", page)])
+}
+
+// Programmable NFTs keep lock and delegate state per token account.
+pub fn token_record(mint: &Pubkey, token_account: &Pubkey) -> Derived {
+    metadata_pda(mint, &[b"token_record", token_account.as_ref()], &["\"token_record\"", "token account"])
+}
+
+pub fn collection_authority_record(mint: &Pubkey, authority: &Pubkey) -> Derived {
+    metadata_pda(mint, &[b"collection_authority", authority.as_ref()], &["\"collection_authority\"", "authority"])
+}
+
+pub fn use_authority_record(mint: &Pubkey, user: &Pubkey) -> Derived {
+    metadata_pda(mint, &[b"user", user.as_ref()], &["\"user\"", "user"])
+}
+
+pub fn associated_token_account(wallet: &Pubkey, mint: &Pubkey, token_program: &str) -> Derived {
+    let token_program = program_id(token_program);
+    let (address, bump) = Pubkey::find_program_address(
+        &[wallet.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &program_id(ASSOCIATED_TOKEN_PROGRAM),
+    );
+    Derived { address, bump, seeds: "wallet, token program, mint".to_string() }
+}
+
+pub fn tree_config(merkle_tree: &Pubkey) -> Derived {
+    let (address, bump) = Pubkey::find_program_address(&[merkle_tree.as_ref()], &program_id(BUBBLEGUM_PROGRAM));
+    Derived { address, bump, seeds: "merkle tree".to_string() }
+}
+
+// Shorthands for the two addresses most commands need.
+pub fn metadata_address(mint: &Pubkey) -> Pubkey {
+    metadata(mint).address
+}
+
+pub fn edition_address(mint: &Pubkey) -> Pubkey {
+    edition(mint).address
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::programs::{TOKEN_2022_PROGRAM, TOKEN_PROGRAM};
+
+    // USDC and a well-known holder; the Metaplex addresses match mpl-token-metadata's find_pda
+    const MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    const OWNER: &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+
+    fn key(address: &str) -> Pubkey {
+        Pubkey::from_str(address).unwrap()
+    }
+
+    #[test]
+    fn metadata_and_edition() {
+        let metadata = metadata(&key(MINT));
+        assert_eq!((metadata.address.to_string().as_str(), metadata.bump), ("5x38Kp4hvdomTCnCrAny4UtMUt5rQBdB6px2K1Ui45Wq", 255));
+        assert_eq!(metadata.seeds, "\"metadata\", program, mint");
+
+        let edition = edition(&key(MINT));
+        assert_eq!((edition.address.to_string().as_str(), edition.bump), ("A7FGB2kzjpDPRLMeqRLgW9XZ3JQ2RYRL4w5kUZv64ZB", 252));
+        assert_eq!(metadata_address(&key(MINT)), metadata.address);
+        assert_eq!(edition_address(&key(MINT)), edition.address);
+    }
+
+    #[test]
+    fn edition_marker_pages() {
+        // Editions 0-247 share the first marker, 248 starts the second
+        let last_on_first = edition_marker(&key(MINT), 247);
+        assert_eq!(last_on_first.address.to_string(), "D6qU7Y8AJHZuQqZLvKW75ZkSjHatVpDUVrrHxMbGeKoC");
+        assert!(last_on_first.seeds.ends_with("\"edition\", \"0\""));
+        assert_eq!(edition_marker(&key(MINT), 0).address, last_on_first.address);
+
+        let first_on_second = edition_marker(&key(MINT), 248);
+        assert_eq!(first_on_second.address.to_string(), "6JQi7YLxAZ42FAx1KBJQappuMDruVVMFRxfiMbZnczZm");
+        assert!(first_on_second.seeds.ends_with("\"edition\", \"1\""));
+    }
+
+    #[test]
+    fn associated_token_accounts() {
+        let ata = associated_token_account(&key(OWNER), &key(MINT), TOKEN_PROGRAM);
+        assert_eq!((ata.address.to_string().as_str(), ata.bump), ("FGETo8T8wMcN2wCjav8VK6eh3dLk63evNDPxzLSJra8B", 254));
+        // The token program is a seed, so Token-2022 gets its own account
+        assert_ne!(associated_token_account(&key(OWNER), &key(MINT), TOKEN_2022_PROGRAM).address, ata.address);
+    }
+}
@@ -0,0 +1,260 @@
+use crate::model::AssetHealth;
+use image::io::Reader as ImageReader;
+use reqwest::{header, Client, Url};
+use std::io::Cursor;
+use std::time::Instant;
+
+const MAX_REDIRECTS: usize = 10;
+// Largest body we'll download; NFT media past this is reported rather than fetched.
+const MAX_BODY_BYTES: u64 = 64 * 1024 * 1024;
+
+// Turn the URI schemes NFTs commonly use into something we can GET.
+// Returns None when the URI is empty or not fetchable over HTTP(S).
+pub fn resolve_uri(raw: &str) -> Option<String> {
+    let uri = raw.trim_end_matches('\0').trim();
+    if uri.is_empty() {
+        return None;
+    }
+
+    let resolved = if let Some(rest) = uri.strip_prefix("ipfs://") {
+        format!("https://ipfs.io/ipfs/{}", rest.trim_start_matches("ipfs/"))
+    } else if let Some(rest) = uri.strip_prefix("ar://") {
+        format!("https://arweave.net/{}", rest)
+    } else if uri.starts_with('/') {
+        // Relative arweave path
+        format!("https://arweave.net{}", uri)
+    } else {
+        uri.to_string()
+    };
+
+    if resolved.starts_with("http://") || resolved.starts_with("https://") {
+        Some(resolved)
+    } else {
+        None
+    }
+}
+
+// Fetch a URL, following redirects by hand, and record everything we learn on the way.
+// The body is handed back so callers don't have to download the asset twice.
+pub async fn fetch(client: &Client, label: &str, url: &str) -> (AssetHealth, Option<Vec<u8>>) {
+    let mut health = AssetHealth {
+        label: label.to_string(),
+        url: url.to_string(),
+        status: None,
+        redirects: Vec::new(),
+        content_type: None,
+        detected_format: None,
+        size_bytes: None,
+        dimensions: None,
+        latency_ms: 0,
+        error: None,
+    };
+
+    let started = Instant::now();
+    let mut current = match Url::parse(url) {
+        Ok(u) => u,
+        Err(e) => {
+            health.error = Some(format!("invalid URL: {}", e));
+            return (health, None);
+        }
+    };
+
+    let mut response = loop {
+        let resp = match client.get(current.clone()).send().await {
+            Ok(r) => r,
+            Err(e) => {
+                health.latency_ms = started.elapsed().as_millis();
+                health.error = Some(describe_error(&e));
+                return (health, None);
+            }
+        };
+
+        if !resp.status().is_redirection() {
+            break resp;
+        }
+
+        let next = resp
+            .headers()
+            .get(header::LOCATION)
+            .and_then(|l| l.to_str().ok())
+            .and_then(|l| current.join(l).ok());
+
+        match next {
+            Some(next) if health.redirects.len() < MAX_REDIRECTS => {
+                health.redirects.push(next.to_string());
+                current = next;
+            }
+            Some(_) => {
+                health.status = Some(resp.status().as_u16());
+                health.latency_ms = started.elapsed().as_millis();
+                health.error = Some(format!("more than {} redirects", MAX_REDIRECTS));
+                return (health, None);
+            }
+            None => break resp,
+        }
+    };
+
+    health.status = Some(response.status().as_u16());
+    health.content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+
+    // Refuse up front when the server says it's too big, and stop reading when it lied
+    if let Some(len) = response.content_length().filter(|len| *len > MAX_BODY_BYTES) {
+        health.latency_ms = started.elapsed().as_millis();
+        health.size_bytes = Some(len);
+        health.error = Some(too_large());
+        return (health, None);
+    }
+    let mut body = Vec::new();
+    loop {
+        match response.chunk().await {
+            Ok(Some(chunk)) => {
+                if (body.len() + chunk.len()) as u64 > MAX_BODY_BYTES {
+                    health.latency_ms = started.elapsed().as_millis();
+                    health.error = Some(too_large());
+                    return (health, None);
+                }
+                body.extend_from_slice(&chunk);
+            }
+            Ok(None) => break,
+            Err(e) => {
+                health.latency_ms = started.elapsed().as_millis();
+                health.error = Some(describe_error(&e));
+                return (health, None);
+            }
+        }
+    }
+    health.latency_ms = started.elapsed().as_millis();
+    health.size_bytes = Some(body.len() as u64);
+    health.detected_format = sniff_format(&body).map(|f| f.to_string());
+
+    if is_raster(health.detected_format.as_deref()) {
+        health.dimensions = ImageReader::new(Cursor::new(&body))
+            .with_guessed_format()
+            .ok()
+            .and_then(|r| r.into_dimensions().ok());
+    }
+
+    (health, Some(body))
+}
+
+fn too_large() -> String {
+    format!("too large (over {} MiB)", MAX_BODY_BYTES / (1024 * 1024))
+}
+
+fn describe_error(e: &reqwest::Error) -> String {
+    if e.is_timeout() {
+        "timed out".to_string()
+    } else if e.is_connect() {
+        "connection failed".to_string()
+    } else {
+        e.to_string()
+    }
+}
+
+// Identify a payload by its magic bytes rather than trusting the server's content-type.
+pub fn sniff_format(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some("png");
+    }
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return Some("jpeg");
+    }
+    if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        return Some("gif");
+    }
+    if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        return Some("webp");
+    }
+    if bytes.starts_with(b"BM") && bytes.len() > 14 {
+        return Some("bmp");
+    }
+    if bytes.starts_with(b"glTF") {
+        return Some("glb");
+    }
+    if bytes.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        return Some("webm");
+    }
+    if bytes.len() >= 12 && &bytes[4..8] == b"ftyp" {
+        return match &bytes[8..12] {
+            b"avif" | b"avis" => Some("avif"),
+            b"qt  " => Some("mov"),
+            _ => Some("mp4"),
+        };
+    }
+    if bytes.starts_with(b"ID3") || bytes.starts_with(&[0xFF, 0xFB]) {
+        return Some("mp3");
+    }
+
+    // Text formats: look past leading whitespace and a UTF-8 BOM
+    let head: Vec<u8> = bytes
+        .iter()
+        .skip_while(|b| b.is_ascii_whitespace() || **b == 0xEF || **b == 0xBB || **b == 0xBF)
+        .take(512)
+        .map(|b| b.to_ascii_lowercase())
+        .collect();
+    let head = String::from_utf8_lossy(&head);
+    if head.starts_with('{') || head.starts_with('[') {
+        return Some("json");
+    }
+    if head.starts_with("<svg") || (head.starts_with("<?xml") && head.contains("<svg")) {
+        return Some("svg");
+    }
+    if head.starts_with("<!doctype html") || head.starts_with("<html") {
+        return Some("html");
+    }
+    None
+}
+
+fn is_raster(format: Option<&str>) -> bool {
+    matches!(format, Some("png" | "jpeg" | "gif" | "webp" | "bmp"))
+}
+
+// Broad media kind of a sniffed format, used to describe animation_url content.
+pub fn media_kind(format: &str) -> &'static str {
+    match format {
+        "png" | "jpeg" | "gif" | "webp" | "bmp" | "avif" | "svg" => "image",
+        "mp4" | "mov" | "webm" => "video",
+        "mp3" => "audio",
+        "glb" => "3d model",
+        "html" => "html",
+        "json" => "json",
+        _ => "unknown",
+    }
+}
+
+// Does the server-declared content-type agree with what the bytes actually are?
+pub fn content_type_matches(content_type: &str, format: &str) -> bool {
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+
+    // Generic types tell us nothing, so they can't be wrong
+    if essence == "application/octet-stream" || essence == "binary/octet-stream" || essence.is_empty() {
+        return true;
+    }
+
+    match format {
+        "png" => essence == "image/png",
+        "jpeg" => essence == "image/jpeg" || essence == "image/jpg",
+        "gif" => essence == "image/gif",
+        "webp" => essence == "image/webp",
+        "bmp" => essence == "image/bmp",
+        "avif" => essence == "image/avif",
+        "svg" => essence == "image/svg+xml",
+        "json" => essence.contains("json") || essence == "text/plain",
+        "html" => essence == "text/html",
+        "mp4" => essence == "video/mp4",
+        "mov" => essence == "video/quicktime",
+        "webm" => essence == "video/webm",
+        "mp3" => essence == "audio/mpeg",
+        "glb" => essence == "model/gltf-binary",
+        _ => true,
+    }
+}
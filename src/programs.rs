@@ -0,0 +1,42 @@
+// Well-known program ids, as base58 strings.
+
+pub const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+pub const TOKEN_2022_PROGRAM: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+pub const STAKE_PROGRAM: &str = "Stake11111111111111111111111111111111111111";
+pub const SYSTEM_PROGRAM: &str = "11111111111111111111111111111111";
+pub const TOKEN_METADATA_PROGRAM: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
+pub const CANDY_MACHINE_V2_PROGRAM: &str = "cndy3Z4yapfJBmL3ShUp5exZKqR3z33thTzeNMm2gRZ";
+pub const CANDY_MACHINE_V3_PROGRAM: &str = "CndyV3LdqHUfDLmE5naZjVN8rBZz4tqhdefbAnjHG3JR";
+pub const BUBBLEGUM_PROGRAM: &str = "BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY";
+pub const CORE_PROGRAM: &str = "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d";
+pub const TOKEN_AUTH_RULES_PROGRAM: &str = "auth9SigNpDKz4sJJ1DfCTuZrZNSAgh9sFD3rboVmgg";
+pub const ASSOCIATED_TOKEN_PROGRAM: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+
+// Friendly names for programs a wallet commonly talks to.
+const KNOWN: &[(&str, &str)] = &[
+    (SYSTEM_PROGRAM, "System"),
+    (TOKEN_PROGRAM, "SPL Token"),
+    (TOKEN_2022_PROGRAM, "Token-2022"),
+    (STAKE_PROGRAM, "Stake"),
+    (ASSOCIATED_TOKEN_PROGRAM, "Associated Token Account"),
+    ("ComputeBudget111111111111111111111111111111", "Compute Budget"),
+    ("Vote111111111111111111111111111111111111111", "Vote"),
+    ("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr", "Memo"),
+    (TOKEN_METADATA_PROGRAM, "Metaplex Token Metadata"),
+    (BUBBLEGUM_PROGRAM, "Metaplex Bubblegum"),
+    (CORE_PROGRAM, "Metaplex Core"),
+    (TOKEN_AUTH_RULES_PROGRAM, "Metaplex Token Auth Rules"),
+    (CANDY_MACHINE_V2_PROGRAM, "Candy Machine v2"),
+    (CANDY_MACHINE_V3_PROGRAM, "Candy Machine v3"),
+    ("Guard1JwRhJkVH6XZhzoYxeBVQe872VH6QggF4BWmS9g", "Candy Guard"),
+    ("M2mx93ekt1fmXSVkTrUL9xVFHkmME8HTUi5Cyc5aF7K", "Magic Eden v2"),
+    ("TSWAPaqyCSx2KABk68Shruf4rp7CxcNi8hAsbdwmHbN", "Tensor Swap"),
+    ("TCMPhJdwDryooaGtiocG1u3xcYbRpiJzb283XfCZsDp", "Tensor Marketplace"),
+    ("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4", "Jupiter v6"),
+    ("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8", "Raydium AMM"),
+    ("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc", "Orca Whirlpool"),
+];
+
+pub fn name(program_id: &str) -> Option<&'static str> {
+    KNOWN.iter().find(|(id, _)| *id == program_id).map(|(_, name)| *name)
+}
@@ -0,0 +1,315 @@
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use crate::activity;
+use crate::jutsu::{collection, dossier, unmask};
+use crate::model::{RankedItem, TraitItem, TraitRarity, TraitTable};
+use crate::probe::{self, resolve_uri};
+use crate::rpc::{create_probe_client, das_url};
+use crate::style;
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::io::{stderr, IsTerminal, Write};
+use std::path::PathBuf;
+
+// Value recorded for members that don't carry a trait type at all.
+pub const NONE_VALUE: &str = "None";
+// Cached tables older than this are rebuilt.
+const CACHE_TTL_SECS: i64 = 24 * 60 * 60;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RarityMethod {
+    /// Product of the trait frequencies, shown as "1 in N"
+    Statistical,
+    /// Sum of 1 / frequency over every trait (rarity.tools style)
+    RarityScore,
+    /// Sum of -log2(frequency), in bits
+    InformationContent,
+}
+
+impl RarityMethod {
+    pub fn label(self) -> &'static str {
+        match self {
+            RarityMethod::Statistical => "statistical",
+            RarityMethod::RarityScore => "rarity score",
+            RarityMethod::InformationContent => "information content",
+        }
+    }
+
+    pub fn format_score(self, score: f64) -> String {
+        match self {
+            RarityMethod::Statistical => format!("1 in {:.0}", score),
+            RarityMethod::RarityScore => format!("{:.2}", score),
+            RarityMethod::InformationContent => format!("{:.2} bits", score),
+        }
+    }
+}
+
+// The collection's trait table, from the cache when it is fresh and covers at least
+// `max_assets` members. The bool tells whether it came from the cache.
+pub async fn load_table(client: &Client, rpc_url: &str, collection: &str, max_assets: u32, refresh: bool) -> Result<(TraitTable, bool)> {
+    if !refresh {
+        if let Some(table) = read_cache(collection) {
+            let fresh = activity::now() - table.built_at < CACHE_TTL_SECS;
+            if fresh && (!table.partial || table.max_assets >= max_assets) {
+                return Ok((table, true));
+            }
+        }
+    }
+
+    let table = build_table(client, rpc_url, collection, max_assets).await?;
+    // A cache that can't be written only costs a rebuild next time
+    let _ = write_cache(&table);
+    Ok((table, false))
+}
+
+// DAS hands back attributes with the listing; without it every member's off-chain
+// JSON has to be fetched, one mint at a time.
+async fn build_table(client: &Client, rpc_url: &str, collection: &str, max_assets: u32) -> Result<TraitTable> {
+    let params = json!({ "groupKey": "collection", "groupValue": collection });
+    let (members, partial, source) = match dossier::fetch_asset_pages(client, &das_url(), "getAssetsByGroup", params, max_assets).await {
+        Ok((assets, partial)) if !assets.is_empty() => {
+            let members = assets
+                .iter()
+                .filter(|a| !a.burnt)
+                .map(|a| {
+                    let metadata = a.content.as_ref().and_then(|c| c.metadata.as_ref());
+                    let attributes = metadata.and_then(|m| m.attributes.clone()).unwrap_or_default();
+                    (a.id.clone(), a.name().map(str::to_string), attributes)
+                })
+                .collect();
+            (members, partial, "das")
+        }
+        _ => {
+            let mints = collection::metadata_mints(client, rpc_url, collection::collection_filters(collection)).await?;
+            let partial = mints.len() > max_assets as usize;
+            let mints: Vec<String> = mints.into_iter().take(max_assets as usize).collect();
+            (off_chain_attributes(client, rpc_url, &mints).await, partial, "off-chain metadata")
+        }
+    };
+    if members.is_empty() {
+        return Err(anyhow!("No verified members with metadata found for collection {}", collection));
+    }
+
+    let mut items = BTreeMap::new();
+    for (mint, name, attributes) in members {
+        items.insert(mint, TraitItem { name, traits: parse_attributes(&attributes) });
+    }
+
+    Ok(TraitTable {
+        collection: collection.to_string(),
+        built_at: activity::now(),
+        max_assets,
+        partial,
+        source: source.to_string(),
+        traits: count_traits(&mut items),
+        items,
+    })
+}
+
+// Attributes of each mint from its off-chain JSON. Mints whose metadata can't be
+// read are left out rather than counted as trait-less.
+async fn off_chain_attributes(client: &Client, rpc_url: &str, mints: &[String]) -> Vec<(String, Option<String>, Vec<Value>)> {
+    let show_progress = stderr().is_terminal() && !style::current().quiet;
+    let probe_client = create_probe_client();
+    let mut members = Vec::new();
+
+    for (i, mint) in mints.iter().enumerate() {
+        if show_progress && i % 10 == 0 {
+            eprint!("\r  🧬 {}/{} metadata files read", i, mints.len());
+            let _ = stderr().flush();
+        }
+        let Ok(metadata) = unmask::fetch_metadata(client, rpc_url, mint).await else { continue };
+        let Some(uri) = resolve_uri(&metadata.data.uri) else { continue };
+        let (health, body) = probe::fetch(&probe_client, "metadata", &uri).await;
+        let Some(json) = body.filter(|_| health.is_ok()).and_then(|b| serde_json::from_slice::<Value>(&b).ok()) else { continue };
+
+        let attributes = json.get("attributes").and_then(Value::as_array).cloned().unwrap_or_default();
+        let name = metadata.data.name.trim_end_matches('\0').trim().to_string();
+        members.push((mint.clone(), Some(name).filter(|n| !n.is_empty()), attributes));
+    }
+    if show_progress {
+        eprintln!("\r  🧬 {}/{} metadata files read", mints.len(), mints.len());
+    }
+    members
+}
+
+// `[{ "trait_type": ..., "value": ... }]` into trait type -> value, values as text.
+pub fn parse_attributes(attributes: &[Value]) -> BTreeMap<String, String> {
+    let mut traits = BTreeMap::new();
+    for attribute in attributes {
+        let Some(trait_type) = attribute.get("trait_type").and_then(Value::as_str) else { continue };
+        let value = match attribute.get("value") {
+            Some(Value::String(s)) => s.trim().to_string(),
+            Some(Value::Null) | None => String::new(),
+            Some(other) => other.to_string(),
+        };
+        let value = if value.is_empty() { NONE_VALUE.to_string() } else { value };
+        traits.insert(trait_type.trim().to_string(), value);
+    }
+    traits
+}
+
+// Frequency table over every trait type seen anywhere in the collection. Members
+// missing a type get it filled in as "None", since lacking a trait is a trait too.
+fn count_traits(items: &mut BTreeMap<String, TraitItem>) -> BTreeMap<String, BTreeMap<String, u32>> {
+    let trait_types: Vec<String> = {
+        let mut types: Vec<String> = items.values().flat_map(|i| i.traits.keys().cloned()).collect();
+        types.sort();
+        types.dedup();
+        types
+    };
+
+    let mut table: BTreeMap<String, BTreeMap<String, u32>> = BTreeMap::new();
+    for item in items.values_mut() {
+        for trait_type in trait_types.iter() {
+            let value = item.traits.entry(trait_type.clone()).or_insert_with(|| NONE_VALUE.to_string());
+            *table.entry(trait_type.clone()).or_default().entry(value.clone()).or_default() += 1;
+        }
+    }
+    table
+}
+
+// Per-trait frequency for a set of traits, in trait type order.
+pub fn trait_rarities(table: &TraitTable, traits: &BTreeMap<String, String>) -> Vec<TraitRarity> {
+    let members = table.items.len().max(1) as f64;
+    traits
+        .iter()
+        .map(|(trait_type, value)| {
+            let count = table.traits.get(trait_type).and_then(|v| v.get(value)).copied().unwrap_or(0);
+            TraitRarity {
+                trait_type: trait_type.clone(),
+                value: value.clone(),
+                count,
+                // A value missing from the table (partial build) is scored as one of a kind
+                frequency: count.max(1) as f64 / members,
+            }
+        })
+        .collect()
+}
+
+// Higher is rarer for every method. Statistical and information content rank the
+// same way (one is log2 of the other); they differ only in how the number reads.
+pub fn score(traits: &[TraitRarity], method: RarityMethod) -> f64 {
+    let frequencies = traits.iter().map(|t| t.frequency);
+    match method {
+        RarityMethod::Statistical => 1.0 / frequencies.product::<f64>(),
+        RarityMethod::RarityScore => frequencies.map(|p| 1.0 / p).sum(),
+        RarityMethod::InformationContent => frequencies.map(|p| -p.log2()).sum(),
+    }
+}
+
+// Every member scored and ranked; ties share a rank.
+pub fn rank(table: &TraitTable, method: RarityMethod) -> Vec<RankedItem> {
+    let mut scored: Vec<(f64, &String, &TraitItem)> = table
+        .items
+        .iter()
+        .map(|(mint, item)| (score(&trait_rarities(table, &item.traits), method), mint, item))
+        .collect();
+    scored.sort_by(|x, y| y.0.total_cmp(&x.0).then_with(|| x.1.cmp(y.1)));
+
+    let mut ranking: Vec<RankedItem> = Vec::with_capacity(scored.len());
+    for (i, (score, mint, item)) in scored.into_iter().enumerate() {
+        let rank = match ranking.last() {
+            Some(previous) if previous.score == score => previous.rank,
+            _ => i as u32 + 1,
+        };
+        ranking.push(RankedItem { rank, mint: mint.clone(), name: item.name.clone(), score });
+    }
+    ranking
+}
+
+fn cache_path(collection: &str) -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(base.join("shinobi").join("rarity").join(format!("{}.json", collection)))
+}
+
+fn read_cache(collection: &str) -> Option<TraitTable> {
+    let text = std::fs::read_to_string(cache_path(collection)?).ok()?;
+    serde_json::from_str::<TraitTable>(&text).ok().filter(|t| t.collection == collection)
+}
+
+fn write_cache(table: &TraitTable) -> Result<()> {
+    let path = cache_path(&table.collection).ok_or_else(|| anyhow!("no cache directory"))?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_string(table)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(traits: &[(&str, &str)]) -> TraitItem {
+        TraitItem { name: None, traits: traits.iter().map(|(t, v)| (t.to_string(), v.to_string())).collect() }
+    }
+
+    // One gold-and-crown member, one without a hat, and two identical common ones
+    fn table() -> TraitTable {
+        let mut items = BTreeMap::from([
+            ("a".to_string(), item(&[("Background", "Gold"), ("Hat", "Crown")])),
+            ("b".to_string(), item(&[("Background", "Blue")])),
+            ("c".to_string(), item(&[("Background", "Blue"), ("Hat", "Cap")])),
+            ("d".to_string(), item(&[("Background", "Blue"), ("Hat", "Cap")])),
+        ]);
+        let traits = count_traits(&mut items);
+        TraitTable {
+            collection: "test".to_string(),
+            built_at: 0,
+            max_assets: 100,
+            partial: false,
+            source: "test".to_string(),
+            traits,
+            items,
+        }
+    }
+
+    #[test]
+    fn missing_traits_count_as_none() {
+        let table = table();
+        assert_eq!(table.items["b"].traits["Hat"], NONE_VALUE);
+        assert_eq!(table.traits["Hat"][NONE_VALUE], 1);
+        assert_eq!(table.traits["Hat"]["Cap"], 2);
+        assert_eq!(table.traits["Background"]["Blue"], 3);
+
+        let parsed = parse_attributes(&[json!({ "trait_type": " Hat ", "value": "" }), json!({ "trait_type": "Level", "value": 3 })]);
+        assert_eq!(parsed["Hat"], NONE_VALUE);
+        assert_eq!(parsed["Level"], "3");
+    }
+
+    #[test]
+    fn scores_per_method() {
+        let table = table();
+        let rarities = trait_rarities(&table, &table.items["a"].traits);
+        assert_eq!(rarities.iter().map(|t| t.frequency).collect::<Vec<_>>(), [0.25, 0.25]);
+        assert_eq!(score(&rarities, RarityMethod::Statistical), 16.0);
+        assert_eq!(score(&rarities, RarityMethod::RarityScore), 8.0);
+        assert_eq!(score(&rarities, RarityMethod::InformationContent), 4.0);
+    }
+
+    #[test]
+    fn every_method_ranks_rarest_first_and_ties_share_a_rank() {
+        let table = table();
+        for method in [RarityMethod::Statistical, RarityMethod::RarityScore, RarityMethod::InformationContent] {
+            let ranked = rank(&table, method);
+            let ranking: Vec<(u32, &str)> = ranked.iter().map(|r| (r.rank, r.mint.as_str())).collect();
+            assert_eq!(ranking, [(1, "a"), (2, "b"), (3, "c"), (3, "d")], "{:?}", method);
+        }
+    }
+
+    #[test]
+    fn values_missing_from_a_partial_table_count_as_unique() {
+        let table = table();
+        let traits = BTreeMap::from([("Background".to_string(), "Rainbow".to_string())]);
+        let rarities = trait_rarities(&table, &traits);
+        assert_eq!(rarities[0].count, 0);
+        assert_eq!(rarities[0].frequency, 0.25);
+    }
+}
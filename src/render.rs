@@ -0,0 +1,687 @@
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use clap::ValueEnum;
+use colored::*;
+use crate::probe::sniff_format;
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::io::Reader as ImageReader;
+use image::{imageops::FilterType, AnimationDecoder, DynamicImage, Frames, GenericImageView, ImageFormat, ImageOutputFormat, RgbImage};
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+use tokio::time::{sleep, Duration};
+use viuer::Config as ViuerConfig;
+
+// Terminal cells are roughly twice as tall as they are wide.
+const CELL_ASPECT: f32 = 0.5;
+// Pixel size assumed for one terminal cell when a renderer works in pixels (sixel).
+const CELL_PX_W: u32 = 10;
+const CELL_PX_H: u32 = 20;
+
+const RAMP_STANDARD: &str = "@%#*+=-:. ";
+const RAMP_DETAILED: &str = "$@B%8&WM#*oahkbdpqwmZO0QLCJUYXzcvunxrjft/\\|()1{}[]?-_+~<>i!lI;:,\"^`'. ";
+const RAMP_BLOCKS: &str = "█▓▒░ ";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Renderer {
+    /// Coloured ASCII characters
+    Ascii,
+    /// Plain ASCII characters, no colour
+    AsciiMono,
+    /// Unicode half blocks, two pixels per cell
+    HalfBlocks,
+    /// DEC sixel graphics
+    Sixel,
+    /// Kitty graphics protocol
+    Kitty,
+    /// iTerm2 inline images
+    Iterm,
+    /// Let viuer pick the best protocol the terminal supports
+    Auto,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Dither {
+    None,
+    FloydSteinberg,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum AnimationMode {
+    /// Only the first frame
+    First,
+    /// A few evenly spaced frames side by side
+    Strip,
+    /// Play the frames in place, once
+    Play,
+}
+
+#[derive(Clone, Debug)]
+pub struct ImageOptions {
+    pub renderer: Renderer,
+    pub width: u32,
+    pub height: Option<u32>,
+    pub ramp: Vec<char>,
+    pub dither: Dither,
+    pub animation: AnimationMode,
+    pub max_frames: usize,
+}
+
+impl ImageOptions {
+    pub fn new(renderer: Renderer, width: u32, height: Option<u32>, ramp: &str, dither: Dither, animation: AnimationMode, max_frames: usize) -> Result<Self> {
+        Ok(ImageOptions {
+            renderer,
+            width,
+            height,
+            ramp: parse_ramp(ramp)?,
+            dither,
+            animation,
+            max_frames: max_frames.max(1),
+        })
+    }
+}
+
+pub struct Frame {
+    pub image: DynamicImage,
+    pub delay_ms: u32,
+}
+
+pub struct Animation {
+    pub format: &'static str,
+    pub frames: Vec<Frame>,
+    // More frames existed than the frame limit allowed us to decode
+    pub truncated: bool,
+}
+
+impl Animation {
+    pub fn duration_ms(&self) -> u32 {
+        self.frames.iter().map(|f| f.delay_ms).sum()
+    }
+}
+
+// A ramp is either a preset name or a literal dark -> light character sequence.
+pub fn parse_ramp(ramp: &str) -> Result<Vec<char>> {
+    let chars: Vec<char> = match ramp {
+        "standard" => RAMP_STANDARD.chars().collect(),
+        "detailed" => RAMP_DETAILED.chars().collect(),
+        "blocks" => RAMP_BLOCKS.chars().collect(),
+        custom => custom.chars().collect(),
+    };
+    if chars.len() < 2 {
+        return Err(anyhow!("A character ramp needs at least two characters, got '{}'", ramp));
+    }
+    Ok(chars)
+}
+
+pub fn decode(bytes: &[u8]) -> Result<DynamicImage> {
+    ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()?
+        .decode()
+        .map_err(|e| anyhow!("Could not decode image: {}", e))
+}
+
+// Decode every frame of an animated GIF, APNG or WebP, up to `max_frames`.
+// Returns None for still images so callers can fall back to `decode`.
+pub fn decode_animation(bytes: &[u8], max_frames: usize) -> Result<Option<Animation>> {
+    let format = match sniff_format(bytes) {
+        Some(f @ ("gif" | "png" | "webp")) => f,
+        _ => return Ok(None),
+    };
+
+    let frames: Frames = match format {
+        "gif" => GifDecoder::new(Cursor::new(bytes))?.into_frames(),
+        "png" => {
+            let decoder = PngDecoder::new(Cursor::new(bytes))?;
+            if !decoder.is_apng() {
+                return Ok(None);
+            }
+            decoder.apng().into_frames()
+        }
+        _ => {
+            let decoder = WebPDecoder::new(Cursor::new(bytes))?;
+            if !decoder.has_animation() {
+                return Ok(None);
+            }
+            decoder.into_frames()
+        }
+    };
+
+    let mut decoded = Vec::new();
+    let mut truncated = false;
+    for frame in frames {
+        if decoded.len() == max_frames {
+            truncated = true;
+            break;
+        }
+        let frame = frame.map_err(|e| anyhow!("Could not decode animation frame: {}", e))?;
+        let (numer, denom) = frame.delay().numer_denom_ms();
+        decoded.push(Frame {
+            delay_ms: numer / denom.max(1),
+            image: DynamicImage::ImageRgba8(frame.into_buffer()),
+        });
+    }
+
+    if decoded.len() < 2 {
+        return Ok(None);
+    }
+    Ok(Some(Animation { format, frames: decoded, truncated }))
+}
+
+// Fit an image into at most `max_cols` x `max_rows` cells, keeping its aspect ratio.
+// `rows_per_cell` is how many pixel rows one text row carries (2 for half blocks).
+pub fn fit_cells(img_w: u32, img_h: u32, max_cols: u32, max_rows: Option<u32>, rows_per_cell: u32) -> (u32, u32) {
+    if img_w == 0 || img_h == 0 || max_cols == 0 {
+        return (0, 0);
+    }
+    let aspect = img_h as f32 / img_w as f32;
+    let mut cols = max_cols as f32;
+    let mut rows = cols * aspect * CELL_ASPECT;
+
+    if let Some(max_rows) = max_rows {
+        if rows > max_rows as f32 {
+            rows = max_rows as f32;
+            cols = rows / (aspect * CELL_ASPECT);
+        }
+    }
+
+    let cols = cols.round().max(1.0) as u32;
+    let rows = (rows.round().max(1.0) as u32) * rows_per_cell;
+    (cols, rows)
+}
+
+// Render to a string that can be written straight to the terminal.
+// `Auto` has no string form because viuer talks to the terminal itself.
+pub fn render(img: &DynamicImage, opts: &ImageOptions) -> Result<String> {
+    let (w, h) = img.dimensions();
+    match opts.renderer {
+        Renderer::Ascii | Renderer::AsciiMono => {
+            let (cols, rows) = fit_cells(w, h, opts.width, opts.height, 1);
+            Ok(render_ascii(img, cols, rows, &opts.ramp, opts.renderer == Renderer::Ascii, opts.dither))
+        }
+        Renderer::HalfBlocks => {
+            let (cols, rows) = fit_cells(w, h, opts.width, opts.height, 2);
+            Ok(render_half_blocks(img, cols, rows))
+        }
+        Renderer::Sixel => {
+            let (cols, rows) = fit_cells(w, h, opts.width, opts.height, 1);
+            Ok(render_sixel(img, cols * CELL_PX_W, rows * CELL_PX_H, opts.dither))
+        }
+        Renderer::Kitty => {
+            let (cols, rows) = fit_cells(w, h, opts.width, opts.height, 1);
+            render_kitty(img, cols, rows)
+        }
+        Renderer::Iterm => {
+            let (cols, rows) = fit_cells(w, h, opts.width, opts.height, 1);
+            render_iterm(img, cols, rows)
+        }
+        Renderer::Auto => Err(anyhow!("The auto renderer prints directly to the terminal")),
+    }
+}
+
+pub fn print(img: &DynamicImage, opts: &ImageOptions) -> Result<()> {
+    if opts.renderer == Renderer::Auto {
+        let conf = ViuerConfig {
+            width: Some(opts.width),
+            height: opts.height,
+            absolute_offset: false,
+            ..Default::default()
+        };
+        viuer::print(img, &conf).map_err(|e| anyhow!("Terminal image rendering failed: {}", e))?;
+        return Ok(());
+    }
+    println!("{}", render(img, opts)?);
+    Ok(())
+}
+
+fn is_text_renderer(renderer: Renderer) -> bool {
+    matches!(renderer, Renderer::Ascii | Renderer::AsciiMono | Renderer::HalfBlocks)
+}
+
+// Show an animation according to `opts.animation`. Playback redraws in place by moving
+// the cursor up, which only works for the text renderers; the others get a strip instead.
+pub async fn print_animation(anim: &Animation, opts: &ImageOptions) -> Result<()> {
+    let mode = match opts.animation {
+        AnimationMode::Play if !is_text_renderer(opts.renderer) => AnimationMode::Strip,
+        mode => mode,
+    };
+
+    match mode {
+        AnimationMode::First => print(&anim.frames[0].image, opts)?,
+        AnimationMode::Strip => print_strip(anim, opts)?,
+        AnimationMode::Play => {
+            let rendered = anim.frames
+                .iter()
+                .map(|f| render(&f.image, opts))
+                .collect::<Result<Vec<String>>>()?;
+            for (i, (text, frame)) in rendered.iter().zip(anim.frames.iter()).enumerate() {
+                if i > 0 {
+                    let previous_lines = rendered[i - 1].lines().count();
+                    print!("\x1b[{}A", previous_lines);
+                }
+                print!("{}", text);
+                sleep(Duration::from_millis(frame.delay_ms.max(20) as u64)).await;
+            }
+            println!();
+        }
+    }
+    Ok(())
+}
+
+const STRIP_FRAMES: usize = 4;
+
+fn print_strip(anim: &Animation, opts: &ImageOptions) -> Result<()> {
+    let count = anim.frames.len().min(STRIP_FRAMES);
+    let picked: Vec<usize> = (0..count)
+        .map(|i| i * (anim.frames.len() - 1) / (count - 1).max(1))
+        .collect();
+
+    if is_text_renderer(opts.renderer) {
+        // Split the width between frames and lay them out next to each other
+        let gap = 2;
+        let mut frame_opts = opts.clone();
+        frame_opts.width = (opts.width.saturating_sub(gap * (count as u32 - 1)) / count as u32).max(4);
+
+        let rendered = picked
+            .iter()
+            .map(|&i| render(&anim.frames[i].image, &frame_opts))
+            .collect::<Result<Vec<String>>>()?;
+        let widths: Vec<usize> = picked
+            .iter()
+            .map(|&i| {
+                let (w, h) = anim.frames[i].image.dimensions();
+                fit_cells(w, h, frame_opts.width, frame_opts.height, 1).0 as usize
+            })
+            .collect();
+        let rows = rendered.iter().map(|r| r.lines().count()).max().unwrap_or(0);
+
+        for row in 0..rows {
+            let line: Vec<String> = rendered
+                .iter()
+                .zip(widths.iter())
+                .map(|(text, width)| text.lines().nth(row).map(|l| l.to_string()).unwrap_or_else(|| " ".repeat(*width)))
+                .collect();
+            println!("{}", line.join(&" ".repeat(gap as usize)));
+        }
+    } else {
+        for &i in picked.iter() {
+            print(&anim.frames[i].image, opts)?;
+        }
+    }
+
+    let numbers: Vec<String> = picked.iter().map(|i| (i + 1).to_string()).collect();
+    println!("frames {} of {}{} · {:.1}s loop",
+        numbers.join(", "),
+        anim.frames.len(),
+        if anim.truncated { "+" } else { "" },
+        anim.duration_ms() as f32 / 1000.0
+    );
+    Ok(())
+}
+
+fn luminance(r: f32, g: f32, b: f32) -> f32 {
+    0.299 * r + 0.587 * g + 0.114 * b
+}
+
+pub fn render_ascii(img: &DynamicImage, cols: u32, rows: u32, ramp: &[char], colorize: bool, dither: Dither) -> String {
+    if cols == 0 || rows == 0 || ramp.len() < 2 {
+        return String::new();
+    }
+    let small = img.resize_exact(cols, rows, FilterType::Triangle).to_rgb8();
+    let levels = (ramp.len() - 1) as f32;
+
+    // Luminance scaled to ramp levels; dithering pushes the rounding error onto neighbours
+    let mut lum: Vec<f32> = small
+        .pixels()
+        .map(|p| luminance(p[0] as f32, p[1] as f32, p[2] as f32) / 255.0 * levels)
+        .collect();
+
+    let (w, h) = (cols as usize, rows as usize);
+    let mut out = String::new();
+    for y in 0..h {
+        for x in 0..w {
+            let i = y * w + x;
+            let level = lum[i].round().clamp(0.0, levels);
+            if dither == Dither::FloydSteinberg {
+                let err = lum[i] - level;
+                diffuse(&mut lum, w, h, x, y, err);
+            }
+            let ch = ramp[level as usize];
+
+            if colorize {
+                let p = small.get_pixel(x as u32, y as u32);
+                // Use 24-bit truecolor if terminal supports; colored crate will do the escape
+                out.push_str(&ch.to_string().truecolor(p[0], p[1], p[2]).to_string());
+            } else {
+                out.push(ch);
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+// Floyd-Steinberg error distribution over a single-channel buffer.
+fn diffuse(buf: &mut [f32], w: usize, h: usize, x: usize, y: usize, err: f32) {
+    let mut spread = |dx: isize, dy: usize, weight: f32| {
+        let nx = x as isize + dx;
+        let ny = y + dy;
+        if nx >= 0 && (nx as usize) < w && ny < h {
+            buf[ny * w + nx as usize] += err * weight;
+        }
+    };
+    spread(1, 0, 7.0 / 16.0);
+    spread(-1, 1, 3.0 / 16.0);
+    spread(0, 1, 5.0 / 16.0);
+    spread(1, 1, 1.0 / 16.0);
+}
+
+// Each cell is an upper half block: foreground is the top pixel, background the bottom one.
+pub fn render_half_blocks(img: &DynamicImage, cols: u32, pixel_rows: u32) -> String {
+    if cols == 0 || pixel_rows == 0 {
+        return String::new();
+    }
+    let small = img.resize_exact(cols, pixel_rows, FilterType::Triangle).to_rgb8();
+    let mut out = String::new();
+    for y in (0..pixel_rows).step_by(2) {
+        for x in 0..cols {
+            let top = small.get_pixel(x, y);
+            let cell = if y + 1 < pixel_rows {
+                let bottom = small.get_pixel(x, y + 1);
+                "▀".truecolor(top[0], top[1], top[2]).on_truecolor(bottom[0], bottom[1], bottom[2])
+            } else {
+                "▀".truecolor(top[0], top[1], top[2])
+            };
+            out.push_str(&cell.to_string());
+        }
+        out.push('\n');
+    }
+    out
+}
+
+// Index into the 6x6x6 colour cube used as the sixel palette.
+fn cube_index(r: f32, g: f32, b: f32) -> usize {
+    let q = |v: f32| ((v.clamp(0.0, 255.0) / 51.0).round() as usize).min(5);
+    q(r) * 36 + q(g) * 6 + q(b)
+}
+
+fn cube_color(index: usize) -> (f32, f32, f32) {
+    let v = |i: usize| (i * 51) as f32;
+    (v(index / 36), v((index / 6) % 6), v(index % 6))
+}
+
+pub fn render_sixel(img: &DynamicImage, width_px: u32, height_px: u32, dither: Dither) -> String {
+    if width_px == 0 || height_px == 0 {
+        return String::new();
+    }
+    let small: RgbImage = img.resize_exact(width_px, height_px, FilterType::Triangle).to_rgb8();
+    let (w, h) = (width_px as usize, height_px as usize);
+
+    // Quantise to the palette, optionally diffusing the error per channel
+    let mut channels: Vec<Vec<f32>> = (0..3)
+        .map(|c| small.pixels().map(|p| p[c] as f32).collect())
+        .collect();
+    let mut indices = vec![0usize; w * h];
+    for y in 0..h {
+        for x in 0..w {
+            let i = y * w + x;
+            let idx = cube_index(channels[0][i], channels[1][i], channels[2][i]);
+            indices[i] = idx;
+            if dither == Dither::FloydSteinberg {
+                let (pr, pg, pb) = cube_color(idx);
+                let errs = [channels[0][i] - pr, channels[1][i] - pg, channels[2][i] - pb];
+                for (c, err) in errs.iter().enumerate() {
+                    diffuse(&mut channels[c], w, h, x, y, *err);
+                }
+            }
+        }
+    }
+
+    let mut out = String::from("\x1bPq");
+    out.push_str(&format!("\"1;1;{};{}", w, h));
+    for idx in 0..216 {
+        let (r, g, b) = cube_color(idx);
+        out.push_str(&format!("#{};2;{};{};{}", idx, (r / 2.55).round(), (g / 2.55).round(), (b / 2.55).round()));
+    }
+
+    for band in (0..h).step_by(6) {
+        let band_rows = (h - band).min(6);
+        let mut used: Vec<usize> = (band..band + band_rows)
+            .flat_map(|y| indices[y * w..(y + 1) * w].iter().copied())
+            .collect();
+        used.sort_unstable();
+        used.dedup();
+
+        for (n, color) in used.iter().enumerate() {
+            if n > 0 {
+                out.push('$');
+            }
+            out.push_str(&format!("#{}", color));
+            let column_bits = |x: usize| {
+                (0..band_rows).fold(0u8, |bits, dy| {
+                    if indices[(band + dy) * w + x] == *color { bits | (1 << dy) } else { bits }
+                })
+            };
+            push_sixel_runs(&mut out, (0..w).map(column_bits));
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+// Run-length encode a row of sixel bitmasks.
+fn push_sixel_runs(out: &mut String, bits: impl Iterator<Item = u8>) {
+    let emit = |out: &mut String, ch: char, count: usize| {
+        if count > 3 {
+            out.push_str(&format!("!{}{}", count, ch));
+        } else {
+            (0..count).for_each(|_| out.push(ch));
+        }
+    };
+
+    let mut current: Option<(char, usize)> = None;
+    for b in bits {
+        let ch = (63 + b) as char;
+        current = match current {
+            Some((c, n)) if c == ch => Some((c, n + 1)),
+            Some((c, n)) => {
+                emit(out, c, n);
+                Some((ch, 1))
+            }
+            None => Some((ch, 1)),
+        };
+    }
+    if let Some((c, n)) = current {
+        emit(out, c, n);
+    }
+}
+
+fn encode_png(img: &DynamicImage) -> Result<Vec<u8>> {
+    let mut buf = Cursor::new(Vec::new());
+    img.write_to(&mut buf, ImageOutputFormat::Png)
+        .map_err(|e| anyhow!("PNG encoding failed: {}", e))?;
+    Ok(buf.into_inner())
+}
+
+// Kitty graphics protocol: PNG payload sent in 4 KiB base64 chunks, scaled into cols x rows cells.
+pub fn render_kitty(img: &DynamicImage, cols: u32, rows: u32) -> Result<String> {
+    let payload = STANDARD.encode(encode_png(img)?);
+    let chunks: Vec<&[u8]> = payload.as_bytes().chunks(4096).collect();
+    let mut out = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = if i + 1 < chunks.len() { 1 } else { 0 };
+        let chunk = std::str::from_utf8(chunk).unwrap_or_default();
+        if i == 0 {
+            out.push_str(&format!("\x1b_Ga=T,f=100,c={},r={},m={};{}\x1b\\", cols, rows, more, chunk));
+        } else {
+            out.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+        }
+    }
+    Ok(out)
+}
+
+// iTerm2 inline image escape (also understood by WezTerm and others).
+pub fn render_iterm(img: &DynamicImage, cols: u32, rows: u32) -> Result<String> {
+    let png = encode_png(img)?;
+    Ok(format!(
+        "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=1:{}\x07",
+        png.len(),
+        cols,
+        rows,
+        STANDARD.encode(&png)
+    ))
+}
+
+// Write the downloaded image: untouched, or resized to `width` pixels as a PNG.
+pub fn save_image(bytes: &[u8], path: &Path, width: Option<u32>) -> Result<()> {
+    match width {
+        None => fs::write(path, bytes)
+            .map_err(|e| anyhow!("Failed to write '{}': {}", path.display(), e)),
+        Some(width) => {
+            let img = decode(bytes)?;
+            let (w, h) = img.dimensions();
+            let height = ((h as f32 * width as f32 / w.max(1) as f32).round() as u32).max(1);
+            img.resize_exact(width, height, FilterType::Lanczos3)
+                .save_with_format(path, ImageFormat::Png)
+                .map_err(|e| anyhow!("Failed to write '{}': {}", path.display(), e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(w: u32, h: u32, rgb: [u8; 3]) -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_pixel(w, h, image::Rgb(rgb)))
+    }
+
+    // Deterministic noise, so PNG compression can't shrink it much
+    fn noise(w: u32, h: u32) -> DynamicImage {
+        let mut state = 0x2545_f491u32;
+        DynamicImage::ImageRgb8(RgbImage::from_fn(w, h, |_, _| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            let [r, g, b, _] = state.to_le_bytes();
+            image::Rgb([r, g, b])
+        }))
+    }
+
+    #[test]
+    fn ramp_presets_and_custom() {
+        assert_eq!(parse_ramp("standard").unwrap(), RAMP_STANDARD.chars().collect::<Vec<_>>());
+        assert_eq!(parse_ramp("blocks").unwrap(), vec!['█', '▓', '▒', '░', ' ']);
+        assert_eq!(parse_ramp("#. ").unwrap(), vec!['#', '.', ' ']);
+        assert!(parse_ramp("").is_err());
+        assert!(parse_ramp("x").is_err());
+    }
+
+    #[test]
+    fn cells_keep_aspect_ratio() {
+        // A square image is half as many rows as columns
+        assert_eq!(fit_cells(100, 100, 40, None, 1), (40, 20));
+        // Half blocks carry two pixel rows per text row
+        assert_eq!(fit_cells(100, 100, 40, None, 2), (40, 40));
+        // Too tall: rows are capped and columns shrink to match
+        assert_eq!(fit_cells(100, 400, 40, Some(20), 1), (10, 20));
+        // Rows under the cap leave the width alone
+        assert_eq!(fit_cells(400, 100, 40, Some(20), 1), (40, 5));
+    }
+
+    #[test]
+    fn cells_for_degenerate_sizes() {
+        assert_eq!(fit_cells(0, 100, 40, None, 1), (0, 0));
+        assert_eq!(fit_cells(100, 0, 40, None, 1), (0, 0));
+        assert_eq!(fit_cells(100, 100, 0, None, 1), (0, 0));
+        // A very wide image still gets one row
+        assert_eq!(fit_cells(10_000, 1, 40, None, 1), (40, 1));
+    }
+
+    #[test]
+    fn ascii_maps_dark_to_first_character() {
+        let ramp = parse_ramp("#. ").unwrap();
+        let dark = render_ascii(&solid(8, 8, [0, 0, 0]), 4, 2, &ramp, false, Dither::None);
+        assert_eq!(dark, "####\n####\n");
+        let light = render_ascii(&solid(8, 8, [255, 255, 255]), 4, 2, &ramp, false, Dither::None);
+        assert_eq!(light, "    \n    \n");
+        assert_eq!(render_ascii(&solid(8, 8, [0, 0, 0]), 0, 2, &ramp, false, Dither::None), "");
+    }
+
+    #[test]
+    fn dither_mixes_levels_for_midtones() {
+        let ramp = parse_ramp("# ").unwrap();
+        let grey = solid(16, 16, [128, 128, 128]);
+
+        // Without dithering a flat midtone rounds the same way everywhere
+        let flat = render_ascii(&grey, 16, 16, &ramp, false, Dither::None);
+        assert!(flat.chars().filter(|c| *c != '\n').all(|c| c == ' '));
+
+        // With it roughly half the cells go each way
+        let dithered = render_ascii(&grey, 16, 16, &ramp, false, Dither::FloydSteinberg);
+        let dark = dithered.chars().filter(|c| *c == '#').count();
+        assert!((96..=160).contains(&dark), "{} dark cells out of 256", dark);
+        assert_eq!(dithered.lines().count(), 16);
+        assert!(dithered.lines().all(|line| line.chars().count() == 16));
+    }
+
+    #[test]
+    fn cube_round_trips_palette_colours() {
+        for index in [0, 1, 43, 107, 215] {
+            let (r, g, b) = cube_color(index);
+            assert_eq!(cube_index(r, g, b), index);
+        }
+        // Out of range channels clamp: r -> 0, g -> 5, b -> 1
+        assert_eq!(cube_index(-10.0, 300.0, 26.0), 31);
+    }
+
+    #[test]
+    fn sixel_runs_are_length_encoded() {
+        let mut out = String::new();
+        push_sixel_runs(&mut out, [0u8, 0, 0, 0, 0].into_iter());
+        assert_eq!(out, "!5?");
+
+        // Runs of three or fewer are cheaper written out
+        let mut out = String::new();
+        push_sixel_runs(&mut out, [1u8, 1, 1, 63, 2].into_iter());
+        assert_eq!(out, "@@@~A");
+    }
+
+    #[test]
+    fn sixel_framing() {
+        let out = render_sixel(&solid(4, 4, [0, 0, 0]), 3, 8, Dither::None);
+        assert!(out.starts_with("\x1bPq\"1;1;3;8#0;2;0;0;0"));
+        assert!(out.ends_with("\x1b\\"));
+        // Two bands of six rows; the second only has two rows lit
+        assert!(out.contains("#0~~~-"));
+        assert!(out.contains("#0BBB-"));
+        assert_eq!(render_sixel(&solid(4, 4, [0, 0, 0]), 0, 8, Dither::None), "");
+    }
+
+    #[test]
+    fn kitty_payload_is_chunked() {
+        let out = render_kitty(&noise(64, 64), 10, 5).unwrap();
+        let chunks: Vec<&str> = out.split("\x1b\\").filter(|c| !c.is_empty()).collect();
+        assert!(chunks.len() > 1);
+        assert!(chunks[0].starts_with("\x1b_Ga=T,f=100,c=10,r=5,m=1;"));
+        for middle in &chunks[1..chunks.len() - 1] {
+            assert!(middle.starts_with("\x1b_Gm=1;"));
+        }
+        assert!(chunks.last().unwrap().starts_with("\x1b_Gm=0;"));
+
+        // The chunks reassemble into the PNG
+        let payload: String = chunks.iter().map(|c| c.split_once(';').unwrap().1).collect();
+        let png = STANDARD.decode(payload).unwrap();
+        assert_eq!(decode(&png).unwrap().dimensions(), (64, 64));
+    }
+
+    #[test]
+    fn kitty_small_image_is_one_chunk() {
+        let out = render_kitty(&solid(2, 2, [255, 0, 0]), 1, 1).unwrap();
+        assert!(out.starts_with("\x1b_Ga=T,f=100,c=1,r=1,m=0;"));
+        assert_eq!(out.matches("\x1b\\").count(), 1);
+    }
+}
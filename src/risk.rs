@@ -0,0 +1,271 @@
+use crate::activity;
+use crate::model::{Metadata, RiskFlag, RiskReport, RiskSignals};
+use crate::pda;
+use crate::programs::TOKEN_METADATA_PROGRAM;
+use crate::probe::resolve_uri;
+use crate::rpc::{self, das_url};
+use crate::transactions;
+use reqwest::{Client, Url};
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+// A metadata change this recent counts as a red flag.
+const RECENT_CHANGE_DAYS: i64 = 30;
+const DAY: i64 = 86_400;
+// Transactions on the metadata account within this long of its creation are the mint
+// itself (creation, collection verification, ...), not a later change.
+const MINT_WINDOW_SECS: i64 = DAY;
+// Newest transactions on the metadata account checked for a write.
+const CHANGE_SCAN: usize = 20;
+// An update authority controlling fewer other assets than this looks like a throwaway key.
+const THROWAWAY_AUTHORITY_ASSETS: u32 = 3;
+
+// Hosts that serve content-addressed or permanent storage.
+const DECENTRALIZED_HOSTS: &[&str] = &["arweave.net", "ipfs", "irys.xyz", "nftstorage.link", "dweb.link", "akrd.net"];
+
+pub struct Rule {
+    pub id: &'static str,
+    pub weight: u32,
+    // Detail shown for the flag when the rule triggers
+    pub check: fn(&RiskSignals) -> Option<String>,
+}
+
+// The built-in rule set. Weights are added up and capped at 100.
+pub const RULES: &[Rule] = &[
+    Rule { id: "mutable-metadata", weight: 15, check: |s| s.mutable.then(|| "the update authority can still change name, image and traits".to_string()) },
+    Rule { id: "unverified-creators", weight: 15, check: |s| (s.creators > 0 && s.verified_creators == 0).then(|| format!("none of the {} creators signed", s.creators)) },
+    Rule { id: "unverified-collection", weight: 15, check: |s| (s.has_collection && !s.collection_verified).then(|| "claims a collection that never verified it".to_string()) },
+    Rule { id: "no-collection", weight: 5, check: |s| (!s.has_collection).then(|| "not part of any on-chain collection".to_string()) },
+    Rule { id: "mint-authority", weight: 25, check: |s| s.mint_authority.as_ref().map(|a| format!("more tokens can be minted by {}", a)) },
+    Rule { id: "freeze-authority", weight: 15, check: |s| s.freeze_authority.as_ref().map(|a| format!("{} can freeze the token in any wallet", a)) },
+    Rule { id: "permanent-delegate", weight: 30, check: |s| s.permanent_delegate.as_ref().map(|a| format!("{} can move or burn the token from any wallet", a)) },
+    Rule { id: "centralized-image", weight: 10, check: |s| s.centralized_image.then(|| format!("image served from {}", s.image_host.as_deref().unwrap_or("?"))) },
+    Rule { id: "recent-metadata-change", weight: 15, check: |s| {
+        s.metadata_changed_at
+            .filter(|t| s.checked_at - t <= RECENT_CHANGE_DAYS * DAY)
+            .map(|t| format!("metadata account written on {}, after the mint", activity::format_date(t)))
+    } },
+    Rule { id: "throwaway-authority", weight: 10, check: |s| {
+        s.authority_assets
+            .filter(|n| *n < THROWAWAY_AUTHORITY_ASSETS)
+            .map(|n| format!("the update authority controls only {} other assets", n))
+    } },
+];
+
+// Gather the signals for an NFT and run the rules over them. Lookups that fail leave
+// their signal unset and come back as warnings.
+pub async fn assess(client: &Client, rpc_url: &str, mint: &str, metadata: &Metadata, image: &str) -> (RiskReport, Vec<String>) {
+    let mut warnings = Vec::new();
+    let mut signals = offline_signals(metadata, image);
+
+    let Ok(mint_key) = Pubkey::from_str(mint) else {
+        return (evaluate(signals), warnings);
+    };
+    if let Err(e) = mint_signals(client, rpc_url, &mint_key, &mut signals).await {
+        warnings.push(format!("mint account: {}", e));
+    }
+    match last_metadata_change(client, rpc_url, &mint_key).await {
+        Ok(changed) => signals.metadata_changed_at = changed,
+        Err(e) => warnings.push(format!("metadata history: {}", e)),
+    }
+    // Only asked of DAS; without it the rule simply doesn't run
+    signals.authority_assets = authority_assets(client, &metadata.update_authority.to_string(), mint).await;
+
+    (evaluate(signals), warnings)
+}
+
+// The signals the metadata account and image URI give away without any lookups.
+pub fn offline_signals(metadata: &Metadata, image: &str) -> RiskSignals {
+    let creators = metadata.data.creators.as_deref().unwrap_or_default();
+    let image_host = resolve_uri(image).and_then(|u| Url::parse(&u).ok()).and_then(|u| u.host_str().map(str::to_string));
+    let image_path_is_ipfs = image.starts_with("ipfs://") || image.starts_with("ar://") || image.contains("/ipfs/");
+
+    RiskSignals {
+        mutable: metadata.is_mutable,
+        creators: creators.len() as u32,
+        verified_creators: creators.iter().filter(|c| c.verified).count() as u32,
+        has_collection: metadata.collection.is_some(),
+        collection_verified: metadata.collection.as_ref().is_some_and(|c| c.verified),
+        royalty_bps: metadata.data.seller_fee_basis_points,
+        token_program: None,
+        mint_authority: None,
+        freeze_authority: None,
+        permanent_delegate: None,
+        centralized_image: image_host.as_deref().is_some_and(|h| !image_path_is_ipfs && !DECENTRALIZED_HOSTS.iter().any(|d| h.contains(d))),
+        image_host,
+        metadata_changed_at: None,
+        authority_assets: None,
+        checked_at: activity::now(),
+    }
+}
+
+pub fn evaluate(signals: RiskSignals) -> RiskReport {
+    let flags: Vec<RiskFlag> = RULES
+        .iter()
+        .filter_map(|rule| {
+            (rule.check)(&signals).map(|detail| RiskFlag { rule: rule.id.to_string(), weight: rule.weight, detail })
+        })
+        .collect();
+    let score = flags.iter().map(|f| f.weight).sum::<u32>().min(100);
+    RiskReport { score, level: level(score).to_string(), flags, signals }
+}
+
+pub fn level(score: u32) -> &'static str {
+    match score {
+        50.. => "high",
+        25..=49 => "medium",
+        _ => "low",
+    }
+}
+
+// Authorities on the mint. Handing them to the master edition PDA is how a regular NFT
+// gives them up, so that doesn't count as still holding them.
+async fn mint_signals(client: &Client, rpc_url: &str, mint: &Pubkey, signals: &mut RiskSignals) -> anyhow::Result<()> {
+    let result = rpc::call(client, rpc_url, "getAccountInfo", json!([mint.to_string(), { "encoding": "jsonParsed" }])).await?;
+    let account = result.get("value").filter(|v| !v.is_null()).ok_or_else(|| anyhow::anyhow!("not found"))?;
+    signals.token_program = account.get("owner").and_then(Value::as_str).map(str::to_string);
+
+    let edition = pda::edition_address(mint).to_string();
+    let info = account.pointer("/data/parsed/info");
+    let authority = |field: &str| {
+        info.and_then(|i| i.get(field))
+            .and_then(Value::as_str)
+            .filter(|a| *a != edition)
+            .map(str::to_string)
+    };
+    signals.mint_authority = authority("mintAuthority");
+    signals.freeze_authority = authority("freezeAuthority");
+
+    // Token-2022 extensions come back parsed next to the mint fields
+    signals.permanent_delegate = info
+        .and_then(|i| i.get("extensions"))
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .find(|e| e.get("extension").and_then(Value::as_str) == Some("permanentDelegate"))
+        .and_then(|e| e.pointer("/state/delegate"))
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    Ok(())
+}
+
+// Latest write to the metadata account that came well after its creation, if any.
+// The account also shows up read-only in transfers and sales, so only successful
+// Token Metadata calls that write to it count. When the history is longer than one
+// page the creation time is unknown, and any such write counts as a change.
+async fn last_metadata_change(client: &Client, rpc_url: &str, mint: &Pubkey) -> anyhow::Result<Option<i64>> {
+    let address = pda::metadata_address(mint).to_string();
+    let (signatures, truncated) = activity::fetch_signatures(client, rpc_url, &address, 1000).await?;
+    let successful: Vec<_> = signatures.iter().filter(|s| s.err.is_none()).collect();
+    let created = successful.last().and_then(|s| s.block_time);
+
+    let candidates = successful
+        .iter()
+        .filter(|s| truncated || s.block_time.zip(created).is_some_and(|(t, c)| t - c > MINT_WINDOW_SECS))
+        .take(CHANGE_SCAN);
+    for info in candidates {
+        let Ok(tx) = transactions::fetch_parsed(client, rpc_url, &info.signature).await else { continue };
+        if transactions::invokes_program(&tx, TOKEN_METADATA_PROGRAM) && transactions::writable_accounts(&tx).contains(&address) {
+            return Ok(transactions::block_time(&tx).or(info.block_time));
+        }
+    }
+    Ok(None)
+}
+
+// Assets besides this one that the update authority controls, up to the threshold.
+async fn authority_assets(client: &Client, authority: &str, mint: &str) -> Option<u32> {
+    let params = json!({ "authorityAddress": authority, "page": 1, "limit": THROWAWAY_AUTHORITY_ASSETS + 1 });
+    let result = rpc::call(client, &das_url(), "getAssetsByAuthority", params).await.ok()?;
+    let items = result.get("items").and_then(Value::as_array)?;
+    Some(items.iter().filter(|i| i.get("id").and_then(Value::as_str) != Some(mint)).count() as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    // An NFT no rule objects to
+    fn clean() -> RiskSignals {
+        RiskSignals {
+            mutable: false,
+            creators: 1,
+            verified_creators: 1,
+            has_collection: true,
+            collection_verified: true,
+            royalty_bps: 500,
+            token_program: None,
+            mint_authority: None,
+            freeze_authority: None,
+            permanent_delegate: None,
+            image_host: Some("arweave.net".to_string()),
+            centralized_image: false,
+            metadata_changed_at: None,
+            authority_assets: None,
+            checked_at: NOW,
+        }
+    }
+
+    fn rules_hit(signals: RiskSignals) -> Vec<String> {
+        evaluate(signals).flags.into_iter().map(|f| f.rule).collect()
+    }
+
+    #[test]
+    fn clean_nft_scores_zero() {
+        let report = evaluate(clean());
+        assert!(report.flags.is_empty());
+        assert_eq!((report.score, report.level.as_str()), (0, "low"));
+    }
+
+    #[test]
+    fn weights_add_up() {
+        let report = evaluate(RiskSignals { mutable: true, freeze_authority: Some("F".to_string()), ..clean() });
+        assert_eq!(report.flags.len(), 2);
+        assert_eq!((report.score, report.level.as_str()), (30, "medium"));
+    }
+
+    #[test]
+    fn score_caps_at_100() {
+        let report = evaluate(RiskSignals {
+            mutable: true,
+            verified_creators: 0,
+            collection_verified: false,
+            mint_authority: Some("M".to_string()),
+            freeze_authority: Some("F".to_string()),
+            permanent_delegate: Some("D".to_string()),
+            ..clean()
+        });
+        assert_eq!(report.flags.iter().map(|f| f.weight).sum::<u32>(), 115);
+        assert_eq!((report.score, report.level.as_str()), (100, "high"));
+    }
+
+    #[test]
+    fn level_boundaries() {
+        assert_eq!(level(0), "low");
+        assert_eq!(level(24), "low");
+        assert_eq!(level(25), "medium");
+        assert_eq!(level(49), "medium");
+        assert_eq!(level(50), "high");
+        assert_eq!(level(100), "high");
+    }
+
+    #[test]
+    fn recent_change_window() {
+        let window = RECENT_CHANGE_DAYS * DAY;
+        assert_eq!(rules_hit(RiskSignals { metadata_changed_at: Some(NOW - DAY), ..clean() }), ["recent-metadata-change"]);
+        assert_eq!(rules_hit(RiskSignals { metadata_changed_at: Some(NOW - window), ..clean() }), ["recent-metadata-change"]);
+        assert!(rules_hit(RiskSignals { metadata_changed_at: Some(NOW - window - 1), ..clean() }).is_empty());
+    }
+
+    #[test]
+    fn throwaway_authority_below_threshold() {
+        let below = THROWAWAY_AUTHORITY_ASSETS - 1;
+        assert_eq!(rules_hit(RiskSignals { authority_assets: Some(0), ..clean() }), ["throwaway-authority"]);
+        assert_eq!(rules_hit(RiskSignals { authority_assets: Some(below), ..clean() }), ["throwaway-authority"]);
+        assert!(rules_hit(RiskSignals { authority_assets: Some(THROWAWAY_AUTHORITY_ASSETS), ..clean() }).is_empty());
+        // Unknown without DAS, so the rule stays quiet
+        assert!(rules_hit(RiskSignals { authority_assets: None, ..clean() }).is_empty());
+    }
+}
@@ -1,4 +1,6 @@
-use reqwest::Client;
+use anyhow::{anyhow, Result};
+use reqwest::{redirect, Client};
+use serde_json::{json, Value};
 use std::env;
 use std::time::Duration;
 
@@ -9,4 +11,56 @@ pub fn create_rpc_client() -> (Client, String) {
         .build()
         .expect("Failed to create RPC client");
     (client, rpc_url)
-}
\ No newline at end of file
+}
+
+pub fn helius_api_key() -> Option<String> {
+    env::var("HELIUS_API_KEY").ok().filter(|k| !k.is_empty())
+}
+
+// DAS (Digital Asset Standard) endpoint. Helius when HELIUS_API_KEY is set, otherwise the public RPC.
+pub fn das_url() -> String {
+    match helius_api_key() {
+        Some(key) => format!("https://mainnet.helius-rpc.com/?api-key={}", key),
+        None => "https://api.mainnet-beta.solana.com".to_string(),
+    }
+}
+
+// Client for asset health checks: redirects are followed by hand so every hop can be reported.
+pub fn create_probe_client() -> Client {
+    Client::builder()
+        .timeout(Duration::from_secs(15))
+        .redirect(redirect::Policy::none())
+        .build()
+        .expect("Failed to create probe client")
+}
+
+// One JSON-RPC round trip. Returns the `result` field, or the RPC error as an anyhow error.
+pub async fn call(client: &Client, url: &str, method: &str, params: Value) -> Result<Value> {
+    let message = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": params,
+    });
+
+    // The Helius URL carries the api key, so errors don't repeat it
+    let resp_text = client
+        .post(url)
+        .json(&message)
+        .send()
+        .await
+        .map_err(|e| e.without_url())?
+        .text()
+        .await
+        .map_err(|e| e.without_url())?;
+
+    let mut v: Value = serde_json::from_str(&resp_text)
+        .map_err(|e| anyhow!("Failed to parse {} response as JSON: {}\nResponse text: {}", method, e, resp_text))?;
+
+    if let Some(err) = v.get("error") {
+        return Err(anyhow!("{} returned error: {}", method, err));
+    }
+    v.get_mut("result")
+        .map(Value::take)
+        .ok_or_else(|| anyhow!("Missing `result` field in {} response: {}", method, resp_text))
+}
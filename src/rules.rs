@@ -0,0 +1,763 @@
+use anyhow::{anyhow, Context, Result};
+use crate::jutsu::unmask;
+use crate::model::{DossierReport, RiskFlag, RiskReport, UnmaskReport};
+use crate::risk;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+// Shipped rules, written in the same language as user rules. User rules with the
+// same id replace them; `"when": "false"` switches one off.
+const DEFAULT_RULES: &str = r#"[
+    { "id": "high-conviction", "report": "dossier", "assessment": true,
+      "when": "len(collections) > 0 and collections[0].count / total_nfts > 0.3",
+      "message": "High-conviction '{collections[0].name}' holder." },
+    { "id": "diversified", "report": "dossier", "assessment": true,
+      "when": "len(collections) > 5",
+      "message": "Diversified collector across multiple collections." },
+    { "id": "selective", "report": "dossier", "assessment": true,
+      "when": "len(collections) > 0",
+      "message": "Selective NFT holder." },
+    { "id": "no-collections", "report": "dossier", "assessment": true,
+      "when": "true",
+      "message": "No NFT collections detected." }
+]"#;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportKind {
+    Unmask,
+    Dossier,
+}
+
+// A rule as written in a rules file.
+#[derive(Deserialize)]
+struct RuleSpec {
+    id: String,
+    report: ReportKind,
+    when: String,
+    message: String,
+    #[serde(default)]
+    weight: u32,
+    #[serde(default)]
+    assessment: bool,
+}
+
+#[derive(Debug)]
+pub struct Rule {
+    pub id: String,
+    pub report: ReportKind,
+    // Expression over the report's JSON fields, as written and as parsed
+    pub when: String,
+    condition: Expr,
+    // Shown when the rule matches; `{expression}` is replaced by its value
+    pub message: String,
+    // Added to the unmask risk score
+    pub weight: u32,
+    // Dossier only: the first matching assessment rule describes the wallet
+    pub assessment: bool,
+    pub builtin: bool,
+}
+
+impl Rule {
+    fn from_spec(spec: RuleSpec, builtin: bool) -> Result<Self> {
+        let condition = parse(&spec.when).map_err(|e| anyhow!("rule '{}': {}", spec.id, e))?;
+        Ok(Rule {
+            id: spec.id,
+            report: spec.report,
+            when: spec.when,
+            condition,
+            message: spec.message,
+            weight: spec.weight,
+            assessment: spec.assessment,
+            builtin,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct RuleFile {
+    rules: Vec<RuleSpec>,
+}
+
+pub struct RuleSet {
+    // User rules first, then the built-ins they didn't replace
+    pub rules: Vec<Rule>,
+    pub source: Option<PathBuf>,
+}
+
+// Rules from `path`, or from the default config file when there is one. A missing
+// default file just means the built-ins. Rule ids compare case-insensitively.
+pub fn load(path: Option<&Path>) -> Result<RuleSet> {
+    let source = path.map(Path::to_path_buf).or_else(|| default_path().filter(|p| p.exists()));
+    let specs = match &source {
+        Some(path) => {
+            let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read rules file '{}'", path.display()))?;
+            let file: RuleFile = serde_json::from_str(&text).with_context(|| format!("Invalid rules file '{}'", path.display()))?;
+            file.rules
+        }
+        None => Vec::new(),
+    };
+    let mut rules = specs.into_iter().map(|spec| Rule::from_spec(spec, false)).collect::<Result<Vec<_>>>()?;
+
+    let builtins: Vec<RuleSpec> = serde_json::from_str(DEFAULT_RULES).expect("valid built-in rules");
+    for spec in builtins {
+        if !rules.iter().any(|r| r.id.eq_ignore_ascii_case(&spec.id)) {
+            rules.push(Rule::from_spec(spec, true).expect("valid built-in rule"));
+        }
+    }
+    Ok(RuleSet { rules, source })
+}
+
+fn default_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("shinobi").join("rules.json"))
+}
+
+// What one rule made of one report.
+pub enum Outcome {
+    Matched(String),
+    NotMatched,
+    Failed(String),
+}
+
+pub fn check(rule: &Rule, report: &Value) -> Outcome {
+    let matched = eval(&rule.condition, report);
+    match matched {
+        Ok(v) if truthy(&v) => match render(&rule.message, report) {
+            Ok(message) => Outcome::Matched(message),
+            Err(e) => Outcome::Failed(format!("message: {}", e)),
+        },
+        Ok(_) => Outcome::NotMatched,
+        Err(e) => Outcome::Failed(e),
+    }
+}
+
+// Which kind of report a saved JSON file holds.
+pub fn report_kind(report: &Value) -> Option<ReportKind> {
+    if report.get("wallet_address").is_some() {
+        Some(ReportKind::Dossier)
+    } else if report.get("mint").is_some() && report.get("update_authority").is_some() {
+        Some(ReportKind::Unmask)
+    } else {
+        None
+    }
+}
+
+impl RuleSet {
+    fn for_report(&self, kind: ReportKind) -> impl Iterator<Item = &Rule> {
+        self.rules.iter().filter(move |r| r.report == kind)
+    }
+
+    // Matching unmask rules become risk flags. A user rule sharing an id with a
+    // built-in risk rule replaces it. Without a risk report (--no-risk, decode) the
+    // rules still run, over the signals the metadata gives away on its own.
+    pub fn apply_unmask(&self, report: &mut UnmaskReport) {
+        let mut risk = match report.risk.take() {
+            Some(risk) => risk,
+            None if self.for_report(ReportKind::Unmask).next().is_none() => return,
+            None => {
+                report.warnings.push("risk: built-in rules skipped, the score only counts your own rules".to_string());
+                let signals = risk::offline_signals(&report.on_chain, &report.off_chain.image);
+                RiskReport { score: 0, level: risk::level(0).to_string(), flags: Vec::new(), signals }
+            }
+        };
+        let user_ids: Vec<&str> = self.for_report(ReportKind::Unmask).map(|r| r.id.as_str()).collect();
+        risk.flags.retain(|f| !user_ids.iter().any(|id| id.eq_ignore_ascii_case(&f.rule)));
+
+        report.risk = Some(risk);
+        let view = unmask::to_json(report);
+        let risk = report.risk.as_mut().expect("risk was just put back");
+        for rule in self.for_report(ReportKind::Unmask) {
+            match check(rule, &view) {
+                Outcome::Matched(detail) => risk.flags.push(RiskFlag { rule: rule.id.clone(), weight: rule.weight, detail }),
+                Outcome::NotMatched => {}
+                Outcome::Failed(e) => report.warnings.push(format!("rule '{}': {}", rule.id, e)),
+            }
+        }
+        risk.score = risk.flags.iter().map(|f| f.weight).sum::<u32>().min(100);
+        risk.level = risk::level(risk.score).to_string();
+    }
+
+    // The first matching assessment rule sets the assessment; other matches are flags.
+    pub fn apply_dossier(&self, report: &mut DossierReport) {
+        let view = serde_json::to_value(&*report).unwrap_or(Value::Null);
+        for rule in self.for_report(ReportKind::Dossier) {
+            if rule.assessment && !report.assessment.is_empty() {
+                continue;
+            }
+            match check(rule, &view) {
+                Outcome::Matched(message) if rule.assessment => report.assessment = message,
+                Outcome::Matched(detail) => report.flags.push(RiskFlag { rule: rule.id.clone(), weight: rule.weight, detail }),
+                Outcome::NotMatched => {}
+                Outcome::Failed(e) => report.warnings.push(format!("rule '{}': {}", rule.id, e)),
+            }
+        }
+    }
+}
+
+// --- The expression language ---
+//
+//   expr     := or
+//   or       := and ("or" and)*
+//   and      := not ("and" not)*
+//   not      := "not" not | compare
+//   compare  := sum (("==" | "!=" | "<" | "<=" | ">" | ">=" | "contains") sum)?
+//   sum      := product (("+" | "-") product)*
+//   product  := unary (("*" | "/" | "%") unary)*
+//   unary    := "-" unary | primary
+//   primary  := number | string | true | false | null | "(" expr ")"
+//             | name "(" args ")" | name ("." name | "[" number "]")*
+//
+// Paths into missing fields are null; null compares false against everything but null.
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Str(String),
+    Name(String),
+    Symbol(&'static str),
+}
+
+#[derive(Debug)]
+enum Segment {
+    Field(String),
+    Index(usize),
+}
+
+#[derive(Debug)]
+enum Expr {
+    Literal(Value),
+    Path(Vec<Segment>),
+    Call(String, Vec<Expr>),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+}
+
+const SYMBOLS: &[&str] = &["==", "!=", "<=", ">=", "<", ">", "+", "-", "*", "/", "%", "(", ")", "[", "]", ".", ","];
+const FUNCTIONS: &[&str] = &["len", "lower", "upper", "exists"];
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.' || chars[i] == '_') {
+                i += 1;
+            }
+            let literal: String = chars[start..i].iter().filter(|c| **c != '_').collect();
+            let number = literal.parse().map_err(|_| format!("bad number '{}'", literal))?;
+            tokens.push(Token::Number(number));
+        } else if c == '"' || c == '\'' {
+            let end = chars[i + 1..].iter().position(|x| *x == c).ok_or("unterminated string")?;
+            tokens.push(Token::Str(chars[i + 1..i + 1 + end].iter().collect()));
+            i += end + 2;
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Name(chars[start..i].iter().collect()));
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let symbol = SYMBOLS.iter().find(|s| rest.starts_with(*s)).ok_or_else(|| format!("unexpected '{}'", c))?;
+            tokens.push(Token::Symbol(symbol));
+            i += symbol.len();
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Name(n)) if n == word) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), String> {
+        if self.eat_symbol(symbol) {
+            Ok(())
+        } else {
+            Err(format!("expected '{}'", symbol))
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut left = self.and()?;
+        while self.eat_word("or") {
+            left = Expr::Binary("or", Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.not()?;
+        while self.eat_word("and") {
+            left = Expr::Binary("and", Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.eat_word("not") {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.compare()
+    }
+
+    fn compare(&mut self) -> Result<Expr, String> {
+        let left = self.sum()?;
+        for op in ["==", "!=", "<=", ">=", "<", ">"] {
+            if self.eat_symbol(op) {
+                return Ok(Expr::Binary(op, Box::new(left), Box::new(self.sum()?)));
+            }
+        }
+        if self.eat_word("contains") {
+            return Ok(Expr::Binary("contains", Box::new(left), Box::new(self.sum()?)));
+        }
+        Ok(left)
+    }
+
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut left = self.product()?;
+        loop {
+            let op = if self.eat_symbol("+") { "+" } else if self.eat_symbol("-") { "-" } else { break };
+            left = Expr::Binary(op, Box::new(left), Box::new(self.product()?));
+        }
+        Ok(left)
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut left = self.unary()?;
+        loop {
+            let op = if self.eat_symbol("*") { "*" } else if self.eat_symbol("/") { "/" } else if self.eat_symbol("%") { "%" } else { break };
+            left = Expr::Binary(op, Box::new(left), Box::new(self.unary()?));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat_symbol("-") {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Expr::Literal(json!(n))),
+            Some(Token::Str(s)) => Ok(Expr::Literal(Value::String(s))),
+            Some(Token::Symbol("(")) => {
+                let inner = self.or()?;
+                self.expect_symbol(")")?;
+                Ok(inner)
+            }
+            Some(Token::Name(name)) => match name.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "null" => Ok(Expr::Literal(Value::Null)),
+                "and" | "or" | "not" | "contains" => Err(format!("unexpected '{}'", name)),
+                _ if self.eat_symbol("(") => {
+                    if !FUNCTIONS.contains(&name.as_str()) {
+                        return Err(format!("unknown function '{}'", name));
+                    }
+                    let mut args = Vec::new();
+                    if !self.eat_symbol(")") {
+                        loop {
+                            args.push(self.or()?);
+                            if self.eat_symbol(")") {
+                                break;
+                            }
+                            self.expect_symbol(",")?;
+                        }
+                    }
+                    Ok(Expr::Call(name, args))
+                }
+                _ => {
+                    let mut path = vec![Segment::Field(name)];
+                    loop {
+                        if self.eat_symbol(".") {
+                            match self.next() {
+                                Some(Token::Name(field)) => path.push(Segment::Field(field)),
+                                _ => return Err("expected a field name after '.'".to_string()),
+                            }
+                        } else if self.eat_symbol("[") {
+                            match self.next() {
+                                Some(Token::Number(n)) if n >= 0.0 && n.fract() == 0.0 => path.push(Segment::Index(n as usize)),
+                                _ => return Err("expected an index inside '[ ]'".to_string()),
+                            }
+                            self.expect_symbol("]")?;
+                        } else {
+                            break;
+                        }
+                    }
+                    Ok(Expr::Path(path))
+                }
+            },
+            Some(token) => Err(format!("unexpected {:?}", token)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+fn parse(text: &str) -> Result<Expr, String> {
+    let mut parser = Parser { tokens: tokenize(text)?, pos: 0 };
+    let expr = parser.or()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(token) => Err(format!("unexpected {:?} after the expression", token)),
+    }
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(a) => !a.is_empty(),
+        Value::Object(o) => !o.is_empty(),
+    }
+}
+
+fn number(value: &Value, op: &str) -> Result<f64, String> {
+    value.as_f64().ok_or_else(|| format!("'{}' needs numbers, got {}", op, value))
+}
+
+fn equal(a: &Value, b: &Value) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(x), Some(y)) => x == y,
+        _ => a == b,
+    }
+}
+
+fn eval(expr: &Expr, report: &Value) -> Result<Value, String> {
+    match expr {
+        Expr::Literal(v) => Ok(v.clone()),
+        Expr::Path(path) => {
+            let mut current = report;
+            for segment in path {
+                let next = match segment {
+                    Segment::Field(field) => current.get(field.as_str()),
+                    Segment::Index(i) => current.get(*i),
+                };
+                match next {
+                    Some(v) => current = v,
+                    None => return Ok(Value::Null),
+                }
+            }
+            Ok(current.clone())
+        }
+        Expr::Not(inner) => Ok(Value::Bool(!truthy(&eval(inner, report)?))),
+        Expr::Neg(inner) => Ok(json!(-number(&eval(inner, report)?, "-")?)),
+        Expr::Call(name, args) => {
+            let values = args.iter().map(|a| eval(a, report)).collect::<Result<Vec<_>, _>>()?;
+            let [arg] = values.as_slice() else {
+                return Err(format!("{}() takes one argument", name));
+            };
+            match (name.as_str(), arg) {
+                ("exists", v) => Ok(Value::Bool(!v.is_null())),
+                ("len", Value::String(s)) => Ok(json!(s.chars().count())),
+                ("len", Value::Array(a)) => Ok(json!(a.len())),
+                ("len", Value::Object(o)) => Ok(json!(o.len())),
+                ("len", Value::Null) => Ok(json!(0)),
+                ("lower", Value::String(s)) => Ok(Value::String(s.to_lowercase())),
+                ("upper", Value::String(s)) => Ok(Value::String(s.to_uppercase())),
+                (_, Value::Null) => Ok(Value::Null),
+                _ => Err(format!("{}() can't take {}", name, arg)),
+            }
+        }
+        Expr::Binary(op, left, right) => {
+            let left = eval(left, report)?;
+            // and/or stop early so guards like `total > 0 and x / total > 0.3` work
+            match *op {
+                "and" if !truthy(&left) => return Ok(Value::Bool(false)),
+                "or" if truthy(&left) => return Ok(Value::Bool(true)),
+                _ => {}
+            }
+            let right = eval(right, report)?;
+            match *op {
+                "and" | "or" => Ok(Value::Bool(truthy(&right))),
+                "==" => Ok(Value::Bool(equal(&left, &right))),
+                "!=" => Ok(Value::Bool(!equal(&left, &right))),
+                "<" | "<=" | ">" | ">=" => {
+                    let ordering = match (&left, &right) {
+                        (Value::Null, _) | (_, Value::Null) => return Ok(Value::Bool(false)),
+                        (Value::String(a), Value::String(b)) => a.cmp(b),
+                        _ => number(&left, op)?.partial_cmp(&number(&right, op)?).ok_or("NaN in comparison")?,
+                    };
+                    Ok(Value::Bool(match *op {
+                        "<" => ordering.is_lt(),
+                        "<=" => ordering.is_le(),
+                        ">" => ordering.is_gt(),
+                        _ => ordering.is_ge(),
+                    }))
+                }
+                "contains" => Ok(Value::Bool(match (&left, &right) {
+                    (Value::String(s), Value::String(part)) => s.contains(part.as_str()),
+                    (Value::Array(items), item) => items.iter().any(|i| equal(i, item)),
+                    (Value::Object(map), Value::String(key)) => map.contains_key(key),
+                    (Value::Null, _) => false,
+                    _ => return Err(format!("can't check whether {} contains {}", left, right)),
+                })),
+                "+" if left.is_string() || right.is_string() => Ok(Value::String(format!("{}{}", text(&left), text(&right)))),
+                _ => {
+                    let (a, b) = (number(&left, op)?, number(&right, op)?);
+                    if (*op == "/" || *op == "%") && b == 0.0 {
+                        return Err("division by zero".to_string());
+                    }
+                    Ok(json!(match *op {
+                        "+" => a + b,
+                        "-" => a - b,
+                        "*" => a * b,
+                        "/" => a / b,
+                        _ => a % b,
+                    }))
+                }
+            }
+        }
+    }
+}
+
+// Values as they read in a message: strings bare, whole numbers without ".0".
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => match n.as_f64() {
+            Some(f) if f.fract() == 0.0 && f.abs() < 1e15 => format!("{}", f as i64),
+            Some(f) => format!("{:.2}", f),
+            None => n.to_string(),
+        },
+        other => other.to_string(),
+    }
+}
+
+fn render(template: &str, report: &Value) -> Result<String, String> {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..].find('}').ok_or("unclosed '{' in message")? + start;
+        out.push_str(&rest[..start]);
+        out.push_str(&text(&eval(&parse(&rest[start + 1..end])?, report)?));
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(text: &str, report: &Value) -> Result<Value, String> {
+        eval(&parse(text)?, report)
+    }
+
+    fn holds(text: &str, report: &Value) -> bool {
+        truthy(&run(text, report).unwrap())
+    }
+
+    #[test]
+    fn precedence() {
+        let report = json!({});
+        assert_eq!(run("1 + 2 * 3", &report).unwrap(), json!(7.0));
+        assert_eq!(run("(1 + 2) * 3", &report).unwrap(), json!(9.0));
+        assert_eq!(run("10 - 4 - 3", &report).unwrap(), json!(3.0));
+        assert_eq!(run("-2 * 3", &report).unwrap(), json!(-6.0));
+        assert!(holds("1 + 1 == 2 and 2 * 2 > 3", &report));
+        // and binds tighter than or, not tighter than and
+        assert!(holds("true or false and false", &report));
+        assert!(!holds("not true and false", &report));
+        assert!(holds("not (true and false)", &report));
+    }
+
+    #[test]
+    fn and_or_short_circuit() {
+        let report = json!({ "total": 0, "count": 3 });
+        assert!(!holds("total > 0 and count / total > 0.3", &report));
+        assert!(holds("total == 0 or count / total > 0.3", &report));
+        assert_eq!(run("false and missing.anything > 1", &report).unwrap(), json!(false));
+        assert_eq!(run("count > 1 and 'yes'", &report).unwrap(), json!(true));
+    }
+
+    #[test]
+    fn null_paths() {
+        let report = json!({ "collections": [{ "name": "A" }], "risk": null });
+        assert_eq!(run("missing", &report).unwrap(), Value::Null);
+        assert_eq!(run("risk.score", &report).unwrap(), Value::Null);
+        assert_eq!(run("collections[5].name", &report).unwrap(), Value::Null);
+        assert!(holds("missing == null", &report));
+        assert!(!holds("missing > 1", &report));
+        assert!(!holds("missing < 1", &report));
+        assert!(!holds("missing == 0", &report));
+        assert!(holds("len(missing) == 0", &report));
+        assert!(holds("exists(collections[0].name)", &report));
+        assert!(!holds("exists(collections[1])", &report));
+        assert_eq!(run("lower(missing)", &report).unwrap(), Value::Null);
+    }
+
+    #[test]
+    fn contains() {
+        let report = json!({ "name": "Free Claim", "spam": ["link in name"], "risk": { "score": 10 } });
+        assert!(holds("name contains 'Claim'", &report));
+        assert!(!holds("name contains 'claim'", &report));
+        assert!(holds("lower(name) contains 'claim'", &report));
+        assert!(holds("spam contains 'link in name'", &report));
+        assert!(!holds("spam contains 'other'", &report));
+        assert!(holds("risk contains 'score'", &report));
+        assert!(!holds("missing contains 'x'", &report));
+        assert!(run("risk.score contains 1", &report).is_err());
+    }
+
+    #[test]
+    fn division_by_zero() {
+        let report = json!({ "n": 0 });
+        assert_eq!(run("1 / n", &report).unwrap_err(), "division by zero");
+        assert_eq!(run("5 % 0", &report).unwrap_err(), "division by zero");
+        assert_eq!(run("5 % 3", &report).unwrap(), json!(2.0));
+    }
+
+    #[test]
+    fn type_errors() {
+        let report = json!({ "name": "x" });
+        assert!(run("name * 2", &report).is_err());
+        assert!(run("len(1)", &report).is_err());
+        assert!(run("len(name, name)", &report).is_err());
+        assert_eq!(run("name + 1", &report).unwrap(), json!("x1"));
+    }
+
+    #[test]
+    fn rendering() {
+        let report = json!({ "collections": [{ "name": "Mad Lads", "count": 12 }], "share": 0.456, "total": 40.0 });
+        assert_eq!(
+            render("High-conviction '{collections[0].name}' holder ({collections[0].count} of {total}).", &report).unwrap(),
+            "High-conviction 'Mad Lads' holder (12 of 40)."
+        );
+        assert_eq!(render("{share * 100}%", &report).unwrap(), "45.60%");
+        assert_eq!(render("no placeholders", &report).unwrap(), "no placeholders");
+        assert_eq!(render("{missing}", &report).unwrap(), "null");
+        assert!(render("unclosed {total", &report).is_err());
+        assert!(render("bad {1 +}", &report).is_err());
+    }
+
+    #[test]
+    fn parse_errors() {
+        for text in ["", "1 +", "(1 + 2", "a.", "a[x]", "a[-1]", "'abc", "1 2", "foo(1)", "a and", "#", "1..2"] {
+            assert!(parse(text).is_err(), "'{}' should not parse", text);
+        }
+        assert!(parse("len(collections) > 0 and collections[0].count / total_nfts > 0.3").is_ok());
+        assert!(parse("1_000 < 2_000").is_ok());
+    }
+
+    #[test]
+    fn user_rules_replace_builtins_ignoring_case() {
+        let path = std::env::temp_dir().join(format!("shinobi-rules-test-{}.json", std::process::id()));
+        std::fs::write(&path, r#"{ "rules": [
+            { "id": "Diversified", "report": "dossier", "when": "false", "message": "off" },
+            { "id": "big-royalty", "report": "unmask", "when": "seller_fee_basis_points > 1000", "message": "{seller_fee_basis_points} bps", "weight": 10 }
+        ] }"#).unwrap();
+        let rule_set = load(Some(&path)).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(rule_set.rules.iter().filter(|r| r.id.eq_ignore_ascii_case("diversified")).count(), 1);
+        assert!(!rule_set.rules.iter().find(|r| r.id == "Diversified").unwrap().builtin);
+        let royalty = rule_set.rules.iter().find(|r| r.id == "big-royalty").unwrap();
+        assert!(matches!(check(royalty, &json!({ "seller_fee_basis_points": 1500 })), Outcome::Matched(m) if m == "1500 bps"));
+        assert!(matches!(check(royalty, &json!({ "seller_fee_basis_points": 500 })), Outcome::NotMatched));
+    }
+
+    #[test]
+    fn invalid_rule_fails_to_load() {
+        let path = std::env::temp_dir().join(format!("shinobi-bad-rules-test-{}.json", std::process::id()));
+        std::fs::write(&path, r#"{ "rules": [{ "id": "broken", "report": "unmask", "when": "1 +", "message": "" }] }"#).unwrap();
+        let error = load(Some(&path)).err().unwrap().to_string();
+        std::fs::remove_file(&path).unwrap();
+        assert!(error.contains("broken"), "{}", error);
+    }
+
+    fn metadata(seller_fee_basis_points: u16, is_mutable: bool) -> crate::model::Metadata {
+        use crate::model::{Data, Metadata};
+        Metadata {
+            key: 4,
+            update_authority: Default::default(),
+            mint: Default::default(),
+            data: Data { name: "Test".to_string(), symbol: String::new(), uri: String::new(), seller_fee_basis_points, creators: None },
+            primary_sale_happened: false,
+            is_mutable,
+            edition_nonce: None,
+            token_standard: None,
+            collection: None,
+            uses: None,
+            collection_details: None,
+            programmable_config: None,
+        }
+    }
+
+    #[test]
+    fn unmask_rules_run_without_a_risk_report() {
+        let spec = RuleSpec {
+            id: "low-royalty-mutable".to_string(),
+            report: ReportKind::Unmask,
+            when: "seller_fee_basis_points < 200 and mutable".to_string(),
+            message: "{seller_fee_basis_points} bps".to_string(),
+            weight: 30,
+            assessment: false,
+        };
+        let rule_set = RuleSet { rules: vec![Rule::from_spec(spec, false).unwrap()], source: None };
+        // What `unmask --no-risk` and `decode` hand over
+        let mut report = unmask::offline_report(metadata(150, true));
+        assert!(report.risk.is_none());
+
+        rule_set.apply_unmask(&mut report);
+        let risk = report.risk.expect("user rules start a risk report");
+        assert_eq!(risk.score, 30);
+        assert_eq!(risk.level, "medium");
+        assert_eq!(risk.flags[0].detail, "150 bps");
+        assert!(risk.signals.mutable);
+        assert!(report.warnings.iter().any(|w| w.contains("built-in rules skipped")));
+    }
+
+    #[test]
+    fn no_unmask_rules_leave_risk_unset() {
+        let rule_set = RuleSet { rules: Vec::new(), source: None };
+        let mut report = unmask::offline_report(metadata(150, true));
+        rule_set.apply_unmask(&mut report);
+        assert!(report.risk.is_none());
+        assert!(report.warnings.is_empty());
+    }
+}
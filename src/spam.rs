@@ -0,0 +1,260 @@
+use crate::model::HeliusAsset;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+// Phrases airdropped scam NFTs use to lure holders to a drainer site. Only lures:
+// everyday words like "visit" or "reward" show up in plenty of real descriptions.
+const PHISHING_KEYWORDS: &[&str] = &["claim", "redeem", "eligible", "free mint", "connect your wallet"];
+
+// Top-level domains that show up in spam names far more than in real collections.
+// Bare words ending in a mainstream TLD (.com, .io, ...) are not taken for links.
+const SUSPICIOUS_TLDS: &[&str] = &[
+    ".xyz", ".app", ".fun", ".site", ".online", ".live", ".pro", ".club", ".gift", ".top", ".vip", ".claims",
+];
+
+// Links to these never count against an NFT; ~/.config/shinobi/domains.json adds more.
+const ALLOWED_DOMAINS: &[&str] = &[
+    "magiceden.io", "tensor.trade", "solana.com", "solscan.io", "solana.fm", "metaplex.com",
+    "exchange.art", "phantom.app", "arweave.net", "ipfs.io", "x.com", "twitter.com", "github.com",
+];
+
+// Image text heuristics work on a downscaled greyscale copy this wide.
+const TEXT_SCAN_WIDTH: u32 = 160;
+
+// An unverified creator on this many unverified-collection assets in one wallet looks like a mass airdrop.
+const MASS_AIRDROP_THRESHOLD: u32 = 5;
+
+// Anything in free text that looks like a link: explicit URLs, www. hosts, or bare domains.
+pub fn find_urls(text: &str) -> Vec<String> {
+    text.split(|c: char| c.is_whitespace() || c == '|' || c == '(' || c == ')')
+        .map(|t| t.trim_matches(|c: char| c == '"' || c == '\'' || c == ',' || c == '!' || c == '.'))
+        .filter(|t| !t.is_empty())
+        .filter(|t| {
+            let lower = t.to_ascii_lowercase();
+            lower.contains("://")
+                || lower.starts_with("www.")
+                || (lower.len() > 4
+                    && SUSPICIOUS_TLDS.iter().any(|tld| {
+                        lower.ends_with(tld) || lower.contains(&format!("{}/", tld))
+                    }))
+        })
+        .map(|t| t.to_string())
+        .collect()
+}
+
+pub fn phishing_keywords(text: &str) -> Vec<&'static str> {
+    let lower = text.to_lowercase();
+    PHISHING_KEYWORDS
+        .iter()
+        .filter(|k| lower.contains(*k))
+        .copied()
+        .collect()
+}
+
+// The free text of an NFT's metadata, where scam airdrops put their links.
+#[derive(Default)]
+pub struct SpamText<'a> {
+    pub name: &'a str,
+    pub symbol: &'a str,
+    pub description: &'a str,
+    pub external_url: &'a str,
+    pub attributes: Vec<String>,
+}
+
+// Domains in links found in the text, lowercased and without "www.".
+pub fn domain_of(url: &str) -> Option<String> {
+    let rest = url.split_once("://").map(|(_, r)| r).unwrap_or(url);
+    let host = rest.split(['/', '?', '#']).next()?.rsplit('@').next()?;
+    let host = host.split(':').next()?.to_ascii_lowercase();
+    let host = host.trim_start_matches("www.").to_string();
+    host.contains('.').then_some(host)
+}
+
+fn listed(domain: &str, list: &[String]) -> bool {
+    list.iter().any(|d| domain == d || domain.ends_with(&format!(".{}", d)))
+}
+
+// Reasons the text looks like a phishing lure. Links to allow-listed domains are
+// ignored; a deny-listed domain anywhere is enough on its own.
+pub fn text_spam_reasons(text: &SpamText) -> Vec<String> {
+    let lists = domain_lists();
+    let mut reasons = Vec::new();
+    let unknown = |urls: Vec<String>| -> Vec<String> {
+        urls.into_iter().filter(|u| domain_of(u).is_none_or(|d| !listed(&d, &lists.allow))).collect()
+    };
+
+    let everything = format!("{} {} {} {} {}", text.name, text.symbol, text.description, text.external_url, text.attributes.join(" "));
+    let denied: BTreeSet<String> = find_urls(&everything)
+        .iter()
+        .filter_map(|u| domain_of(u))
+        .filter(|d| listed(d, &lists.deny))
+        .collect();
+    if !denied.is_empty() {
+        reasons.push(format!("known phishing domain: {}", denied.into_iter().collect::<Vec<_>>().join(", ")));
+    }
+
+    // Names, symbols and trait values should not be advertising websites
+    let name_text = format!("{} {}", text.name, text.symbol);
+    let name_urls = unknown(find_urls(&name_text));
+    if !name_urls.is_empty() {
+        reasons.push(format!("link in name: {}", name_urls.join(", ")));
+    }
+    let attribute_urls = unknown(find_urls(&text.attributes.join(" ")));
+    if !attribute_urls.is_empty() {
+        reasons.push(format!("link in attributes: {}", attribute_urls.join(", ")));
+    }
+
+    let description_urls = unknown(find_urls(text.description));
+    let keywords = phishing_keywords(&format!("{} {} {}", name_text, text.description, text.attributes.join(" ")));
+    if !keywords.is_empty() && (!name_urls.is_empty() || !attribute_urls.is_empty() || !description_urls.is_empty()) {
+        reasons.push(format!("phishing wording: {}", keywords.join(", ")));
+    }
+
+    let external_allowed = domain_of(text.external_url).is_some_and(|d| listed(&d, &lists.allow));
+    if !external_allowed && !phishing_keywords(text.external_url).is_empty() {
+        reasons.push(format!("suspicious external_url: {}", text.external_url));
+    }
+
+    reasons
+}
+
+// Reasons an asset in a wallet listing looks like spam; empty means it looks legitimate.
+// `airdrop_creators` counts unverified creators across the wallet (see `airdrop_creator_counts`).
+pub fn asset_spam_reasons(asset: &HeliusAsset, airdrop_creators: &HashMap<String, u32>) -> Vec<String> {
+    let mut reasons = Vec::new();
+    let verified_collection = asset.collection().map(|g| g.verified.unwrap_or(true)).unwrap_or(false);
+
+    let metadata = asset.content.as_ref().and_then(|c| c.metadata.as_ref());
+    let attributes = metadata
+        .and_then(|m| m.attributes.as_ref())
+        .into_iter()
+        .flatten()
+        .filter_map(|a| match a.get("value")? {
+            Value::String(s) => Some(s.clone()),
+            _ => None,
+        })
+        .collect();
+    reasons.extend(text_spam_reasons(&SpamText {
+        name: metadata.and_then(|m| m.name.as_deref()).unwrap_or(""),
+        symbol: metadata.and_then(|m| m.symbol.as_deref()).unwrap_or(""),
+        description: metadata.and_then(|m| m.description.as_deref()).unwrap_or(""),
+        external_url: asset.content.as_ref().and_then(|c| c.links.as_ref()).and_then(|l| l.external_url.as_deref()).unwrap_or(""),
+        attributes,
+    }));
+
+    if !verified_collection && asset.creators.iter().all(|c| !c.verified) {
+        let mass = asset.creators.iter().find(|c| {
+            airdrop_creators.get(&c.address).copied().unwrap_or(0) >= MASS_AIRDROP_THRESHOLD
+        });
+        if let Some(creator) = mass {
+            reasons.push(format!("mass-airdrop creator {}", creator.address));
+        }
+    }
+
+    // Plenty of legitimate and legacy mints never got their collection verified, so
+    // on its own this says nothing; it only backs up the signals above
+    if !reasons.is_empty() && asset.collection().is_some() && !verified_collection {
+        reasons.push("unverified collection".to_string());
+    }
+
+    reasons
+}
+
+// How many unverified-collection, unverified-creator assets each creator has in this listing.
+pub fn airdrop_creator_counts(assets: &[HeliusAsset]) -> HashMap<String, u32> {
+    let mut counts = HashMap::new();
+    for asset in assets {
+        let verified_collection = asset.collection().map(|g| g.verified.unwrap_or(true)).unwrap_or(false);
+        if verified_collection || asset.creators.iter().any(|c| c.verified) {
+            continue;
+        }
+        for creator in asset.creators.iter() {
+            *counts.entry(creator.address.clone()).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+// User additions to the built-in allow list, and domains that always mean phishing.
+#[derive(Deserialize, Default)]
+pub struct DomainLists {
+    #[serde(default)]
+    pub allow: Vec<String>,
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+static DOMAINS: OnceLock<DomainLists> = OnceLock::new();
+
+// Read once per run. A broken file is reported and otherwise ignored, since spam
+// labels are a hint, not something to abort a report over.
+pub fn domain_lists() -> &'static DomainLists {
+    DOMAINS.get_or_init(|| {
+        let mut lists = domains_path()
+            .and_then(|path| {
+                let text = std::fs::read_to_string(&path).ok()?;
+                match serde_json::from_str::<DomainLists>(&text) {
+                    Ok(lists) => Some(lists),
+                    Err(e) => {
+                        eprintln!("⚠️  Ignoring {}: {}", path.display(), e);
+                        None
+                    }
+                }
+            })
+            .unwrap_or_default();
+        lists.allow.extend(ALLOWED_DOMAINS.iter().map(|d| d.to_string()));
+        for list in [&mut lists.allow, &mut lists.deny] {
+            for domain in list.iter_mut() {
+                *domain = domain.trim().trim_start_matches("www.").to_ascii_lowercase();
+            }
+        }
+        lists
+    })
+}
+
+fn domains_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("shinobi").join("domains.json"))
+}
+
+// Without OCR: "claim your reward at ..." images are a flat background with
+// high-contrast lettering, so nearly every pixel sits at one of two brightness
+// levels and rows flip between them often. Artwork rarely looks like that.
+pub fn looks_like_text_image(bytes: &[u8]) -> bool {
+    let Ok(img) = image::load_from_memory(bytes) else { return false };
+    let img = img.thumbnail(TEXT_SCAN_WIDTH, TEXT_SCAN_WIDTH).to_luma8();
+    let pixels = img.as_raw();
+    if pixels.len() < 1000 {
+        return false;
+    }
+
+    // Two most common brightness levels, in 16 buckets, and how much they cover
+    let mut histogram = [0usize; 16];
+    for p in pixels.iter() {
+        histogram[(*p / 16) as usize] += 1;
+    }
+    let mut buckets: Vec<(usize, usize)> = histogram.iter().copied().enumerate().collect();
+    buckets.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    let (background, ink) = (buckets[0], buckets[1]);
+    let two_tone = (background.1 + ink.1) as f64 / pixels.len() as f64;
+    let contrast = background.0.abs_diff(ink.0);
+    if two_tone < 0.85 || contrast < 6 || ink.1 * 50 < pixels.len() {
+        return false;
+    }
+
+    // Lettering gives many short runs; a logo or a single shape gives few
+    let width = img.width() as usize;
+    let threshold = ((background.0 + ink.0) * 16 / 2 + 8) as u8;
+    let mut flips = 0;
+    for row in pixels.chunks(width) {
+        flips += row.windows(2).filter(|w| (w[0] < threshold) != (w[1] < threshold)).count();
+    }
+    let flips_per_row = flips as f64 / (pixels.len() / width) as f64;
+    flips_per_row >= 4.0
+}
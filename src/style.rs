@@ -0,0 +1,82 @@
+use std::env;
+use std::io::{stdout, IsTerminal};
+use std::sync::OnceLock;
+use tokio::time::{sleep, Duration};
+
+// How reports should look, decided once at startup from flags and the environment.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OutputStyle {
+    // No emoji, no box art, no banner
+    pub plain: bool,
+    // Only the key facts, one per line
+    pub quiet: bool,
+    // Keep the dramatic pauses
+    pub theatrics: bool,
+    pub color: bool,
+}
+
+static STYLE: OnceLock<OutputStyle> = OnceLock::new();
+
+// Piped output and NO_COLOR (https://no-color.org) turn colours off; piped output is also plain.
+pub fn init(plain: bool, quiet: bool, theatrics: bool) -> OutputStyle {
+    let tty = stdout().is_terminal();
+    let no_color = env::var_os("NO_COLOR").map(|v| !v.is_empty()).unwrap_or(false);
+
+    let style = OutputStyle {
+        plain: plain || !tty,
+        quiet,
+        theatrics: theatrics && tty,
+        color: tty && !no_color,
+    };
+    colored::control::set_override(style.color);
+    let _ = STYLE.set(style);
+    style
+}
+
+pub fn current() -> OutputStyle {
+    STYLE.get().copied().unwrap_or_default()
+}
+
+// Sleep for effect, but only when --theatrics asked for it.
+pub async fn pause(ms: u64) {
+    if current().theatrics {
+        sleep(Duration::from_millis(ms)).await;
+    }
+}
+
+// In plain mode drop emoji and decorative symbols, then tidy the spaces they leave behind.
+pub fn clean(s: &str) -> String {
+    if !current().plain {
+        return s.to_string();
+    }
+    let stripped: String = s.chars().filter(|c| !is_decoration(*c)).collect();
+    let leading = stripped.len() - stripped.trim_start().len();
+    let indent = &stripped[..leading];
+    let body = stripped.trim_start();
+
+    // Collapse the double spaces that used to sit next to an icon
+    let mut out = String::from(indent);
+    let mut last_space = false;
+    for c in body.chars() {
+        if c == ' ' {
+            if !last_space {
+                out.push(c);
+            }
+            last_space = true;
+        } else {
+            out.push(c);
+            last_space = false;
+        }
+    }
+    out.trim_end().to_string()
+}
+
+fn is_decoration(c: char) -> bool {
+    matches!(c as u32,
+        0x1F000..=0x1FAFF   // emoji and pictographs
+        | 0x2600..=0x27BF   // misc symbols, dingbats (⚠ ⛓ ✨ ✓ ❌)
+        | 0x2B00..=0x2BFF   // arrows and stars
+        | 0x25A0..=0x25FF   // geometric shapes (◆ ▸ ▶)
+        | 0xFE0F | 0x200D   // variation selector, zero-width joiner
+    )
+}
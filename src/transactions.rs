@@ -0,0 +1,239 @@
+use anyhow::Result;
+use crate::activity;
+use crate::model::{EnhancedTransaction, Movement, LAMPORTS_PER_SOL};
+use crate::programs::SYSTEM_PROGRAM;
+use crate::rpc;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+
+// Without Helius every transaction is a separate getTransaction call, so sample fewer.
+const RPC_SAMPLE: usize = 50;
+
+// A native SOL movement found in a parsed transaction.
+pub struct SolTransfer {
+    pub from: String,
+    pub to: String,
+    pub lamports: u64,
+}
+
+pub struct TokenMovement {
+    pub mint: String,
+    pub from: String,
+    pub to: String,
+    pub amount: f64,
+}
+
+// getTransaction in jsonParsed form. Null when the node no longer has it.
+pub async fn fetch_parsed(client: &Client, rpc_url: &str, signature: &str) -> Result<Value> {
+    rpc::call(
+        client,
+        rpc_url,
+        "getTransaction",
+        json!([signature, { "encoding": "jsonParsed", "maxSupportedTransactionVersion": 0 }]),
+    )
+    .await
+}
+
+// System program transfers and account creations, top-level and inner (CPI) alike.
+pub fn sol_transfers(tx: &Value) -> Vec<SolTransfer> {
+    let outer = tx
+        .pointer("/transaction/message/instructions")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten();
+    let inner = tx
+        .pointer("/meta/innerInstructions")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|group| group.get("instructions").and_then(Value::as_array))
+        .flatten();
+
+    outer
+        .chain(inner)
+        .filter(|ix| ix.get("programId").and_then(Value::as_str) == Some(SYSTEM_PROGRAM))
+        .filter_map(|ix| {
+            let parsed = ix.get("parsed")?;
+            let info = parsed.get("info")?;
+            let to = match parsed.get("type")?.as_str()? {
+                "transfer" | "transferWithSeed" => info.get("destination")?,
+                "createAccount" | "createAccountWithSeed" => info.get("newAccount")?,
+                _ => return None,
+            };
+            Some(SolTransfer {
+                from: info.get("source")?.as_str()?.to_string(),
+                to: to.as_str()?.to_string(),
+                lamports: info.get("lamports")?.as_u64()?,
+            })
+        })
+        .collect()
+}
+
+// Accounts that signed the transaction; the fee payer comes first.
+pub fn signers(tx: &Value) -> Vec<String> {
+    tx.pointer("/transaction/message/accountKeys")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|key| key.get("signer").and_then(Value::as_bool).unwrap_or(false))
+        .filter_map(|key| key.get("pubkey").and_then(Value::as_str).map(str::to_string))
+        .collect()
+}
+
+// Whether the program is called at all, directly or through another program.
+pub fn invokes_program(tx: &Value, program_id: &str) -> bool {
+    let outer = tx.pointer("/transaction/message/instructions").and_then(Value::as_array).into_iter().flatten();
+    let inner = tx
+        .pointer("/meta/innerInstructions")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|group| group.get("instructions").and_then(Value::as_array))
+        .flatten();
+    outer.chain(inner).any(|ix| ix.get("programId").and_then(Value::as_str) == Some(program_id))
+}
+
+pub fn writable_accounts(tx: &Value) -> Vec<String> {
+    tx.pointer("/transaction/message/accountKeys")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|key| key.get("writable").and_then(Value::as_bool).unwrap_or(false))
+        .filter_map(|key| key.get("pubkey").and_then(Value::as_str).map(str::to_string))
+        .collect()
+}
+
+pub fn block_time(tx: &Value) -> Option<i64> {
+    tx.get("blockTime").and_then(Value::as_i64)
+}
+
+// Token movements read off the pre/post token balances: for each mint, whoever
+// lost tokens sent them to whoever gained. Owners are wallets, not token accounts.
+pub fn token_movements(tx: &Value) -> Vec<TokenMovement> {
+    let mut deltas: HashMap<(String, String), f64> = HashMap::new();
+    for (key, sign) in [("/meta/preTokenBalances", -1.0), ("/meta/postTokenBalances", 1.0)] {
+        for balance in tx.pointer(key).and_then(Value::as_array).into_iter().flatten() {
+            let (Some(mint), Some(owner)) = (
+                balance.get("mint").and_then(Value::as_str),
+                balance.get("owner").and_then(Value::as_str),
+            ) else {
+                continue;
+            };
+            let amount = balance
+                .pointer("/uiTokenAmount/uiAmountString")
+                .and_then(Value::as_str)
+                .and_then(|a| a.parse::<f64>().ok())
+                .unwrap_or(0.0);
+            *deltas.entry((mint.to_string(), owner.to_string())).or_default() += sign * amount;
+        }
+    }
+
+    let mut movements = Vec::new();
+    let mints: HashSet<&String> = deltas.keys().map(|(m, _)| m).collect();
+    for mint in mints {
+        let senders: Vec<(&String, f64)> = deltas.iter()
+            .filter(|((m, _), d)| m == mint && **d < 0.0)
+            .map(|((_, o), d)| (o, -*d))
+            .collect();
+        let receivers: Vec<(&String, f64)> = deltas.iter()
+            .filter(|((m, _), d)| m == mint && **d > 0.0)
+            .map(|((_, o), d)| (o, *d))
+            .collect();
+        for (from, sent) in &senders {
+            for (to, received) in &receivers {
+                movements.push(TokenMovement {
+                    mint: mint.clone(),
+                    from: (*from).clone(),
+                    to: (*to).clone(),
+                    amount: sent.min(*received),
+                });
+            }
+        }
+    }
+    movements
+}
+
+// Recent SOL and token movements touching `wallet`. Helius enhanced transactions when
+// HELIUS_API_KEY is set, otherwise a smaller sample parsed from plain RPC.
+// Transactions that fail to load are skipped and counted in the returned warnings.
+pub async fn recent_movements(client: &Client, rpc_url: &str, wallet: &str, max: usize) -> Result<(Vec<Movement>, Vec<String>)> {
+    let mut movements = Vec::new();
+    let mut warnings = Vec::new();
+    if let Some(key) = rpc::helius_api_key() {
+        let txs = activity::fetch_enhanced(client, &key, wallet, max).await?;
+        movements.extend(txs.iter().flat_map(enhanced_movements));
+        return Ok((touching(movements, wallet), warnings));
+    }
+
+    let (signatures, _) = activity::fetch_signatures(client, rpc_url, wallet, max.min(RPC_SAMPLE)).await?;
+    let mut failed = 0;
+    let mut last_error = None;
+    for sig in signatures.iter().filter(|s| s.err.is_none()) {
+        let tx = match fetch_parsed(client, rpc_url, &sig.signature).await {
+            Ok(tx) => tx,
+            Err(e) => {
+                failed += 1;
+                last_error = Some(e);
+                continue;
+            }
+        };
+        let timestamp = block_time(&tx).or(sig.block_time);
+        for t in sol_transfers(&tx) {
+            movements.push(Movement {
+                signature: sig.signature.clone(),
+                timestamp,
+                from: t.from,
+                to: t.to,
+                mint: None,
+                amount: t.lamports as f64 / LAMPORTS_PER_SOL,
+            });
+        }
+        for t in token_movements(&tx) {
+            movements.push(Movement {
+                signature: sig.signature.clone(),
+                timestamp,
+                from: t.from,
+                to: t.to,
+                mint: Some(t.mint),
+                amount: t.amount,
+            });
+        }
+    }
+    if let Some(e) = last_error {
+        warnings.push(format!("{} transaction(s) of {} could not be fetched and were skipped (last error: {})", failed, wallet, e));
+    }
+    Ok((touching(movements, wallet), warnings))
+}
+
+fn touching(movements: Vec<Movement>, wallet: &str) -> Vec<Movement> {
+    movements
+        .into_iter()
+        .filter(|m| m.from == wallet || m.to == wallet)
+        .collect()
+}
+
+fn enhanced_movements(tx: &EnhancedTransaction) -> Vec<Movement> {
+    let timestamp = Some(tx.timestamp).filter(|t| *t > 0);
+    let native = tx.native_transfers.iter().filter_map(|t| {
+        Some(Movement {
+            signature: tx.signature.clone(),
+            timestamp,
+            from: t.from_user_account.clone()?,
+            to: t.to_user_account.clone()?,
+            mint: None,
+            amount: t.amount as f64 / LAMPORTS_PER_SOL,
+        })
+    });
+    let tokens = tx.token_transfers.iter().filter_map(|t| {
+        Some(Movement {
+            signature: tx.signature.clone(),
+            timestamp,
+            from: t.from_user_account.clone()?,
+            to: t.to_user_account.clone()?,
+            mint: Some(t.mint.clone()),
+            amount: t.token_amount,
+        })
+    });
+    native.chain(tokens).collect()
+}